@@ -0,0 +1,58 @@
+// Synthetic file lists shaped like real folders: camera imports, downloads with copy
+// markers, numbered documents and a few folders deep, generated from a fixed seed so runs
+// compare like with like
+
+use file_rename_plus::types::FileEntry;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const STEMS: [&str; 8] = [
+    "Report",
+    "invoice",
+    "Meeting notes",
+    "holiday",
+    "scan",
+    "résumé",
+    "Screenshot 2024-05-17 at 10.42.13",
+    "track",
+];
+const EXTENSIONS: [&str; 8] = ["jpg", "JPG", "png", "pdf", "docx", "mp3", "tar.gz", "txt"];
+const FOLDERS: [&str; 4] = ["Photos", "Photos/2023", "Downloads", "Documents/Work"];
+
+// Small linear congruential generator; quality doesn't matter, repeatability does
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+// `count` entries under /bench, in generation order like an unsorted folder listing
+pub fn corpus(count: usize) -> Vec<FileEntry> {
+    let mut rng = Lcg(0x5eed);
+    (0..count)
+        .map(|i| {
+            let ext = EXTENSIONS[rng.next(EXTENSIONS.len())];
+            let name = match rng.next(5) {
+                0 => format!("IMG_{:04}.{}", i % 10_000, ext),
+                1 => format!("DSC{:05}.{}", rng.next(100_000), ext),
+                2 => format!("{} ({}).{}", STEMS[rng.next(STEMS.len())], i, ext),
+                3 => format!("{} - Copy {}.{}", STEMS[rng.next(STEMS.len())], i, ext),
+                _ => format!("{}_{}_v{}.{}", STEMS[rng.next(STEMS.len())], i, i % 7, ext),
+            };
+            let folder = FOLDERS[rng.next(FOLDERS.len())];
+            FileEntry {
+                path: PathBuf::from("/bench").join(folder).join(&name),
+                name: Arc::new(name),
+                identity: None,
+                pinned: false,
+                added: i as u64,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,118 @@
+// Rename engine benchmarks. Save a baseline before a change and compare after it:
+//   cargo bench --bench engine -- --save-baseline before
+//   cargo bench --bench engine -- --baseline before
+
+mod corpus;
+
+use corpus::corpus;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use file_rename_plus::file_ops::sort_files;
+use file_rename_plus::rename::{
+    apply_find_replace, apply_iteration_numbering, detect_conflicts, FindOptions, NumberingOptions,
+};
+use file_rename_plus::types::{Counter, SortOrder};
+use std::collections::HashMap;
+use std::hint::black_box;
+
+fn sorting(c: &mut Criterion) {
+    let files = corpus(50_000);
+    for (label, order) in [
+        ("sort_natural_50k", SortOrder::Natural),
+        ("sort_lexicographic_50k", SortOrder::Lexicographic),
+    ] {
+        c.bench_function(label, |b| {
+            b.iter_batched_ref(
+                || files.clone(),
+                |files| sort_files(files, order),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn find_replace(c: &mut Criterion) {
+    let files = corpus(10_000);
+    let no_hashes = HashMap::new();
+    let plain = FindOptions {
+        case_sensitive: true,
+        ..FindOptions::default()
+    };
+    let regex = FindOptions {
+        use_regex: true,
+        ..FindOptions::default()
+    };
+    c.bench_function("find_replace_plain_10k", |b| {
+        b.iter(|| {
+            apply_find_replace(
+                black_box(&files),
+                "IMG_",
+                "photo_",
+                "",
+                plain,
+                &no_hashes,
+                None,
+            )
+        })
+    });
+    c.bench_function("find_replace_regex_10k", |b| {
+        b.iter(|| {
+            apply_find_replace(
+                black_box(&files),
+                r"^(.+?)(?:\s+-\s+copy\s+\d+|\s*\(\d+\))(\.[^.]*)$",
+                "$1$2",
+                "",
+                regex,
+                &no_hashes,
+                None,
+            )
+        })
+    });
+}
+
+fn iteration(c: &mut Criterion) {
+    let files = corpus(10_000);
+    let options = NumberingOptions {
+        start_number: 1,
+        padding: 4,
+        counters: vec![
+            Counter::default(),
+            Counter {
+                start: 100,
+                step: 10,
+                padding: 5,
+            },
+        ],
+        ..NumberingOptions::default()
+    };
+    c.bench_function("iteration_counters_10k", |b| {
+        b.iter(|| apply_iteration_numbering(black_box(&files), "set_{n}_{n1}_{n2}", &options, None))
+    });
+}
+
+fn conflicts(c: &mut Criterion) {
+    let files = corpus(10_000);
+    // Dropping every digit collides most names with one another
+    let previews = apply_find_replace(
+        &files,
+        r"\d",
+        "",
+        "",
+        FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        },
+        &HashMap::new(),
+        None,
+    )
+    .unwrap_or_default();
+    c.bench_function("detect_conflicts_10k", |b| {
+        b.iter_batched_ref(
+            || previews.clone(),
+            |previews| detect_conflicts(previews),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, sorting, find_replace, iteration, conflicts);
+criterion_main!(benches);
@@ -0,0 +1,191 @@
+// Row context menu: the actions it offers, and a wrapper that reports right clicks with
+// the cursor position so the menu can open where the click was
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::widget::text_input;
+use iced::{event, mouse, Element, Event, Length, Point, Rectangle, Size, Vector};
+
+/// Action offered by a row's context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowAction {
+    Reveal,
+    Open,
+    CopyOldName,
+    CopyNewName,
+    CopyPath,
+    // Leaves the file out of the preview and rename, or takes it back in
+    ToggleExclude,
+    EditNewName,
+    Remove,
+}
+
+impl RowAction {
+    pub fn label(self, excluded: bool) -> &'static str {
+        match self {
+            RowAction::Reveal => "Reveal in File Manager",
+            RowAction::Open => "Open File",
+            RowAction::CopyOldName => "Copy Old Name",
+            RowAction::CopyNewName => "Copy New Name",
+            RowAction::CopyPath => "Copy Full Path",
+            RowAction::ToggleExclude if excluded => "Include in Rename",
+            RowAction::ToggleExclude => "Exclude from Rename",
+            RowAction::EditNewName => "Edit New Name",
+            RowAction::Remove => "Remove from List",
+        }
+    }
+}
+
+// Input for the new name typed after "Edit New Name"
+pub fn edit_input_id() -> text_input::Id {
+    text_input::Id::new("row_menu_edit_input")
+}
+
+// Wraps `content` so a right click on it publishes `on_right_press` with the cursor
+// position, in the coordinates `content` is laid out in. Inside a scrollable those are
+// shifted by the scroll offset.
+pub fn right_click_area<'a, Message: 'a, Theme: 'a, Renderer: renderer::Renderer + 'a>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+    on_right_press: impl Fn(Point) -> Message + 'a,
+) -> Element<'a, Message, Theme, Renderer> {
+    Element::new(RightClickArea {
+        content: content.into(),
+        on_right_press: Box::new(on_right_press),
+    })
+}
+
+struct RightClickArea<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    on_right_press: Box<dyn Fn(Point) -> Message + 'a>,
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for RightClickArea<'_, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        let status = self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event.clone(),
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+        if status == event::Status::Captured {
+            return status;
+        }
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                match cursor.position_over(layout.bounds()) {
+                    Some(position) => {
+                        shell.publish((self.on_right_press)(position));
+                        event::Status::Captured
+                    }
+                    None => event::Status::Ignored,
+                }
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer, translation)
+    }
+}
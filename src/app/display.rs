@@ -0,0 +1,294 @@
+// Display helpers: fitting long names into fixed-width panes, list row styling and
+// the preview folder tree
+
+use super::Message;
+use crate::theme::{FONT_SM, SPACING_LG, SPACING_MD, SPACING_SM};
+use crate::types::RenamePreview;
+use iced::widget::{button, container, row, text, tooltip};
+use iced::{border, Color, Element, Theme};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Approximate glyph width relative to font size for proportional UI fonts
+const CHAR_WIDTH_RATIO: f32 = 0.6;
+const ELLIPSIS: char = '…';
+
+// Character budget for one of the two side-by-side list panes
+pub fn pane_char_budget(window_width: f32) -> usize {
+    let pane_width = (window_width - f32::from(SPACING_LG) * 2.0 - f32::from(SPACING_MD)) / 2.0;
+    chars_for_width(pane_width)
+}
+
+// Character budget for a full-width line such as the status bar
+pub fn line_char_budget(window_width: f32) -> usize {
+    chars_for_width(window_width - f32::from(SPACING_LG) * 2.0 - f32::from(SPACING_MD) * 2.0)
+}
+
+fn chars_for_width(width: f32) -> usize {
+    (width / (FONT_SM * CHAR_WIDTH_RATIO)).max(8.0) as usize
+}
+
+// Truncates in the middle keeping the start and the extension visible
+pub fn elide_middle(name: &str, max_chars: usize) -> Option<String> {
+    let chars: Vec<char> = name.chars().collect();
+    if chars.len() <= max_chars || max_chars < 3 {
+        return None;
+    }
+
+    let available = max_chars - 1;
+    let ext_len = name
+        .rfind('.')
+        .filter(|&i| i > 0)
+        .map(|i| name[i..].chars().count())
+        .unwrap_or(0);
+    let tail_len = (available / 3).max(ext_len).min(available - 1);
+    let head_len = available - tail_len;
+
+    let mut elided: String = chars[..head_len].iter().collect();
+    elided.push(ELLIPSIS);
+    elided.extend(&chars[chars.len() - tail_len..]);
+    Some(elided)
+}
+
+// Renders a possibly elided name, showing the full text in a tooltip when shortened
+pub fn fitted_text<'a>(
+    full: &'a str,
+    max_chars: usize,
+    color: Option<Color>,
+) -> Element<'a, Message> {
+    match elide_middle(full, max_chars) {
+        Some(short) => tooltip(
+            text(short).size(FONT_SM).color_maybe(color),
+            container(text(full).size(FONT_SM))
+                .padding(SPACING_SM)
+                .style(container::rounded_box),
+            tooltip::Position::Bottom,
+        )
+        .into(),
+        None => text(full).size(FONT_SM).color_maybe(color).into(),
+    }
+}
+
+// Caption above an input, with a "Reset" link while the value differs from its default
+pub fn field_label<'a>(
+    label: &'a str,
+    value: &str,
+    default: &str,
+    reset: Message,
+) -> Element<'a, Message> {
+    row![text(label).size(FONT_SM)]
+        .push_maybe((value != default).then(|| {
+            button(text("Reset").size(FONT_SM))
+                .padding(0)
+                .style(button::text)
+                .on_press(reset)
+        }))
+        .spacing(SPACING_SM)
+        .into()
+}
+
+// Short inline marker with an explanatory tooltip
+pub fn badge<'a>(label: &'a str, detail: String, color: Color) -> Element<'a, Message> {
+    tooltip(
+        text(label).size(FONT_SM).color(color),
+        container(text(detail).size(FONT_SM))
+            .padding(SPACING_SM)
+            .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+// Button-like row style; the drop target is highlighted while dragging
+pub fn list_row_style(selected: bool, drop_target: bool) -> impl Fn(&Theme) -> container::Style {
+    move |theme| {
+        let palette = theme.extended_palette();
+        let pair = if selected {
+            palette.primary.base
+        } else if drop_target {
+            palette.primary.weak
+        } else {
+            palette.secondary.base
+        };
+        container::Style {
+            background: Some(pair.color.into()),
+            text_color: Some(pair.text),
+            border: border::rounded(2),
+            ..container::Style::default()
+        }
+    }
+}
+
+// Horizontal indent per level of the preview tree, in characters
+pub const TREE_INDENT_CHARS: usize = 3;
+
+// Width of `chars` characters at the list font size
+pub fn indent_width(chars: usize) -> f32 {
+    chars as f32 * FONT_SM * CHAR_WIDTH_RATIO
+}
+
+/// One line of the preview tree: a folder heading or a file under it
+pub enum TreeRow {
+    Folder {
+        // Full path, used to collapse the folder
+        path: PathBuf,
+        // Path below the parent heading; a chain of folders without files is one heading
+        label: String,
+        depth: usize,
+        // Files and conflicts in the folder and everything below it
+        files: usize,
+        conflicts: usize,
+    },
+    File {
+        // Index into the preview list
+        index: usize,
+        depth: usize,
+    },
+}
+
+impl TreeRow {
+    pub fn depth(&self) -> usize {
+        match self {
+            TreeRow::Folder { depth, .. } | TreeRow::File { depth, .. } => *depth,
+        }
+    }
+}
+
+// Folder in the preview tree, with its files and subfolders in order of first appearance
+struct TreeNode {
+    path: PathBuf,
+    rows: Vec<usize>,
+    conflicts: usize,
+    children: Vec<usize>,
+}
+
+// Nests previews under their folders, starting from the deepest folder they all share.
+// Within a folder its files come first, then its subfolders. Returns rows in display order.
+pub fn preview_tree(previews: &[RenamePreview]) -> Vec<TreeRow> {
+    let groups = group_by_folder(previews);
+    let Some(first) = groups.first() else {
+        return Vec::new();
+    };
+    let root = groups[1..]
+        .iter()
+        .fold(first.folder.clone(), |shared, group| {
+            shared
+                .components()
+                .zip(group.folder.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        });
+
+    let mut nodes = vec![TreeNode {
+        path: root.clone(),
+        rows: Vec::new(),
+        conflicts: 0,
+        children: Vec::new(),
+    }];
+    for group in groups {
+        let mut node = 0;
+        let relative = group.folder.strip_prefix(&root).unwrap_or(Path::new(""));
+        for component in relative.components() {
+            let path = nodes[node].path.join(component);
+            node = match nodes[node]
+                .children
+                .iter()
+                .find(|&&c| nodes[c].path == path)
+            {
+                Some(&child) => child,
+                None => {
+                    nodes.push(TreeNode {
+                        path,
+                        rows: Vec::new(),
+                        conflicts: 0,
+                        children: Vec::new(),
+                    });
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    child
+                }
+            };
+        }
+        nodes[node].rows = group.rows;
+        nodes[node].conflicts = group.conflicts;
+    }
+
+    let mut rows = Vec::with_capacity(previews.len() + nodes.len());
+    emit_tree(&nodes, 0, root.display().to_string(), 0, &mut rows);
+    rows
+}
+
+// Appends the rows for `node` and everything below it; returns their file and conflict count
+fn emit_tree(
+    nodes: &[TreeNode],
+    mut node: usize,
+    mut label: String,
+    depth: usize,
+    rows: &mut Vec<TreeRow>,
+) -> (usize, usize) {
+    // Folders holding nothing but one subfolder share its heading: `a/b/c`
+    while nodes[node].rows.is_empty() && nodes[node].children.len() == 1 {
+        node = nodes[node].children[0];
+        let name = nodes[node].path.file_name().unwrap_or_default();
+        label = Path::new(&label).join(name).display().to_string();
+    }
+    let heading = rows.len();
+    rows.push(TreeRow::Folder {
+        path: nodes[node].path.clone(),
+        label,
+        depth,
+        files: 0,
+        conflicts: 0,
+    });
+    rows.extend(nodes[node].rows.iter().map(|&index| TreeRow::File {
+        index,
+        depth: depth + 1,
+    }));
+    let mut totals = (nodes[node].rows.len(), nodes[node].conflicts);
+    for &child in &nodes[node].children {
+        let name = nodes[child].path.file_name().unwrap_or_default();
+        let label = name.to_string_lossy().to_string();
+        let (files, conflicts) = emit_tree(nodes, child, label, depth + 1, rows);
+        totals.0 += files;
+        totals.1 += conflicts;
+    }
+    if let TreeRow::Folder {
+        files, conflicts, ..
+    } = &mut rows[heading]
+    {
+        (*files, *conflicts) = totals;
+    }
+    totals
+}
+
+/// Preview rows sharing a parent directory
+struct PreviewGroup {
+    folder: PathBuf,
+    // Indices into the preview list, in list order
+    rows: Vec<usize>,
+    conflicts: usize,
+}
+
+// Groups previews by parent directory, ordered by each directory's first appearance
+fn group_by_folder(previews: &[RenamePreview]) -> Vec<PreviewGroup> {
+    let mut groups: Vec<PreviewGroup> = Vec::new();
+    let mut index: HashMap<&Path, usize> = HashMap::new();
+    for (i, preview) in previews.iter().enumerate() {
+        let folder = preview.original_path.parent().unwrap_or(Path::new(""));
+        let slot = *index.entry(folder).or_insert_with(|| {
+            groups.push(PreviewGroup {
+                folder: folder.to_path_buf(),
+                rows: Vec::new(),
+                conflicts: 0,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[slot];
+        group.rows.push(i);
+        if !preview.conflicts.is_empty() {
+            group.conflicts += 1;
+        }
+    }
+    groups
+}
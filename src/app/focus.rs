@@ -0,0 +1,462 @@
+// Keyboard focus model: logical Tab order and focus ring styling
+
+use crate::types::AppMode;
+use iced::widget::{container, text_input};
+use iced::{border, Color, Task, Theme};
+
+const FOCUS_RING_WIDTH: f32 = 2.0;
+
+/// Interactive widgets reachable with Tab / Shift+Tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusTarget {
+    RecentFolderPicker,
+    RemoveCopyMarkers,
+    FolderPrefix,
+    WebSafeNames,
+    ThemeToggle,
+    SettingsToggle,
+    ModePicker,
+    AddFolder,
+    ImportPathList,
+    SortByName,
+    AnalyzeNames,
+    ClearFiles,
+    FileList,
+    MoveUp,
+    MoveDown,
+    PinFile,
+    RemoveFile,
+    ExtensionFilterInput,
+    ExtensionGroups,
+    AccessFilterPicker,
+    PreviewTreeToggle,
+    PreviewFilters,
+    ResultingListingToggle,
+    AlignPreviewToggle,
+    PasteNames,
+    ScriptFormatPicker,
+    ExportScript,
+    PreviewList,
+    FindInput,
+    RegexSnippetPicker,
+    SwapFindReplace,
+    ReplaceInput,
+    SkipIfContainsInput,
+    RegexToggle,
+    CaseToggle,
+    LowercaseToggle,
+    WholeWordToggle,
+    CaseLocalePicker,
+    ShowCapturesToggle,
+    TermsModeToggle,
+    TermPresetPicker,
+    TermPresetNameInput,
+    SaveTermPreset,
+    DeleteTermPreset,
+    ToggleFolderPreset,
+    ToggleFolderNumbering,
+    UndoFolderPreset,
+    TemplateInput,
+    ExtensionTemplatesInput,
+    NewExtensionInput,
+    StartInput,
+    PaddingInput,
+    NumberGroupingPicker,
+    MatchWidthToggle,
+    FitPaddingToggle,
+    SkipExistingToggle,
+    KeepNumberedToggle,
+    CompoundExtensionsToggle,
+    ReverseNumberingToggle,
+    ContinueNumberingToggle,
+    ApplyTemplateSuggestion,
+    DismissTemplateSuggestion,
+    ResetContinuation,
+    AddCounter,
+    AddRule,
+    ConventionPicker,
+    ConflictPolicyPicker,
+    SelectionOnlyToggle,
+    Execute,
+    RunWhenIdle,
+    PreviewBudgetInput,
+    OrderedCommitToggle,
+    PreviewArrowPicker,
+    MaxNameWidthInput,
+    PerFolderLimitInput,
+    ConfirmThresholdInput,
+    IdleQuietInput,
+    SortOrderPicker,
+    TruncateNamesToggle,
+    ImportKeepOrderToggle,
+    NumberDuplicatesToggle,
+    NotifyToggle,
+    ProbeDirectoriesToggle,
+    StrictCaseToggle,
+    VerifyRenamesToggle,
+    RecordOriginalNamesToggle,
+    OpenDataFolder,
+    BackUpSettings,
+    RestoreSettings,
+    ProtectedRootsInput,
+    ExtensionGroupsInput,
+    FolderSeparatorInput,
+    PostRenameHookInput,
+    CancelLargeBatch,
+    ConfirmLargeBatch,
+    CancelIdleNewFiles,
+    ConfirmIdleNewFiles,
+    CancelClearFiles,
+    ConfirmClearFiles,
+    CopyStatus,
+    RestoreClearedFiles,
+    CancelProtected,
+    AcknowledgeProtected,
+    ConfirmProtected,
+    CancelConflicts,
+    ConfirmConflicts,
+    DismissFailures,
+    RetryFailed,
+    ApplySuggestion(usize),
+    CloseAnalysis,
+    VerifyClipboard,
+    CloseNameCheck,
+    ErrorDetailsToggle,
+    // Action the error panel suggests for the kind of failure
+    ErrorSuggestion,
+    CopyErrorDetails,
+    DismissErrorDetails,
+}
+
+/// Panel currently filling the options area below the lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionsPanel {
+    Mode(AppMode),
+    // Find/replace with the "match any of" term builder
+    FindAnyOf,
+    Settings,
+    // More files would be renamed than the confirmation threshold allows without asking
+    LargeBatch,
+    // Files that appeared while an idle run waited, awaiting confirmation to include them
+    IdleNewFiles,
+    ProtectedWarning,
+    // Ctrl+Shift+X asks before clearing the file list
+    ClearFiles,
+    // Existing files taken by new names, each with a skip/trash/overwrite choice
+    Conflicts,
+    RenameFailures,
+    // Name analysis with the given number of suggestions
+    Analysis(usize),
+    // The preview's new names compared with a pasted list of expected ones
+    NameCheck,
+}
+
+// Focus order follows the layout: header, file list and its controls, preview, mode options, execute
+const HEADER_AND_LISTS: [FocusTarget; 29] = [
+    FocusTarget::RecentFolderPicker,
+    FocusTarget::RemoveCopyMarkers,
+    FocusTarget::FolderPrefix,
+    FocusTarget::WebSafeNames,
+    FocusTarget::ThemeToggle,
+    FocusTarget::SettingsToggle,
+    FocusTarget::ModePicker,
+    FocusTarget::AddFolder,
+    FocusTarget::ImportPathList,
+    FocusTarget::SortByName,
+    FocusTarget::AnalyzeNames,
+    FocusTarget::ClearFiles,
+    FocusTarget::FileList,
+    FocusTarget::MoveUp,
+    FocusTarget::MoveDown,
+    FocusTarget::PinFile,
+    FocusTarget::RemoveFile,
+    FocusTarget::ExtensionFilterInput,
+    FocusTarget::ExtensionGroups,
+    FocusTarget::AccessFilterPicker,
+    FocusTarget::PreviewTreeToggle,
+    FocusTarget::ResultingListingToggle,
+    FocusTarget::AlignPreviewToggle,
+    FocusTarget::PasteNames,
+    FocusTarget::VerifyClipboard,
+    FocusTarget::ScriptFormatPicker,
+    FocusTarget::ExportScript,
+    FocusTarget::PreviewFilters,
+    FocusTarget::PreviewList,
+];
+
+const FIND_REPLACE_OPTIONS: [FocusTarget; 16] = [
+    FocusTarget::FindInput,
+    FocusTarget::RegexSnippetPicker,
+    FocusTarget::SwapFindReplace,
+    FocusTarget::ReplaceInput,
+    FocusTarget::SkipIfContainsInput,
+    FocusTarget::RegexToggle,
+    FocusTarget::TermsModeToggle,
+    FocusTarget::WholeWordToggle,
+    FocusTarget::CaseToggle,
+    FocusTarget::LowercaseToggle,
+    FocusTarget::CaseLocalePicker,
+    FocusTarget::ShowCapturesToggle,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+// The find input adds terms here; presets sit below the term chips
+const FIND_ANY_OF_OPTIONS: [FocusTarget; 18] = [
+    FocusTarget::FindInput,
+    FocusTarget::ReplaceInput,
+    FocusTarget::SkipIfContainsInput,
+    FocusTarget::TermsModeToggle,
+    FocusTarget::WholeWordToggle,
+    FocusTarget::CaseToggle,
+    FocusTarget::LowercaseToggle,
+    FocusTarget::CaseLocalePicker,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+    FocusTarget::TermPresetPicker,
+    FocusTarget::TermPresetNameInput,
+    FocusTarget::SaveTermPreset,
+    FocusTarget::DeleteTermPreset,
+    FocusTarget::ToggleFolderPreset,
+    FocusTarget::UndoFolderPreset,
+];
+
+const ITERATION_OPTIONS: [FocusTarget; 21] = [
+    FocusTarget::TemplateInput,
+    FocusTarget::StartInput,
+    FocusTarget::PaddingInput,
+    FocusTarget::NumberGroupingPicker,
+    FocusTarget::MatchWidthToggle,
+    FocusTarget::FitPaddingToggle,
+    FocusTarget::SkipExistingToggle,
+    FocusTarget::KeepNumberedToggle,
+    FocusTarget::CompoundExtensionsToggle,
+    FocusTarget::ReverseNumberingToggle,
+    FocusTarget::ContinueNumberingToggle,
+    FocusTarget::ResetContinuation,
+    FocusTarget::ApplyTemplateSuggestion,
+    FocusTarget::DismissTemplateSuggestion,
+    FocusTarget::UndoFolderPreset,
+    FocusTarget::ToggleFolderNumbering,
+    FocusTarget::AddCounter,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+const CONVENTION_OPTIONS: [FocusTarget; 5] = [
+    FocusTarget::ConventionPicker,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+const BY_EXTENSION_OPTIONS: [FocusTarget; 7] = [
+    FocusTarget::ExtensionTemplatesInput,
+    FocusTarget::StartInput,
+    FocusTarget::PaddingInput,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+const CHANGE_EXTENSION_OPTIONS: [FocusTarget; 6] = [
+    FocusTarget::NewExtensionInput,
+    FocusTarget::CompoundExtensionsToggle,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+const RULES_OPTIONS: [FocusTarget; 5] = [
+    FocusTarget::AddRule,
+    FocusTarget::ConflictPolicyPicker,
+    FocusTarget::SelectionOnlyToggle,
+    FocusTarget::Execute,
+    FocusTarget::RunWhenIdle,
+];
+
+// The settings panel replaces the mode options while open
+const SETTINGS_OPTIONS: [FocusTarget; 23] = [
+    FocusTarget::PreviewBudgetInput,
+    FocusTarget::PreviewArrowPicker,
+    FocusTarget::MaxNameWidthInput,
+    FocusTarget::PerFolderLimitInput,
+    FocusTarget::ConfirmThresholdInput,
+    FocusTarget::IdleQuietInput,
+    FocusTarget::SortOrderPicker,
+    FocusTarget::ProtectedRootsInput,
+    FocusTarget::ExtensionGroupsInput,
+    FocusTarget::FolderSeparatorInput,
+    FocusTarget::PostRenameHookInput,
+    FocusTarget::OrderedCommitToggle,
+    FocusTarget::ImportKeepOrderToggle,
+    FocusTarget::NumberDuplicatesToggle,
+    FocusTarget::StrictCaseToggle,
+    FocusTarget::TruncateNamesToggle,
+    FocusTarget::NotifyToggle,
+    FocusTarget::ProbeDirectoriesToggle,
+    FocusTarget::VerifyRenamesToggle,
+    FocusTarget::RecordOriginalNamesToggle,
+    FocusTarget::OpenDataFolder,
+    FocusTarget::BackUpSettings,
+    FocusTarget::RestoreSettings,
+];
+
+// Shown instead of the options while a rename over the confirmation threshold awaits
+// confirmation
+const LARGE_BATCH_OPTIONS: [FocusTarget; 2] = [
+    FocusTarget::CancelLargeBatch,
+    FocusTarget::ConfirmLargeBatch,
+];
+
+// Shown instead of the options while new files found by an idle run await confirmation
+const IDLE_NEW_FILES_OPTIONS: [FocusTarget; 2] = [
+    FocusTarget::CancelIdleNewFiles,
+    FocusTarget::ConfirmIdleNewFiles,
+];
+
+// Shown instead of the options while clearing the list by shortcut awaits confirmation
+const CLEAR_FILES_OPTIONS: [FocusTarget; 2] = [
+    FocusTarget::CancelClearFiles,
+    FocusTarget::ConfirmClearFiles,
+];
+
+// Shown instead of the options while a protected-folder rename awaits confirmation
+const PROTECTED_WARNING_OPTIONS: [FocusTarget; 3] = [
+    FocusTarget::CancelProtected,
+    FocusTarget::AcknowledgeProtected,
+    FocusTarget::ConfirmProtected,
+];
+
+// Shown instead of the options while the conflict choices await confirmation
+const CONFLICT_OPTIONS: [FocusTarget; 2] =
+    [FocusTarget::CancelConflicts, FocusTarget::ConfirmConflicts];
+
+// Shown after a rename in which some files failed, until retried successfully or dismissed
+const RENAME_FAILURES_OPTIONS: [FocusTarget; 2] =
+    [FocusTarget::DismissFailures, FocusTarget::RetryFailed];
+
+impl FocusTarget {
+    // Returns the full Tab order for the given options panel, followed by the
+    // status line targets currently shown
+    pub fn order(panel: OptionsPanel, status: &[FocusTarget]) -> Vec<FocusTarget> {
+        let analysis: Vec<FocusTarget>;
+        let options: &[FocusTarget] = match panel {
+            OptionsPanel::Mode(AppMode::FindReplace) => &FIND_REPLACE_OPTIONS,
+            OptionsPanel::Mode(AppMode::Iteration) => &ITERATION_OPTIONS,
+            OptionsPanel::Mode(AppMode::Convention) => &CONVENTION_OPTIONS,
+            OptionsPanel::Mode(AppMode::ByExtension) => &BY_EXTENSION_OPTIONS,
+            OptionsPanel::Mode(AppMode::ChangeExtension) => &CHANGE_EXTENSION_OPTIONS,
+            OptionsPanel::Mode(AppMode::Rules) => &RULES_OPTIONS,
+            OptionsPanel::FindAnyOf => &FIND_ANY_OF_OPTIONS,
+            OptionsPanel::Settings => &SETTINGS_OPTIONS,
+            OptionsPanel::LargeBatch => &LARGE_BATCH_OPTIONS,
+            OptionsPanel::IdleNewFiles => &IDLE_NEW_FILES_OPTIONS,
+            OptionsPanel::ClearFiles => &CLEAR_FILES_OPTIONS,
+            OptionsPanel::ProtectedWarning => &PROTECTED_WARNING_OPTIONS,
+            OptionsPanel::Conflicts => &CONFLICT_OPTIONS,
+            OptionsPanel::RenameFailures => &RENAME_FAILURES_OPTIONS,
+            OptionsPanel::NameCheck => &[FocusTarget::CloseNameCheck],
+            // One Apply button per suggestion, then Close
+            OptionsPanel::Analysis(suggestions) => {
+                analysis = (0..suggestions)
+                    .map(FocusTarget::ApplySuggestion)
+                    .chain([FocusTarget::CloseAnalysis])
+                    .collect();
+                &analysis
+            }
+        };
+        HEADER_AND_LISTS
+            .iter()
+            .chain(options)
+            .chain(status)
+            .copied()
+            .collect()
+    }
+
+    // Next (or previous) target in Tab order, wrapping around
+    pub fn cycle(
+        current: Option<FocusTarget>,
+        panel: OptionsPanel,
+        status: &[FocusTarget],
+        forward: bool,
+    ) -> FocusTarget {
+        let order = Self::order(panel, status);
+        let len = order.len();
+        let position = current.and_then(|c| order.iter().position(|&t| t == c));
+        let index = match (position, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        order[index]
+    }
+
+    // Widget id for text input targets, used to move iced's caret focus
+    pub fn input_id(self) -> Option<text_input::Id> {
+        let id = match self {
+            FocusTarget::FindInput => "find_input",
+            FocusTarget::ReplaceInput => "replace_input",
+            FocusTarget::SkipIfContainsInput => "skip_if_contains_input",
+            FocusTarget::TemplateInput => "template_input",
+            FocusTarget::ExtensionTemplatesInput => "extension_templates_input",
+            FocusTarget::NewExtensionInput => "new_extension_input",
+            FocusTarget::StartInput => "start_input",
+            FocusTarget::PaddingInput => "padding_input",
+            FocusTarget::PreviewBudgetInput => "preview_budget_input",
+            FocusTarget::MaxNameWidthInput => "max_name_width_input",
+            FocusTarget::PerFolderLimitInput => "per_folder_limit_input",
+            FocusTarget::ConfirmThresholdInput => "confirm_threshold_input",
+            FocusTarget::IdleQuietInput => "idle_quiet_input",
+            FocusTarget::ProtectedRootsInput => "protected_roots_input",
+            FocusTarget::ExtensionFilterInput => "extension_filter_input",
+            FocusTarget::ExtensionGroupsInput => "extension_groups_input",
+            FocusTarget::FolderSeparatorInput => "folder_separator_input",
+            FocusTarget::PostRenameHookInput => "post_rename_hook_input",
+            FocusTarget::TermPresetNameInput => "term_preset_name_input",
+            _ => return None,
+        };
+        Some(text_input::Id::new(id))
+    }
+
+    // Widget id for the target; non-input targets map to an id no widget uses
+    pub fn widget_id(self) -> text_input::Id {
+        self.input_id()
+            .unwrap_or_else(|| text_input::Id::new("no_input"))
+    }
+
+    pub fn is_text_input(self) -> bool {
+        self.input_id().is_some()
+    }
+}
+
+// Moves caret focus to the target's text input, or clears it for non-input targets.
+// Focusing an id that no widget uses unfocuses every text input.
+pub fn focus_input<T>(target: FocusTarget) -> Task<T> {
+    text_input::focus(target.widget_id())
+}
+
+// Outline drawn around the focused widget; transparent otherwise so layout stays stable
+pub fn focus_ring_style(focused: bool) -> impl Fn(&Theme) -> container::Style {
+    move |theme| {
+        let color = if focused {
+            theme.extended_palette().primary.strong.color
+        } else {
+            Color::TRANSPARENT
+        };
+        container::Style {
+            border: border::rounded(4).color(color).width(FOCUS_RING_WIDTH),
+            ..container::Style::default()
+        }
+    }
+}
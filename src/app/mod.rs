@@ -1,41 +1,523 @@
 // Application state and iced GUI implementation
 
-use crate::file_ops::{scan_directory, validate_and_rename};
-use crate::rename::{apply_find_replace, apply_iteration_numbering};
-use crate::security::can_modify_file;
-use crate::settings::{load_settings, save_settings, Settings};
+mod context_menu;
+mod display;
+mod focus;
+mod navigation;
+mod shortcuts;
+
+use crate::file_ops::{
+    content_hash, detect_existing_number_width, error_kind, existing_paths, export_script,
+    file_sizes, folder_contents, folder_fingerprint, hard_link_peers, hold_back, import_path_list,
+    mark_disk_conflicts, matches_extensions, network_folder, occupied_targets, open_path,
+    parse_extension_groups, parse_extension_list, parse_extension_templates, record_original_names,
+    relocated_entry, reorder_around_pins, resulting_listing, reveal_in_file_manager,
+    scan_directory, sort_files, validate_and_rename, verify_renames, FailedRename, FolderScan,
+    PathListImport, RenameReport, RenameStage, ScanError, CASE_INSENSITIVE_OS,
+};
+use crate::hook::run_post_rename_hook;
+use crate::notify::notify;
+use crate::rename::{
+    align_stems, alternation_pattern, analyze, apply_convention, apply_edits,
+    apply_extension_change, apply_extension_templates, apply_find_replace,
+    apply_iteration_numbering_continued, apply_rules, assign_names, capture_views,
+    check_against_list, lint_template, resolve_conflicts_with_suffix, suggested_template,
+    uses_content_hash, Analysis, CaptureView, FindOptions, NameListCheck, NumberingOptions,
+    PreviewTimeout, SuggestedAction, TemplateLint, COPY_MARKER_PATTERN, COPY_MARKER_REPLACEMENT,
+    FOLDER_TOKEN,
+};
+use crate::security::{
+    blocked_files, is_running_as_admin, locked_files, parse_protected_roots, unmodifiable_files,
+    ProtectedRoots,
+};
+use crate::settings::{
+    backup_settings, load_settings, open_data_folder, restore_settings, save_settings, Settings,
+    DEFAULT_TEMPLATE,
+};
 use crate::theme::{
-    COLOR_CONFLICT, COLOR_ERROR, COLOR_INFO, COLOR_MUTED_DARK, COLOR_SUCCESS, FONT_LG, FONT_SM,
-    FONT_XL, LIST_HEIGHT, MAX_FILES, SPACING_LG, SPACING_MD, SPACING_SM, SPACING_XS,
+    CAPTURE_VIEW_FILES, COLOR_CONFLICT, COLOR_ERROR, COLOR_ERROR_LIGHT, COLOR_INFO,
+    COLOR_MUTED_DARK, COLOR_MUTED_LIGHT, COLOR_SUCCESS, COLOR_SUCCESS_LIGHT, COLOR_WARNING,
+    COLOR_WARNING_LIGHT, DEFAULT_IDLE_QUIET_SECS, DEFAULT_PREVIEW_BUDGET_MS, FONT_LG, FONT_SM,
+    FONT_XL, GROUP_COLORS, LIST_HEIGHT, MAX_COUNTERS, MAX_FILES, MAX_PATTERN_LENGTH,
+    MAX_RECENT_FOLDERS, MAX_RULES, MIN_PREVIEW_BUDGET_MS, SPACING_LG, SPACING_MD, SPACING_SM,
+    SPACING_XS, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+use crate::types::{
+    AccessFilter, AppMode, CaseLocale, ConflictPolicy, ConflictReason, Counter, ErrorKind,
+    ExtensionGroup, FileEntry, FolderOptions, FolderPreset, NameConvention, NumberGrouping,
+    PreviewArrow, PreviewFilter, RegexSnippet, RenamePreview, RenameRule, ScriptFormat, SortOrder,
+    TermPreset, REGEX_SNIPPETS,
 };
-use crate::types::{AppMode, FileEntry, RenamePreview};
+use context_menu::{edit_input_id, right_click_area, RowAction};
+use display::{
+    badge, elide_middle, field_label, fitted_text, indent_width, line_char_budget, list_row_style,
+    pane_char_budget, preview_tree, TreeRow, TREE_INDENT_CHARS,
+};
+use focus::{focus_input, focus_ring_style, FocusTarget, OptionsPanel};
+use iced::futures::channel::{mpsc, oneshot};
 use iced::widget::{
-    button, checkbox, column, container, horizontal_rule, horizontal_space, pick_list, row,
-    scrollable, text, text_input, vertical_space, Column,
+    button, checkbox, column, container, horizontal_rule, horizontal_space, mouse_area, opaque,
+    pick_list, rich_text, row, scrollable, span, stack, text, text_input, vertical_space, Column,
+    Row, Space,
+};
+use iced::{
+    keyboard, mouse, time, window, Bottom, Center, Color, Element, Fill, Font, Padding, Point,
+    Size, Subscription, Task, Theme,
 };
-use iced::{keyboard, time, Center, Element, Fill, Subscription, Task, Theme};
-use std::path::PathBuf;
+use navigation::{file_list_id, preview_list_id, scroll_previews_to};
+use shortcuts::{shortcut, text_input_focused, Shortcut};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 const DEBOUNCE_MS: u64 = 300;
+// How long "Copied ..." stands in for the status message after a copy
+const COPY_NOTICE_MS: u64 = 1500;
+const MAX_PRESET_NAME_LENGTH: usize = 64;
+// File names listed in the access-denied message before the rest are counted
+const MAX_DENIED_LISTED: usize = 5;
+// Row menu size, the item height being an estimate used to keep it inside the window
+const ROW_MENU_WIDTH: f32 = 200.0;
+const ROW_MENU_ITEM_HEIGHT: f32 = 30.0;
+
+// Example list for the protected folders input, using the platform's PATH separator
+#[cfg(windows)]
+const PROTECTED_ROOTS_PLACEHOLDER: &str = r"D:\Apps;E:\Games";
+#[cfg(not(windows))]
+const PROTECTED_ROOTS_PLACEHOLDER: &str = "/opt:/srv";
 
 pub struct FileRenamePlus {
     mode: AppMode,
     files: Vec<FileEntry>,
     selected_index: Option<usize>,
+    // Files picked with Ctrl/Shift-click; empty when only `selected_index` is selected
+    marked_files: HashSet<PathBuf>,
+    // Modifier keys held right now, read when a file row is clicked
+    modifiers: keyboard::Modifiers,
+    // Preview and rename only the selected files
+    selection_only: bool,
+    dragging_index: Option<usize>,
+    drop_target: Option<usize>,
     find_pattern: String,
+    // Caret in the Find field after the last typed edit; None means the end.
+    // iced does not report caret moves, so snippets land where the user last typed.
+    find_caret: Option<usize>,
     replace_with: String,
+    // Names already containing this are left out of find/replace
+    skip_if_contains: String,
     regex_mode: bool,
     case_sensitive: bool,
+    case_locale: CaseLocale,
+    lowercase_result: bool,
+    whole_word: bool,
+    // Regex debugging aid: what each group captures in the first few matching names
+    show_captures: bool,
+    capture_views: Vec<CaptureView>,
+    // "Match any of" builder: literal terms combined into an escaped alternation
+    terms_mode: bool,
+    find_terms: Vec<String>,
+    term_input: String,
+    term_presets: Vec<TermPreset>,
+    preset_name: String,
+    folder_presets: Vec<FolderPreset>,
+    // What a folder's preset replaced when it was loaded by a scan, for Undo
+    folder_preset_undo: Option<FolderPresetUndo>,
+    recent_folders: Vec<PathBuf>,
     template: String,
+    // Hints shown under the template input, refreshed along with the preview
+    template_lint: TemplateLint,
     start_number: String,
     padding: String,
+    counters: Vec<CounterFields>,
+    skip_existing: bool,
+    keep_numbered: bool,
+    match_existing_width: bool,
+    fit_padding: bool,
+    compound_extensions: bool,
+    reverse_numbering: bool,
+    continue_numbering: bool,
+    // Next start number per template, advanced by successful renames only
+    numbering_continuations: HashMap<String, u32>,
+    // Template and the start number that follows this batch, recorded once it succeeds
+    pending_continuation: Option<(String, u32)>,
+    // Template suited to the scanned file types, offered while the template is the default
+    template_suggestion: Option<&'static str>,
+    number_grouping: NumberGrouping,
+    name_convention: NameConvention,
     previews: Vec<RenamePreview>,
+    // Start number that carries the sequence on after the last Iteration preview
+    iteration_next: u32,
+    // Preview folder sections folded away; kept across regenerations
+    collapsed_folders: HashSet<PathBuf>,
+    preview_state: PreviewState,
+    preview_generation: u64,
+    preview_key: u64,
+    preview_cache: HashMap<AppMode, (u64, Vec<RenamePreview>)>,
+    // Modification times of the folders the cached Iteration preview read existing names from
+    listing_stamp: u64,
+    // Previews computed rather than served from the cache
+    #[cfg(test)]
+    preview_computations: usize,
+    // Per-mode view state kept across mode switches, reset when the set of files changes
+    mode_states: HashMap<AppMode, ModeState>,
+    mode_states_key: u64,
+    preview_budget: String,
+    show_settings: bool,
+    ordered_commit: bool,
+    preview_arrow: PreviewArrow,
+    truncate_names: bool,
+    preview_tree: bool,
+    // Row states the preview is narrowed to; empty shows every row
+    preview_filters: Vec<PreviewFilter>,
+    // Preview shown as each folder's listing after the rename instead of pairs of names
+    preview_listing: bool,
+    // Names on disk in the previewed folders, read with each preview while the listing shows
+    folder_contents: HashMap<PathBuf, Vec<String>>,
+    align_preview_names: bool,
+    max_name_width: String,
+    per_folder_limit: String,
+    confirm_threshold: String,
+    idle_quiet_secs: String,
+    // Batch waiting for the listed files' folders to go quiet; lasts until it runs or is
+    // cancelled, never across restarts
+    idle_run: Option<IdleRun>,
+    // The folders went quiet: Execute follows once the rescanned preview is ready
+    idle_execute_pending: bool,
+    // Files that appeared while an idle run waited and were added to the list; the rename
+    // waits for confirmation to include them
+    idle_new_files: Option<usize>,
+    // Folders loaded by a scan this session, exactly as picked; an idle run reads them again
+    scanned_roots: HashSet<PathBuf>,
+    // Applied to scans and imports as they arrive; the list is re-sorted only by Sort
+    sort_order: SortOrder,
+    // Addition position given to the next listed file
+    next_added: u64,
+    import_keep_order: bool,
+    number_duplicates: bool,
+    probe_directories: bool,
+    verify_renames: bool,
+    strict_case_check: bool,
+    record_original_names: bool,
+    notify_on_completion: bool,
+    protected_roots: String,
+    post_rename_hook: String,
+    // Only files with these extensions are previewed and renamed; empty for all
+    extension_filter: String,
+    extension_groups: String,
+    folder_separator: String,
+    // Files the last access check found can't be renamed, with the reason
+    blocked_files: HashMap<PathBuf, String>,
+    access_filter: AccessFilter,
+    // Content hashes for {hash}, computed only once a replacement uses it; None for files
+    // that couldn't be read
+    content_hashes: HashMap<PathBuf, Option<String>>,
+    // Files hashed so far and in total while a hashing pass runs
+    hashing: Option<(usize, usize)>,
+    // Bumped when the hashes are dropped, so a pass still running is ignored
+    hash_generation: u64,
+    // "jpg png: image_{n}; pdf: doc_{n}" for the template-per-extension mode
+    extension_templates: String,
+    new_extension: String,
+    rename_rules: Vec<RenameRule>,
+    // Protected folder holding some listed file, shown as a standing warning
+    protected_notice: Option<PathBuf>,
+    // Every folder of a listed file that lies under a protected root; their files get a badge
+    protected_folders: BTreeSet<PathBuf>,
+    // Checked once per run, since it turns the protected-folder warnings up
+    running_as_admin: bool,
+    // Network share holding some listed file, where renames can't be made atomic
+    network_notice: Option<PathBuf>,
+    // Number of files awaiting confirmation because it exceeds the threshold
+    large_batch_warning: Option<usize>,
+    // Protected folder awaiting confirmation before the rename runs
+    protected_warning: Option<PathBuf>,
+    // "I understand" box in that confirmation; Rename Anyway stays off until it's checked
+    protected_acknowledged: bool,
+    // Number of files Ctrl+Shift+X would clear, awaiting confirmation
+    clear_warning: Option<usize>,
+    // The list as it was before the last Clear, until restored, replaced or renamed
+    cleared_files: Option<Vec<FileEntry>>,
+    // Default answer for new names taken by existing files; per run, never persisted
+    conflict_policy: ConflictPolicy,
+    // Existing files colliding with new names, awaiting a confirmed choice for each
+    conflict_choices: Option<Vec<(PathBuf, ConflictPolicy)>>,
+    // Confirmed for the next rename only
+    conflict_resolutions: HashMap<PathBuf, ConflictPolicy>,
+    // Files from the last run that can be retried, and every failure since the batch began
+    failed_renames: Vec<FailedRename>,
+    failure_log: Vec<String>,
+    // Name analysis shown in place of the options until a suggestion is applied or closed
+    analysis: Option<Analysis>,
+    // Preview checked against a pasted list of expected names; dropped when the preview
+    // changes, so it always describes what Execute would do
+    name_check: Option<NameListCheck>,
+    // Row whose right-click menu is open
+    row_menu: Option<RowMenu>,
+    // Files left out of the preview and rename from their row menu
+    excluded: HashSet<PathBuf>,
+    // Background work that Execute and new scans must not overlap
+    is_renaming: bool,
+    // Where each file stands in the running or last rename, keyed by its path in the list
+    rename_progress: HashMap<PathBuf, RenameStage>,
+    is_scanning: bool,
+    script_format: ScriptFormat,
     status_message: Option<String>,
-    is_error: bool,
+    status_level: StatusLevel,
+    // Confirmation shown over the status message for a moment after copying something
+    copy_notice: Option<(String, Instant)>,
+    // When the running scan or rename began, for the timing in its completion message
+    batch_started: Option<Instant>,
+    // Full text of the last error; kept until dismissed or replaced by a newer error
+    error_details: Option<ErrorDetails>,
+    error_details_open: bool,
     dark_mode: bool,
     last_input_time: Option<Instant>,
     pending_preview: bool,
+    window_width: f32,
+    window_height: f32,
+    focus: Option<FocusTarget>,
+    file_list_offset: f32,
+}
+
+// Lifecycle of the current preview; only a ready preview can be executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewState {
+    Ready,
+    Running,
+    TimedOut,
+}
+
+// Severity of the status line message, which picks its icon and color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl StatusLevel {
+    fn icon(self) -> &'static str {
+        match self {
+            StatusLevel::Info => "ℹ",
+            StatusLevel::Success => "✓",
+            StatusLevel::Warning => "⚠",
+            StatusLevel::Error => "✕",
+        }
+    }
+
+    fn color(self, dark_mode: bool) -> Color {
+        match (self, dark_mode) {
+            (StatusLevel::Info, true) => COLOR_MUTED_DARK,
+            (StatusLevel::Info, false) => COLOR_MUTED_LIGHT,
+            (StatusLevel::Success, true) => COLOR_SUCCESS,
+            (StatusLevel::Success, false) => COLOR_SUCCESS_LIGHT,
+            (StatusLevel::Warning, true) => COLOR_WARNING,
+            (StatusLevel::Warning, false) => COLOR_WARNING_LIGHT,
+            (StatusLevel::Error, true) => COLOR_ERROR,
+            (StatusLevel::Error, false) => COLOR_ERROR_LIGHT,
+        }
+    }
+}
+
+// What a mode's preview looked like when it was left: where it was scrolled to, and names
+// pasted by hand keyed by original path
+#[derive(Debug, Default)]
+struct ModeState {
+    preview_offset: f32,
+    edits: HashMap<PathBuf, String>,
+}
+
+// An armed "run when idle": the folders watched and how long they've been unchanged
+#[derive(Debug)]
+struct IdleRun {
+    folders: Vec<PathBuf>,
+    // Scanned folders among them, read again when the run fires
+    roots: Vec<PathBuf>,
+    // Every file in `roots` when the run was armed, None while that listing runs. Only files
+    // missing from it are new; the others were left out of the list on purpose.
+    baseline: Option<HashSet<PathBuf>>,
+    // Last fingerprint seen, None until the first check
+    fingerprint: Option<u64>,
+    quiet_since: Instant,
+    // A check is in flight, so ticks don't pile up behind a slow folder
+    checking: bool,
+}
+
+// Right-click menu for one file, shown where the click was; `editing` holds the new name
+// being typed once "Edit New Name" is picked
+#[derive(Debug)]
+struct RowMenu {
+    path: PathBuf,
+    position: Point,
+    editing: Option<String>,
+}
+
+// Options as they were before a scan loaded a folder's term preset or numbering
+#[derive(Debug)]
+struct FolderPresetUndo {
+    preset: String,
+    mode: AppMode,
+    terms_mode: bool,
+    find_terms: Vec<String>,
+    preset_name: String,
+    template: String,
+    padding: String,
+}
+
+// Inputs for one {n1}, {n2}... counter, parsed when the preview is built
+#[derive(Debug, Clone, Hash)]
+struct CounterFields {
+    start: String,
+    step: String,
+    padding: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CounterField {
+    Start,
+    Step,
+    Padding,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RuleField {
+    Pattern,
+    Template,
+}
+
+impl CounterFields {
+    fn parse(&self) -> Counter {
+        let default = Counter::default();
+        Counter {
+            start: self.start.trim().parse().unwrap_or(default.start),
+            step: self.step.trim().parse().unwrap_or(default.step),
+            padding: self
+                .padding
+                .trim()
+                .parse()
+                .unwrap_or(default.padding)
+                .min(10),
+        }
+    }
+}
+
+impl From<Counter> for CounterFields {
+    fn from(counter: Counter) -> Self {
+        Self {
+            start: counter.start.to_string(),
+            step: counter.step.to_string(),
+            padding: counter.padding.to_string(),
+        }
+    }
+}
+
+// Error chain, one context level per line, the paths it concerns and what kind of
+// failure it is. Background tasks hand these back rather than bare strings.
+#[derive(Debug, Clone)]
+pub struct ErrorDetails {
+    lines: Vec<String>,
+    paths: Vec<PathBuf>,
+    kind: ErrorKind,
+}
+
+impl ErrorDetails {
+    fn new(chain: &str, paths: Vec<PathBuf>) -> Self {
+        let lines = chain
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        Self {
+            lines,
+            paths,
+            kind: ErrorKind::Other,
+        }
+    }
+
+    fn from_error(error: &anyhow::Error, paths: Vec<PathBuf>) -> Self {
+        Self::new(&error_chain(error), paths).with_kind(error_kind(error))
+    }
+
+    fn with_kind(self, kind: ErrorKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    // Button the panel offers for this kind of failure, and the message it sends
+    fn suggestion(&self) -> Option<(&'static str, Message)> {
+        match self.kind {
+            ErrorKind::PermissionDenied => Some((
+                "Show Locked Files",
+                Message::AccessFilterChanged(AccessFilter::Locked),
+            )),
+            ErrorKind::NotFound => Some(("Add Folder", Message::AddFolder)),
+            ErrorKind::Conflict | ErrorKind::InvalidName | ErrorKind::Other => None,
+        }
+    }
+
+    fn color(&self, dark_mode: bool) -> Color {
+        match (self.kind, dark_mode) {
+            (ErrorKind::Conflict | ErrorKind::InvalidName, _) => COLOR_CONFLICT,
+            (ErrorKind::PermissionDenied | ErrorKind::NotFound, true) => COLOR_WARNING,
+            (ErrorKind::PermissionDenied | ErrorKind::NotFound, false) => COLOR_WARNING_LIGHT,
+            (ErrorKind::Other, _) => StatusLevel::Error.color(dark_mode),
+        }
+    }
+
+    // Adds another error's lines and paths; mixed kinds count as Other
+    fn append(&mut self, other: ErrorDetails) {
+        if self.kind != other.kind {
+            self.kind = ErrorKind::Other;
+        }
+        self.lines.extend(other.lines);
+        for path in other.paths {
+            if !self.paths.contains(&path) {
+                self.paths.push(path);
+            }
+        }
+    }
+
+    // Plain text for the clipboard
+    fn to_clipboard_text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        for path in &self.paths {
+            text.push('\n');
+            text.push_str(&path.display().to_string());
+        }
+        text
+    }
+}
+
+// Single-line form for the status bar, e.g. "Failed to read: /x: Permission denied".
+// Indented lines, like the caret under a regex error, only make sense in the panel.
+impl std::fmt::Display for ErrorDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut summary = String::new();
+        for line in self
+            .lines
+            .iter()
+            .filter(|l| !l.starts_with(char::is_whitespace))
+        {
+            if !summary.is_empty() {
+                summary.push_str(if summary.ends_with(':') { " " } else { ": " });
+            }
+            summary.push_str(line.trim());
+        }
+        write!(f, "{}", summary)
+    }
+}
+
+// Result of a background find/replace preview job
+#[derive(Debug, Clone)]
+pub enum PreviewOutcome {
+    Ready(Vec<RenamePreview>),
+    TimedOut,
+    Failed(ErrorDetails),
 }
 
 #[derive(Debug, Clone)]
@@ -43,52 +525,340 @@ pub enum Message {
     ModeChanged(AppMode),
     AddFolder,
     FolderSelected(Option<PathBuf>),
-    ScanCompleted(Result<Vec<FileEntry>, String>),
+    RecentFolderSelected(String),
+    ScanCompleted(PathBuf, Result<FolderScan, ErrorDetails>),
+    FolderMissing(PathBuf),
+    ImportPathList,
+    PathListSelected(Option<PathBuf>),
+    PathListImported(Result<PathListImport, ErrorDetails>),
     FileSelected(usize),
+    ModifiersChanged(keyboard::Modifiers),
+    SelectionOnlyToggled(bool),
+    DragStarted(usize),
+    DragHovered(usize),
+    DragReleased,
     MoveUp,
     MoveDown,
+    TogglePin,
     RemoveFile,
+    SortByName,
+    AnalyzeNames,
+    ApplySuggestion(usize),
+    CloseAnalysis,
     ClearFiles,
     FindPatternChanged(String),
+    InsertRegexSnippet(RegexSnippet),
     ReplaceWithChanged(String),
+    SkipIfContainsChanged(String),
+    SwapFindReplace,
+    RemoveCopyMarkers,
+    WebSafeNames,
+    FolderPrefix,
     RegexModeToggled(bool),
     CaseSensitiveToggled(bool),
+    CaseLocaleChanged(CaseLocale),
+    LowercaseResultToggled(bool),
+    WholeWordToggled(bool),
+    ShowCapturesToggled(bool),
+    TermsModeToggled(bool),
+    TermInputChanged(String),
+    AddTerm,
+    RemoveTerm(usize),
+    TermPresetSelected(String),
+    TermPresetNameChanged(String),
+    SaveTermPreset,
+    DeleteTermPreset,
+    ToggleFolderPreset,
+    // Links the current template and padding to the last scanned folder, or unlinks them
+    ToggleFolderNumbering,
+    RemoveFolderPreset(usize),
+    UndoFolderPreset,
     TemplateChanged(String),
     StartNumberChanged(String),
     PaddingChanged(String),
+    AddCounter,
+    RemoveCounter(usize),
+    CounterChanged(usize, CounterField, String),
+    SkipExistingToggled(bool),
+    KeepNumberedToggled(bool),
+    MatchExistingWidthToggled(bool),
+    FitPaddingToggled(bool),
+    CompoundExtensionsToggled(bool),
+    ReverseNumberingToggled(bool),
+    ContinueNumberingToggled(bool),
+    ResetContinuation,
+    ApplyTemplateSuggestion,
+    DismissTemplateSuggestion,
+    NumberGroupingChanged(NumberGrouping),
+    NameConventionChanged(NameConvention),
+    ExtensionTemplatesChanged(String),
+    NewExtensionChanged(String),
+    AddRule,
+    RemoveRule(usize),
+    // Swaps the rule with the one above it
+    MoveRuleUp(usize),
+    RuleChanged(usize, RuleField, String),
+    // Files the background access check found blocked, for the file set with this key
+    AccessChecked(u64, HashMap<PathBuf, String>),
+    HashProgress(u64, usize),
+    HashesComputed(u64, Vec<(PathBuf, Option<String>)>),
+    AccessFilterChanged(AccessFilter),
     ExecuteRename,
-    RenameCompleted(Result<usize, String>),
+    RenameProgress(PathBuf, RenameStage),
+    RenameCompleted(Result<RenameReport, ErrorDetails>),
+    RetryFailedRenames,
+    DismissFailedRenames,
+    ErrorDetailsToggled,
+    CopyErrorDetails,
+    CopyStatus,
+    CopyNoticeTick,
+    DismissErrorDetails,
+    PreviewFolderToggled(PathBuf),
+    PreviewTreeToggled,
+    PreviewFilterToggled(PreviewFilter),
+    PreviewFiltersCleared,
+    // Keyboard: steps through showing one row state at a time
+    PreviewFilterCycled,
+    ResultingListingToggled,
+    AlignPreviewToggled,
+    PasteNames,
+    NamesPasted(Option<String>),
+    VerifyClipboard,
+    ClipboardVerified(Option<String>),
+    CloseNameCheck,
+    // Position in window coordinates
+    RowMenuOpened(PathBuf, Point),
+    RowMenuClosed,
+    RowMenuAction(RowAction),
+    RowMenuEditChanged(String),
+    RowMenuEditSubmitted,
+    ScriptFormatChanged(ScriptFormat),
+    ExportScript,
+    ScriptPathSelected(Option<PathBuf>),
+    OpenDataFolder,
+    BackUpSettings,
+    BackupPathSelected(Option<PathBuf>),
+    SettingsBackedUp(Result<PathBuf, ErrorDetails>),
+    RestoreSettings,
+    RestorePathSelected(Option<PathBuf>),
+    SettingsRestored(Result<PathBuf, ErrorDetails>),
+    ScriptExported(Result<PathBuf, ErrorDetails>),
     ToggleTheme,
+    ToggleSettings,
+    PreviewBudgetChanged(String),
+    OrderedCommitToggled(bool),
+    PreviewArrowChanged(PreviewArrow),
+    SortOrderChanged(SortOrder),
+    TruncateNamesToggled(bool),
+    MaxNameWidthChanged(String),
+    PerFolderLimitChanged(String),
+    ConfirmThresholdChanged(String),
+    IdleQuietChanged(String),
+    // Arms the rename to run once the folders go quiet, or cancels it when armed
+    IdleRunToggled,
+    IdleTick,
+    IdleBaselineListed(HashSet<PathBuf>),
+    IdleChecked(u64),
+    IdleRescanned(Vec<FileEntry>),
+    ConfirmIdleNewFiles,
+    CancelIdleNewFiles,
+    ImportKeepOrderToggled(bool),
+    NumberDuplicatesToggled(bool),
+    ProbeDirectoriesToggled(bool),
+    StrictCaseToggled(bool),
+    VerifyRenamesToggled(bool),
+    RecordOriginalNamesToggled(bool),
+    NotifyToggled(bool),
+    ProtectedRootsChanged(String),
+    PostRenameHookChanged(String),
+    PostRenameHookFinished(Result<(), ErrorDetails>),
+    ExtensionFilterChanged(String),
+    // Index into the parsed extension groups; None clears the filter
+    ExtensionGroupSelected(Option<usize>),
+    ExtensionGroupsChanged(String),
+    FolderSeparatorChanged(String),
+    ConfirmLargeBatch,
+    CancelLargeBatch,
+    ConfirmClearFiles,
+    CancelClearFiles,
+    RestoreClearedFiles,
+    ProtectedAcknowledged(bool),
+    ConfirmProtectedRename,
+    CancelProtectedRename,
+    ConflictPolicyChanged(ConflictPolicy),
+    ConflictChoiceChanged(usize, ConflictPolicy),
+    ConfirmConflicts,
+    CancelConflicts,
     SettingsSaved,
+    NotificationShown,
     DebounceTick,
+    PreviewComputed(u64, PreviewOutcome),
     KeyboardEvent(keyboard::Key, keyboard::Modifiers),
+    Shortcut(Shortcut),
+    WindowResized(Size),
+    FileListScrolled(f32),
+    PreviewListScrolled(f32),
 }
 
 impl FileRenamePlus {
     // Creates new app instance, loads saved settings
     pub fn new() -> (Self, Task<Message>) {
-        let settings = load_settings();
-        (
-            Self {
-                mode: AppMode::FindReplace,
-                files: Vec::new(),
-                selected_index: None,
-                find_pattern: String::new(),
-                replace_with: String::new(),
-                regex_mode: settings.regex_mode,
-                case_sensitive: settings.case_sensitive,
-                template: settings.template,
-                start_number: settings.start_number.to_string(),
-                padding: settings.padding.to_string(),
-                previews: Vec::new(),
-                status_message: Some("Click 'Add Folder' or press Ctrl+O".to_string()),
-                is_error: false,
-                dark_mode: settings.dark_mode,
-                last_input_time: None,
-                pending_preview: false,
+        (Self::from_settings(load_settings()), Task::none())
+    }
+
+    // App state for the given settings, with no files listed
+    fn from_settings(settings: Settings) -> Self {
+        Self {
+            mode: AppMode::FindReplace,
+            files: Vec::new(),
+            selected_index: None,
+            marked_files: HashSet::new(),
+            modifiers: keyboard::Modifiers::default(),
+            selection_only: false,
+            dragging_index: None,
+            drop_target: None,
+            find_pattern: String::new(),
+            find_caret: None,
+            replace_with: String::new(),
+            skip_if_contains: String::new(),
+            regex_mode: settings.regex_mode,
+            case_sensitive: settings.case_sensitive,
+            case_locale: settings.case_locale,
+            lowercase_result: settings.lowercase_result,
+            whole_word: settings.whole_word,
+            show_captures: false,
+            capture_views: Vec::new(),
+            terms_mode: false,
+            find_terms: Vec::new(),
+            term_input: String::new(),
+            term_presets: settings.term_presets,
+            folder_presets: settings.folder_presets,
+            folder_preset_undo: None,
+            // Folders deleted or unmounted since the last session are dropped
+            recent_folders: settings
+                .recent_folders
+                .into_iter()
+                .filter(|folder| folder.is_dir())
+                .collect(),
+            preset_name: String::new(),
+            template: settings.template,
+            template_lint: TemplateLint::default(),
+            start_number: settings.start_number.to_string(),
+            padding: settings.padding.to_string(),
+            counters: settings
+                .counters
+                .into_iter()
+                .map(CounterFields::from)
+                .collect(),
+            skip_existing: settings.skip_existing,
+            keep_numbered: settings.keep_numbered,
+            match_existing_width: settings.match_existing_width,
+            fit_padding: settings.fit_padding,
+            compound_extensions: settings.compound_extensions,
+            reverse_numbering: settings.reverse_numbering,
+            continue_numbering: settings.continue_numbering,
+            numbering_continuations: settings.numbering_continuations,
+            pending_continuation: None,
+            template_suggestion: None,
+            number_grouping: settings.number_grouping,
+            name_convention: settings.name_convention,
+            previews: Vec::new(),
+            iteration_next: 1,
+            collapsed_folders: HashSet::new(),
+            preview_state: PreviewState::Ready,
+            preview_generation: 0,
+            preview_key: 0,
+            preview_cache: HashMap::new(),
+            listing_stamp: 0,
+            #[cfg(test)]
+            preview_computations: 0,
+            mode_states: HashMap::new(),
+            mode_states_key: 0,
+            preview_budget: settings.preview_budget_ms.to_string(),
+            show_settings: false,
+            ordered_commit: settings.ordered_commit,
+            preview_arrow: settings.preview_arrow,
+            truncate_names: settings.truncate_names,
+            preview_tree: settings.preview_tree,
+            preview_filters: Vec::new(),
+            preview_listing: false,
+            folder_contents: HashMap::new(),
+            align_preview_names: settings.align_preview_names,
+            max_name_width: match settings.max_name_width {
+                0 => String::new(),
+                width => width.to_string(),
             },
-            Task::none(),
-        )
+            per_folder_limit: match settings.per_folder_limit {
+                0 => String::new(),
+                limit => limit.to_string(),
+            },
+            confirm_threshold: match settings.confirm_threshold {
+                0 => String::new(),
+                threshold => threshold.to_string(),
+            },
+            idle_quiet_secs: settings.idle_quiet_secs.to_string(),
+            idle_run: None,
+            idle_execute_pending: false,
+            idle_new_files: None,
+            scanned_roots: HashSet::new(),
+            sort_order: settings.sort_order,
+            next_added: 0,
+            import_keep_order: settings.import_keep_order,
+            number_duplicates: settings.number_duplicates,
+            probe_directories: settings.probe_directories,
+            verify_renames: settings.verify_renames,
+            strict_case_check: settings.strict_case_check,
+            record_original_names: settings.record_original_names,
+            notify_on_completion: settings.notify_on_completion,
+            protected_roots: settings.protected_roots,
+            post_rename_hook: settings.post_rename_hook,
+            extension_filter: settings.extension_filter,
+            extension_groups: settings.extension_groups,
+            extension_templates: settings.extension_templates,
+            new_extension: settings.new_extension,
+            rename_rules: settings.rename_rules,
+            blocked_files: HashMap::new(),
+            content_hashes: HashMap::new(),
+            hashing: None,
+            hash_generation: 0,
+            access_filter: AccessFilter::All,
+            folder_separator: settings.folder_separator,
+            protected_notice: None,
+            protected_folders: BTreeSet::new(),
+            running_as_admin: is_running_as_admin(),
+            network_notice: None,
+            large_batch_warning: None,
+            protected_warning: None,
+            protected_acknowledged: false,
+            clear_warning: None,
+            cleared_files: None,
+            conflict_policy: ConflictPolicy::default(),
+            conflict_choices: None,
+            conflict_resolutions: HashMap::new(),
+            failed_renames: Vec::new(),
+            failure_log: Vec::new(),
+            analysis: None,
+            name_check: None,
+            row_menu: None,
+            excluded: HashSet::new(),
+            is_renaming: false,
+            rename_progress: HashMap::new(),
+            is_scanning: false,
+            script_format: ScriptFormat::default(),
+            status_message: Some("Click 'Add Folder' or press Ctrl+O".to_string()),
+            status_level: StatusLevel::Info,
+            copy_notice: None,
+            batch_started: None,
+            error_details: None,
+            error_details_open: false,
+            dark_mode: settings.dark_mode,
+            last_input_time: None,
+            pending_preview: false,
+            window_width: WINDOW_WIDTH,
+            window_height: WINDOW_HEIGHT,
+            focus: None,
+            file_list_offset: 0.0,
+        }
     }
 
     // Creates Settings struct from current state
@@ -97,12 +867,125 @@ impl FileRenamePlus {
             dark_mode: self.dark_mode,
             regex_mode: self.regex_mode,
             case_sensitive: self.case_sensitive,
+            case_locale: self.case_locale,
+            lowercase_result: self.lowercase_result,
+            whole_word: self.whole_word,
             template: self.template.clone(),
             start_number: self.start_number.parse().unwrap_or(1),
             padding: self.padding.parse().unwrap_or(3),
+            counters: self.counters.iter().map(CounterFields::parse).collect(),
+            skip_existing: self.skip_existing,
+            keep_numbered: self.keep_numbered,
+            match_existing_width: self.match_existing_width,
+            fit_padding: self.fit_padding,
+            compound_extensions: self.compound_extensions,
+            reverse_numbering: self.reverse_numbering,
+            continue_numbering: self.continue_numbering,
+            numbering_continuations: self.numbering_continuations.clone(),
+            number_grouping: self.number_grouping,
+            name_convention: self.name_convention,
+            preview_budget_ms: self.preview_budget_ms(),
+            ordered_commit: self.ordered_commit,
+            preview_arrow: self.preview_arrow,
+            truncate_names: self.truncate_names,
+            preview_tree: self.preview_tree,
+            align_preview_names: self.align_preview_names,
+            max_name_width: self.max_name_width.parse().unwrap_or(0),
+            per_folder_limit: self.per_folder_limit.parse().unwrap_or(0),
+            confirm_threshold: self.confirm_threshold.parse().unwrap_or(0),
+            idle_quiet_secs: self.idle_quiet(),
+            sort_order: self.sort_order,
+            import_keep_order: self.import_keep_order,
+            number_duplicates: self.number_duplicates,
+            probe_directories: self.probe_directories,
+            verify_renames: self.verify_renames,
+            strict_case_check: self.strict_case_check,
+            record_original_names: self.record_original_names,
+            notify_on_completion: self.notify_on_completion,
+            protected_roots: self.protected_roots.clone(),
+            post_rename_hook: self.post_rename_hook.clone(),
+            extension_filter: self.extension_filter.clone(),
+            extension_groups: self.extension_groups.clone(),
+            extension_templates: self.extension_templates.clone(),
+            new_extension: self.new_extension.clone(),
+            rename_rules: self.rename_rules.clone(),
+            folder_separator: self.folder_separator.clone(),
+            term_presets: self.term_presets.clone(),
+            folder_presets: self.folder_presets.clone(),
+            recent_folders: self.recent_folders.clone(),
         }
     }
 
+    // Replaces every setting-backed field, as after restoring a backup
+    fn apply_settings(&mut self, settings: Settings) {
+        self.dark_mode = settings.dark_mode;
+        self.regex_mode = settings.regex_mode;
+        self.case_sensitive = settings.case_sensitive;
+        self.case_locale = settings.case_locale;
+        self.lowercase_result = settings.lowercase_result;
+        self.whole_word = settings.whole_word;
+        self.template = settings.template;
+        self.start_number = settings.start_number.to_string();
+        self.padding = settings.padding.to_string();
+        self.counters = settings
+            .counters
+            .into_iter()
+            .map(CounterFields::from)
+            .collect();
+        self.skip_existing = settings.skip_existing;
+        self.keep_numbered = settings.keep_numbered;
+        self.match_existing_width = settings.match_existing_width;
+        self.fit_padding = settings.fit_padding;
+        self.compound_extensions = settings.compound_extensions;
+        self.reverse_numbering = settings.reverse_numbering;
+        self.continue_numbering = settings.continue_numbering;
+        self.numbering_continuations = settings.numbering_continuations;
+        self.number_grouping = settings.number_grouping;
+        self.name_convention = settings.name_convention;
+        self.preview_budget = settings.preview_budget_ms.to_string();
+        self.ordered_commit = settings.ordered_commit;
+        self.preview_arrow = settings.preview_arrow;
+        self.truncate_names = settings.truncate_names;
+        self.preview_tree = settings.preview_tree;
+        self.align_preview_names = settings.align_preview_names;
+        self.max_name_width = match settings.max_name_width {
+            0 => String::new(),
+            width => width.to_string(),
+        };
+        self.per_folder_limit = match settings.per_folder_limit {
+            0 => String::new(),
+            limit => limit.to_string(),
+        };
+        self.confirm_threshold = match settings.confirm_threshold {
+            0 => String::new(),
+            threshold => threshold.to_string(),
+        };
+        self.idle_quiet_secs = settings.idle_quiet_secs.to_string();
+        self.sort_order = settings.sort_order;
+        self.import_keep_order = settings.import_keep_order;
+        self.number_duplicates = settings.number_duplicates;
+        self.probe_directories = settings.probe_directories;
+        self.verify_renames = settings.verify_renames;
+        self.strict_case_check = settings.strict_case_check;
+        self.record_original_names = settings.record_original_names;
+        self.notify_on_completion = settings.notify_on_completion;
+        self.protected_roots = settings.protected_roots;
+        self.post_rename_hook = settings.post_rename_hook;
+        self.extension_filter = settings.extension_filter;
+        self.extension_groups = settings.extension_groups;
+        self.extension_templates = settings.extension_templates;
+        self.new_extension = settings.new_extension;
+        self.rename_rules = settings.rename_rules;
+        self.folder_separator = settings.folder_separator;
+        self.term_presets = settings.term_presets;
+        self.folder_presets = settings.folder_presets;
+        self.recent_folders = settings
+            .recent_folders
+            .into_iter()
+            .filter(|folder| folder.is_dir())
+            .collect();
+    }
+
     // Saves settings asynchronously
     fn save_settings_async(&self) -> Task<Message> {
         let settings = self.to_settings();
@@ -114,12 +997,681 @@ impl FileRenamePlus {
         )
     }
 
+    // Parsed preview time budget, falling back to the default
+    fn preview_budget_ms(&self) -> u64 {
+        self.preview_budget
+            .parse()
+            .unwrap_or(DEFAULT_PREVIEW_BUDGET_MS)
+            .max(MIN_PREVIEW_BUDGET_MS)
+    }
+
+    // Moves the folder to the front of the recent list, dropping the oldest past the cap
+    fn remember_folder(&mut self, folder: PathBuf) {
+        self.recent_folders.retain(|f| *f != folder);
+        self.recent_folders.insert(0, folder);
+        self.recent_folders.truncate(MAX_RECENT_FOLDERS);
+    }
+
+    // Loads what the scanned folder is linked to: a term preset switches to "match any of"
+    // with its terms, a numbering link to iteration mode with its template and padding.
+    // The previous options are kept for Undo; returns a description of what was loaded.
+    fn load_folder_preset(&mut self, folder: &Path) -> Option<String> {
+        self.folder_preset_undo = None;
+        let options = FolderPreset::for_folder(&self.folder_presets, folder)?
+            .options
+            .clone();
+        let undo = FolderPresetUndo {
+            preset: options.to_string(),
+            mode: self.mode,
+            terms_mode: self.terms_mode,
+            find_terms: self.find_terms.clone(),
+            preset_name: self.preset_name.clone(),
+            template: self.template.clone(),
+            padding: self.padding.clone(),
+        };
+        match options {
+            FolderOptions::Terms(name) => {
+                let terms = self
+                    .term_presets
+                    .iter()
+                    .find(|p| p.name == name)?
+                    .terms
+                    .clone();
+                if self.mode == AppMode::FindReplace && self.terms_mode && self.find_terms == terms
+                {
+                    return None;
+                }
+                self.mode = AppMode::FindReplace;
+                self.terms_mode = true;
+                self.find_terms = terms;
+                self.preset_name = name;
+            }
+            FolderOptions::Numbering { template, padding } => {
+                let padding = padding.to_string();
+                if self.mode == AppMode::Iteration
+                    && self.template == template
+                    && self.padding == padding
+                {
+                    return None;
+                }
+                self.mode = AppMode::Iteration;
+                self.template = template;
+                self.padding = padding;
+                self.continue_start_number();
+            }
+        }
+        self.show_settings = false;
+        let loaded = undo.preset.clone();
+        self.folder_preset_undo = Some(undo);
+        Some(loaded)
+    }
+
+    // Links the options to the last scanned folder, replacing any link it had, or unlinks
+    // them when that folder already loads exactly these
+    fn toggle_folder_link(&mut self, options: FolderOptions) -> Task<Message> {
+        let Some(folder) = self.recent_folders.first().cloned() else {
+            return Task::none();
+        };
+        let linked = FolderPreset { folder, options };
+        if self.folder_presets.contains(&linked) {
+            self.folder_presets.retain(|a| *a != linked);
+            self.status_message = Some(format!(
+                "{} no longer loads \"{}\"",
+                linked.folder.display(),
+                linked.options
+            ));
+        } else {
+            // One link per folder; subfolders can still have their own
+            self.folder_presets.retain(|a| a.folder != linked.folder);
+            self.status_message = Some(format!(
+                "Scanning {} or its subfolders loads \"{}\"",
+                linked.folder.display(),
+                linked.options
+            ));
+            self.folder_presets.push(linked);
+            self.folder_presets.sort_by(|a, b| a.folder.cmp(&b.folder));
+        }
+        self.status_level = StatusLevel::Success;
+        self.save_settings_async()
+    }
+
+    // Template and padding as a numbering link, None while the padding isn't a number
+    fn folder_numbering(&self) -> Option<FolderOptions> {
+        Some(FolderOptions::Numbering {
+            template: self.template.clone(),
+            padding: self.padding.parse().ok()?,
+        })
+    }
+
+    // Appends new entries up to the file limit, skipping paths already in the list
+    fn add_entries(&mut self, entries: Vec<FileEntry>) {
+        // Restoring would now drop the new files
+        self.cleared_files = None;
+        for entry in entries {
+            if self.files.len() >= MAX_FILES {
+                self.status_message = Some(format!("Max {} files", MAX_FILES));
+                break;
+            }
+            if !self.files.iter().any(|f| f.path == entry.path) {
+                self.next_added += 1;
+                self.files.push(FileEntry {
+                    added: self.next_added,
+                    ..entry
+                });
+            }
+        }
+    }
+
+    // Preview indices in the order rows are shown, collapsed folders included
+    fn displayed_preview_order(&self) -> Vec<usize> {
+        if !self.preview_tree {
+            return (0..self.previews.len()).collect();
+        }
+        preview_tree(&self.previews)
+            .into_iter()
+            .filter_map(|row| match row {
+                TreeRow::File { index, .. } => Some(index),
+                TreeRow::Folder { .. } => None,
+            })
+            .collect()
+    }
+
+    // Assigns clipboard lines to preview rows in display order, folder by folder, collapsed
+    // sections included. Without a preview every listed file gets a row, so names can be
+    // pasted straight onto the file list.
+    fn paste_names(&mut self, clipboard: String) {
+        if self.preview_state == PreviewState::Running {
+            self.status_message = Some("Preview is still updating".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        let names: Vec<&str> = clipboard
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if names.is_empty() {
+            self.status_message = Some("Clipboard has no names to paste".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        if self.previews.is_empty() {
+            self.previews = self
+                .files
+                .iter()
+                .map(|f| RenamePreview {
+                    original_path: f.path.clone(),
+                    original_name: Arc::clone(&f.name),
+                    new_name: f.name.to_string(),
+                    conflicts: Vec::new(),
+                    invalid_reason: None,
+                    match_count: 0,
+                    edited: false,
+                    warnings: Vec::new(),
+                })
+                .collect();
+        }
+
+        let order = self.displayed_preview_order();
+        let mut displayed: Vec<RenamePreview> =
+            order.iter().map(|&i| self.previews[i].clone()).collect();
+        let assigned = assign_names(&mut displayed, &names);
+        for (i, preview) in order.into_iter().zip(displayed) {
+            self.previews[i] = preview;
+        }
+        let fold_case = self.fold_case();
+        mark_disk_conflicts(&mut self.previews, fold_case);
+        let edits = &mut self.mode_states.entry(self.mode).or_default().edits;
+        for preview in self.previews.iter().filter(|p| p.edited) {
+            edits.insert(preview.original_path.clone(), preview.new_name.clone());
+        }
+        if names.len() == self.previews.len() {
+            self.status_message = Some(format!("Pasted {} name(s)", assigned));
+            self.status_level = StatusLevel::Success;
+            self.report_invalid_names();
+        } else {
+            self.status_message = Some(format!(
+                "Pasted {} name(s): clipboard has {} line(s) for {} row(s)",
+                assigned,
+                names.len(),
+                self.previews.len()
+            ));
+            self.status_level = StatusLevel::Warning;
+        }
+    }
+
+    // Carries out a row menu choice; all but "Edit New Name" close the menu
+    fn row_menu_action(&mut self, action: RowAction) -> Task<Message> {
+        let Some(path) = self.row_menu.as_ref().map(|menu| menu.path.clone()) else {
+            return Task::none();
+        };
+        if action != RowAction::EditNewName {
+            self.row_menu = None;
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let new_name = self
+            .previews
+            .iter()
+            .find(|p| p.original_path == path)
+            .map(|p| p.new_name.clone());
+        match action {
+            RowAction::Reveal | RowAction::Open => {
+                let result = if action == RowAction::Reveal {
+                    reveal_in_file_manager(&path)
+                } else {
+                    open_path(&path)
+                };
+                if let Err(e) = result {
+                    self.show_error(ErrorDetails::from_error(&e, Vec::new()));
+                }
+                Task::none()
+            }
+            RowAction::CopyOldName => self.copy_to_clipboard("old name", name),
+            RowAction::CopyNewName => new_name.map_or_else(Task::none, |new_name| {
+                self.copy_to_clipboard("new name", new_name)
+            }),
+            RowAction::CopyPath => self.copy_to_clipboard("full path", path.display().to_string()),
+            RowAction::ToggleExclude => {
+                if self.excluded.remove(&path) {
+                    self.status_message = Some(format!("{} is back in the rename", name));
+                } else {
+                    self.excluded.insert(path);
+                    self.status_message = Some(format!("Left {} out of the rename", name));
+                }
+                self.status_level = StatusLevel::Info;
+                self.generate_preview()
+            }
+            RowAction::EditNewName => {
+                if let Some(menu) = &mut self.row_menu {
+                    menu.editing = Some(new_name.unwrap_or(name));
+                }
+                text_input::focus(edit_input_id())
+            }
+            RowAction::Remove => match self.files.iter().position(|f| f.path == path) {
+                Some(i) => {
+                    self.selected_index = Some(i);
+                    self.update(Message::RemoveFile)
+                }
+                None => Task::none(),
+            },
+        }
+    }
+
+    // Puts `content` on the clipboard and confirms it in the status line for a moment,
+    // leaving the status message itself as it was
+    fn copy_to_clipboard(&mut self, what: &str, content: String) -> Task<Message> {
+        self.copy_notice = Some((format!("Copied {}", what), Instant::now()));
+        iced::clipboard::write(content)
+    }
+
+    // Gives one file the name typed in its row menu, kept over later previews like a
+    // pasted name
+    fn edit_new_name(&mut self, path: PathBuf, name: &str) {
+        if self.preview_state == PreviewState::Running {
+            self.status_message = Some("Preview is still updating".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        if name.is_empty() {
+            self.status_message = Some("Type a name to rename the file to".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        self.mode_states
+            .entry(self.mode)
+            .or_default()
+            .edits
+            .insert(path, name.to_string());
+        self.restore_edits();
+        self.name_check = None;
+        self.status_message = Some(format!("New name set to {}", name));
+        self.status_level = StatusLevel::Success;
+        self.report_invalid_names();
+    }
+
+    // Compares the preview's new names with clipboard lines holding the expected final
+    // names, one per line in any order, and shows the differences
+    fn verify_names(&mut self, clipboard: String) {
+        if self.preview_state == PreviewState::Running {
+            self.status_message = Some("Preview is still updating".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        let names: Vec<&str> = clipboard
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if names.is_empty() {
+            self.status_message = Some("Clipboard has no names to verify against".to_string());
+            self.status_level = StatusLevel::Warning;
+            return;
+        }
+        let check = check_against_list(&self.previews, &names);
+        if check.is_match() {
+            self.status_message = Some(format!(
+                "All {} new name(s) match the clipboard list",
+                self.previews.len()
+            ));
+            self.status_level = StatusLevel::Success;
+        } else {
+            self.status_message = Some(format!(
+                "Preview differs from the clipboard list: {} missing, {} not listed, {} repeated",
+                check.missing.len(),
+                check.unexpected.len(),
+                check.repeated.len()
+            ));
+            self.status_level = StatusLevel::Warning;
+        }
+        self.name_check = Some(check);
+    }
+
+    // Asks for confirmation when any file lies in a protected folder, otherwise moves on
+    // to the conflict review
+    fn check_protected_or_rename(&mut self) -> Task<Message> {
+        let protected = self.protected_folder(self.previews.iter().map(|p| &p.original_path));
+        if let Some(folder) = protected {
+            self.status_message = Some(format!(
+                "{} is a protected system folder; confirm to continue",
+                folder.display()
+            ));
+            self.status_level = StatusLevel::Warning;
+            self.protected_warning = Some(folder);
+            self.protected_acknowledged = false;
+            return Task::none();
+        }
+        self.review_conflicts_or_rename()
+    }
+
+    // Lists every existing file a new name would collide with, each with its own choice
+    // starting from the global policy. With the policy at "Stop" the rename reports the
+    // collision as before.
+    fn review_conflicts_or_rename(&mut self) -> Task<Message> {
+        self.conflict_resolutions.clear();
+        if self.conflict_policy != ConflictPolicy::Abort {
+            let occupied = occupied_targets(&self.previews);
+            if !occupied.is_empty() {
+                self.status_message = Some(format!(
+                    "{} new name(s) are taken by existing files; review and confirm",
+                    occupied.len()
+                ));
+                self.status_level = StatusLevel::Warning;
+                let policy = self.conflict_policy;
+                self.conflict_choices =
+                    Some(occupied.into_iter().map(|path| (path, policy)).collect());
+                return Task::none();
+            }
+        }
+        self.start_rename()
+    }
+
+    // Checks write access to every file, then renames, all in the background. Files that
+    // can't be modified are reported together and nothing is renamed.
+    fn start_rename(&mut self) -> Task<Message> {
+        // A confirmation panel may have been answered while a rescan was running
+        if self.is_scanning {
+            self.status_message =
+                Some("Wait for the scan to finish, then execute again".to_string());
+            self.status_level = StatusLevel::Warning;
+            return Task::none();
+        }
+        let previews = self.previews.clone();
+        let ordered = self.ordered_commit;
+        let probe_fallback = self.probe_directories;
+        // Shares can report renames they haven't carried out, so those are always verified
+        let verify = self.verify_renames || self.network_notice.is_some();
+        let record = self.record_original_names;
+        let resolutions = std::mem::take(&mut self.conflict_resolutions);
+        self.is_renaming = true;
+        self.batch_started = Some(Instant::now());
+        self.rename_progress.clear();
+        self.status_message = Some(format!(
+            "Checking access to {} file(s)...",
+            self.changed_count()
+        ));
+        self.status_level = StatusLevel::Info;
+        // The batch runs on its own thread so its progress reaches the list while it works;
+        // the event stream ends when the thread drops its sender, then the report follows
+        let (events, progress) = mpsc::unbounded();
+        let (done, outcome) = oneshot::channel();
+        std::thread::spawn(move || {
+            let result = run_rename(
+                &previews,
+                ordered,
+                probe_fallback,
+                verify,
+                record,
+                &resolutions,
+                &events,
+            );
+            // Ends the event stream, after which the report is picked up
+            drop(events);
+            let _ = done.send(result);
+        });
+        Task::run(progress, |(path, stage)| {
+            Message::RenameProgress(path, stage)
+        })
+        .chain(Task::perform(
+            async move {
+                outcome.await.unwrap_or_else(|_| {
+                    Err(ErrorDetails::new(
+                        "The rename stopped unexpectedly",
+                        Vec::new(),
+                    ))
+                })
+            },
+            Message::RenameCompleted,
+        ))
+    }
+
+    // Hashes the files' contents on their own thread for {hash}, reporting each file done
+    fn content_hash_task(&mut self, paths: Vec<PathBuf>) -> Task<Message> {
+        let generation = self.hash_generation;
+        self.hashing = Some((0, paths.len()));
+        self.status_message = Some(format!("Hashing {} file(s) for {{hash}}...", paths.len()));
+        self.status_level = StatusLevel::Info;
+        let (events, progress) = mpsc::unbounded();
+        let (done, outcome) = oneshot::channel();
+        std::thread::spawn(move || {
+            let mut hashes = Vec::with_capacity(paths.len());
+            for path in paths {
+                let hash = content_hash(&path).ok();
+                hashes.push((path, hash));
+                let _ = events.unbounded_send(hashes.len());
+            }
+            drop(events);
+            let _ = done.send(hashes);
+        });
+        Task::run(progress, move |done| {
+            Message::HashProgress(generation, done)
+        })
+        .chain(Task::perform(
+            async move { outcome.await.unwrap_or_default() },
+            move |hashes| Message::HashesComputed(generation, hashes),
+        ))
+    }
+
+    // Forgets the content hashes and cached previews once files may have changed or moved,
+    // and any hashing pass running
+    fn clear_file_caches(&mut self) {
+        self.preview_cache.clear();
+        self.content_hashes.clear();
+        self.hashing = None;
+        self.hash_generation = self.hash_generation.wrapping_add(1);
+    }
+
+    // Checks every listed file for what would stop its rename, off the UI thread
+    fn access_check_task(&self) -> Task<Message> {
+        let paths: Vec<PathBuf> = self.files.iter().map(|f| f.path.clone()).collect();
+        let key = file_set_key(&self.files);
+        let probe_fallback = self.probe_directories;
+        Task::perform(
+            async move { blocked_files(&paths, probe_fallback) },
+            move |blocked| Message::AccessChecked(key, blocked),
+        )
+    }
+
+    // Runs the configured post-rename hook on its own thread once a batch renamed something
+    fn post_rename_hook_task(&self, report: &RenameReport) -> Task<Message> {
+        let command = self.post_rename_hook.trim().to_string();
+        if command.is_empty() || report.renamed == 0 {
+            return Task::none();
+        }
+        let report = report.clone();
+        let (done, outcome) = oneshot::channel();
+        std::thread::spawn(move || {
+            let result = run_post_rename_hook(&command, &report)
+                .map_err(|e| ErrorDetails::from_error(&e, Vec::new()));
+            let _ = done.send(result);
+        });
+        Task::perform(
+            async move {
+                outcome
+                    .await
+                    .unwrap_or_else(|_| Err(ErrorDetails::new("stopped unexpectedly", Vec::new())))
+            },
+            Message::PostRenameHookFinished,
+        )
+    }
+
+    // Carries the progress marks over to where each file ended up, and marks every file
+    // the report says failed, so the list shows what happened on disk
+    fn settle_rename_progress(&mut self, report: &RenameReport) {
+        let mut progress = std::mem::take(&mut self.rename_progress);
+        for (source, now) in &report.locations {
+            if let Some(stage) = progress.remove(source) {
+                self.rename_progress.insert(now.clone(), stage);
+            }
+        }
+        let failed = report
+            .failed
+            .iter()
+            .map(|f| &f.preview.original_path)
+            .chain(report.verify_failed.iter().map(|v| &v.path));
+        for path in failed {
+            self.rename_progress
+                .insert(path.clone(), RenameStage::Failed);
+        }
+    }
+
+    // First folder holding any of the paths that lies under a protected root
+    fn protected_folder<'a>(&self, paths: impl Iterator<Item = &'a PathBuf>) -> Option<PathBuf> {
+        self.protected_folders_of(paths).into_iter().next()
+    }
+
+    // Every folder holding any of the paths that lies under a protected root
+    fn protected_folders_of<'a>(
+        &self,
+        paths: impl Iterator<Item = &'a PathBuf>,
+    ) -> BTreeSet<PathBuf> {
+        let roots = ProtectedRoots::new(&parse_protected_roots(&self.protected_roots));
+        let folders: BTreeSet<&Path> = paths.filter_map(|p| p.parent()).collect();
+        folders
+            .into_iter()
+            .filter(|folder| roots.is_protected_path(folder))
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    // Error details chevron and, while the panel is open, its buttons
+    fn status_focus_targets(&self) -> Vec<FocusTarget> {
+        let mut targets = Vec::new();
+        if self.status_message.is_some() {
+            targets.push(FocusTarget::CopyStatus);
+        }
+        if self.cleared_files.is_some() {
+            targets.push(FocusTarget::RestoreClearedFiles);
+        }
+        if self.error_details.is_some() {
+            if self.status_level == StatusLevel::Error {
+                targets.push(FocusTarget::ErrorDetailsToggle);
+            }
+            if self.error_details_open {
+                if self
+                    .error_details
+                    .as_ref()
+                    .is_some_and(|d| d.suggestion().is_some())
+                {
+                    targets.push(FocusTarget::ErrorSuggestion);
+                }
+                targets.push(FocusTarget::CopyErrorDetails);
+                targets.push(FocusTarget::DismissErrorDetails);
+            }
+        }
+        targets
+    }
+
+    fn options_panel(&self) -> OptionsPanel {
+        if self.large_batch_warning.is_some() {
+            OptionsPanel::LargeBatch
+        } else if self.idle_new_files.is_some() {
+            OptionsPanel::IdleNewFiles
+        } else if self.protected_warning.is_some() {
+            OptionsPanel::ProtectedWarning
+        } else if self.clear_warning.is_some() {
+            OptionsPanel::ClearFiles
+        } else if self.conflict_choices.is_some() {
+            OptionsPanel::Conflicts
+        } else if !self.failed_renames.is_empty() {
+            OptionsPanel::RenameFailures
+        } else if self.name_check.is_some() {
+            OptionsPanel::NameCheck
+        } else if let Some(analysis) = &self.analysis {
+            OptionsPanel::Analysis(analysis.suggestions.len())
+        } else if self.show_settings {
+            OptionsPanel::Settings
+        } else if self.mode == AppMode::FindReplace && self.terms_mode {
+            OptionsPanel::FindAnyOf
+        } else {
+            OptionsPanel::Mode(self.mode)
+        }
+    }
+
+    // Shows a desktop notification in the background when enabled
+    fn notify_task(&self, summary: &'static str, body: String) -> Task<Message> {
+        if !self.notify_on_completion {
+            return Task::none();
+        }
+        Task::perform(async move { notify(summary, &body) }, |()| {
+            Message::NotificationShown
+        })
+    }
+
+    // Character budget for names in the preview pane, honoring the truncation settings
+    fn preview_name_budget(&self) -> usize {
+        if !self.truncate_names {
+            return usize::MAX;
+        }
+        let pane = pane_char_budget(self.window_width);
+        match self.max_name_width.parse::<usize>() {
+            Ok(width) if width > 0 => width.min(pane),
+            _ => pane,
+        }
+    }
+
+    fn idle_quiet(&self) -> u64 {
+        self.idle_quiet_secs
+            .parse()
+            .unwrap_or(DEFAULT_IDLE_QUIET_SECS)
+    }
+
+    // Executes the rename an idle run started, once the preview built from the rescanned
+    // folders is done; a failed preview is left to Execute to report
+    fn run_idle_execute(&mut self) -> Task<Message> {
+        if !self.idle_execute_pending
+            || self.preview_state == PreviewState::Running
+            || self.pending_preview
+            || self.hashing.is_some()
+        {
+            return Task::none();
+        }
+        self.idle_execute_pending = false;
+        self.update(Message::ExecuteRename)
+    }
+
     // Schedules debounced preview generation
     fn schedule_preview(&mut self) {
         self.last_input_time = Some(Instant::now());
         self.pending_preview = true;
     }
 
+    // iced has no accessibility tree yet, so the window title is the one piece of text
+    // screen readers can read back: it spells out the selected file and the latest status
+    pub fn title(&self) -> String {
+        let mut title = "File Rename Plus".to_string();
+        if let Some(description) = self.describe_selected_file() {
+            title.push_str(" - ");
+            title.push_str(&description);
+        }
+        if let Some(status) = &self.status_message {
+            title.push_str(" - ");
+            title.push_str(status);
+        }
+        title
+    }
+
+    // "file 12 of 300, IMG_0042.jpg, will be renamed to vacation_012.jpg, conflict"
+    fn describe_selected_file(&self) -> Option<String> {
+        let i = self.selected_index?;
+        let file = self.files.get(i)?;
+        let mut description = format!("file {} of {}, {}", i + 1, self.files.len(), file.name);
+        if let Some(preview) = self.previews.iter().find(|p| p.original_path == file.path) {
+            if preview.new_name != *preview.original_name {
+                description.push_str(&format!(", will be renamed to {}", preview.new_name));
+            }
+            if !preview.conflicts.is_empty() {
+                description.push_str(", conflict");
+            }
+            if preview.invalid_reason.is_some() {
+                description.push_str(", invalid name");
+            }
+        }
+        Some(description)
+    }
+
     pub fn theme(&self) -> Theme {
         if self.dark_mode {
             Theme::Dark
@@ -129,61 +1681,493 @@ impl FileRenamePlus {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        let keyboard_sub = iced::event::listen_with(|event, _status, _id| {
-            if let iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event
-            {
+        let keyboard_sub = iced::event::listen_with(|event, _status, _id| match event {
+            iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
                 Some(Message::KeyboardEvent(key, modifiers))
-            } else {
-                None
             }
+            iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
+            // Released anywhere so a drag dropped outside the list is still ended
+            iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                Some(Message::DragReleased)
+            }
+            _ => None,
         });
 
-        let debounce_sub = if self.pending_preview {
+        let debounce_sub = if self.pending_preview && !self.is_renaming {
             time::every(Duration::from_millis(50)).map(|_| Message::DebounceTick)
         } else {
             Subscription::none()
         };
 
-        Subscription::batch([keyboard_sub, debounce_sub])
-    }
+        let resize_sub = window::resize_events().map(|(_id, size)| Message::WindowResized(size));
 
-    // Handles all application messages
+        let idle_sub = if self.idle_run.is_some() {
+            time::every(Duration::from_secs(1)).map(|_| Message::IdleTick)
+        } else {
+            Subscription::none()
+        };
+
+        let copy_notice_sub = if self.copy_notice.is_some() {
+            time::every(Duration::from_millis(250)).map(|_| Message::CopyNoticeTick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            keyboard_sub,
+            debounce_sub,
+            resize_sub,
+            idle_sub,
+            copy_notice_sub,
+        ])
+    }
+
+    // Handles all application messages
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::KeyboardEvent(key, modifiers) => {
-                match key {
-                    keyboard::Key::Named(keyboard::key::Named::Delete) => {
-                        return self.update(Message::RemoveFile);
-                    }
-                    keyboard::Key::Named(keyboard::key::Named::Enter) if modifiers.control() => {
-                        return self.update(Message::ExecuteRename);
+            // Asks iced whether a text input has focus, which also covers inputs focused by a
+            // click rather than by Tab, then dispatches the shortcut
+            Message::KeyboardEvent(key, modifiers) => text_input_focused()
+                .map(move |typing| shortcut(&key, modifiers, typing))
+                .and_then(|action| Task::done(Message::Shortcut(action))),
+            Message::Shortcut(action) => match action {
+                Shortcut::ExecuteRename => self.update(Message::ExecuteRename),
+                Shortcut::AddFolder => self.update(Message::AddFolder),
+                Shortcut::RemoveFile => self.update(Message::RemoveFile),
+                Shortcut::ClearFiles => {
+                    // Asks first, since a stray key press shouldn't wipe a curated list
+                    let all_pinned = self.files.iter().all(|f| f.pinned);
+                    let count = self
+                        .files
+                        .iter()
+                        .filter(|f| !f.pinned || all_pinned)
+                        .count();
+                    if count > 0 && !self.is_renaming {
+                        self.clear_warning = Some(count);
                     }
-                    keyboard::Key::Character(c) if modifiers.control() && c.as_str() == "o" => {
-                        return self.update(Message::AddFolder);
-                    }
-                    _ => {}
+                    Task::none()
                 }
-                Task::none()
-            }
+                Shortcut::CycleFocus { forward } => {
+                    let target = FocusTarget::cycle(
+                        self.focus,
+                        self.options_panel(),
+                        &self.status_focus_targets(),
+                        forward,
+                    );
+                    self.set_focus(target)
+                }
+                Shortcut::Activate => match self.focus.filter(|t| !t.is_text_input()) {
+                    Some(target) => self.activate(target),
+                    None => Task::none(),
+                },
+                Shortcut::Dismiss => {
+                    self.row_menu = None;
+                    Task::none()
+                }
+                Shortcut::Navigate { key, control } => {
+                    self.navigate_list(key, control).unwrap_or_else(Task::none)
+                }
+            },
             Message::DebounceTick => {
                 if let Some(last_time) = self.last_input_time {
                     if last_time.elapsed() >= Duration::from_millis(DEBOUNCE_MS) {
                         self.pending_preview = false;
                         self.last_input_time = None;
-                        self.generate_preview();
+                        return self.generate_preview();
                     }
                 }
                 Task::none()
             }
-            Message::SettingsSaved => Task::none(),
+            Message::PreviewComputed(generation, outcome) => {
+                self.apply_preview_outcome(generation, outcome);
+                self.run_idle_execute()
+            }
+            Message::SettingsSaved | Message::NotificationShown => Task::none(),
+            Message::FileListScrolled(offset) => {
+                self.file_list_offset = offset;
+                Task::none()
+            }
+            Message::PreviewListScrolled(offset) => {
+                self.mode_states
+                    .entry(self.mode)
+                    .or_default()
+                    .preview_offset = offset;
+                Task::none()
+            }
+            Message::WindowResized(size) => {
+                self.window_width = size.width;
+                self.window_height = size.height;
+                Task::none()
+            }
             Message::ToggleTheme => {
                 self.dark_mode = !self.dark_mode;
                 self.save_settings_async()
             }
+            Message::ToggleSettings => {
+                self.show_settings = !self.show_settings;
+                Task::none()
+            }
+            Message::PreviewBudgetChanged(budget) => {
+                self.preview_budget = budget;
+                self.save_settings_async()
+            }
+            Message::OrderedCommitToggled(e) => {
+                self.ordered_commit = e;
+                self.save_settings_async()
+            }
+            Message::SortOrderChanged(order) => {
+                // Never re-sorts the list behind the user's back
+                self.sort_order = order;
+                self.status_message = Some(format!(
+                    "File order set to {}; press \"{}\" to re-sort the current list",
+                    order,
+                    sort_button_label(order)
+                ));
+                self.status_level = StatusLevel::Info;
+                self.save_settings_async()
+            }
+            Message::PreviewArrowChanged(arrow) => {
+                self.preview_arrow = arrow;
+                self.save_settings_async()
+            }
+            Message::TruncateNamesToggled(e) => {
+                self.truncate_names = e;
+                self.save_settings_async()
+            }
+            Message::NumberDuplicatesToggled(e) => {
+                self.number_duplicates = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ProbeDirectoriesToggled(e) => {
+                self.probe_directories = e;
+                self.save_settings_async()
+            }
+            Message::StrictCaseToggled(e) => {
+                self.strict_case_check = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::VerifyRenamesToggled(e) => {
+                self.verify_renames = e;
+                self.save_settings_async()
+            }
+            Message::RecordOriginalNamesToggled(e) => {
+                self.record_original_names = e;
+                self.save_settings_async()
+            }
+            Message::ImportKeepOrderToggled(e) => {
+                self.import_keep_order = e;
+                self.save_settings_async()
+            }
+            Message::NotifyToggled(e) => {
+                self.notify_on_completion = e;
+                self.save_settings_async()
+            }
+            Message::ProtectedRootsChanged(roots) => {
+                self.protected_roots = roots;
+                self.save_settings_async()
+            }
+            Message::PostRenameHookChanged(command) => {
+                self.post_rename_hook = command;
+                self.save_settings_async()
+            }
+            Message::PostRenameHookFinished(result) => {
+                // The rename itself went through; only the hook is reported
+                if let Err(e) = result {
+                    self.status_message = Some(format!(
+                        "{} (post-rename hook: {})",
+                        self.status_message.as_deref().unwrap_or("Renamed"),
+                        e
+                    ));
+                    if self.status_level != StatusLevel::Error {
+                        self.status_level = StatusLevel::Warning;
+                    }
+                }
+                Task::none()
+            }
+            Message::ExtensionFilterChanged(filter) => {
+                self.extension_filter = filter;
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::ExtensionGroupSelected(index) => {
+                let groups = parse_extension_groups(&self.extension_groups);
+                self.extension_filter = index
+                    .and_then(|i| groups.get(i))
+                    .map(|group| group.extensions.join(", "))
+                    .unwrap_or_default();
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ExtensionGroupsChanged(groups) => {
+                self.extension_groups = groups;
+                self.save_settings_async()
+            }
+            Message::FolderSeparatorChanged(separator) => {
+                self.folder_separator = separator;
+                self.save_settings_async()
+            }
+            Message::ConfirmLargeBatch => {
+                self.large_batch_warning = None;
+                self.check_protected_or_rename()
+            }
+            Message::CancelLargeBatch => {
+                self.large_batch_warning = None;
+                self.status_message = Some("Rename cancelled".to_string());
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
+            Message::ConfirmClearFiles => {
+                self.clear_warning = None;
+                self.update(Message::ClearFiles)
+            }
+            Message::CancelClearFiles => {
+                self.clear_warning = None;
+                self.status_message = Some("Clear cancelled".to_string());
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
+            Message::RestoreClearedFiles => {
+                let Some(files) = self.cleared_files.take() else {
+                    return Task::none();
+                };
+                self.files = files;
+                self.selected_index = None;
+                self.marked_files.clear();
+                self.status_message = Some(format!("Restored {} files", self.files.len()));
+                self.status_level = StatusLevel::Success;
+                self.generate_preview()
+            }
+            Message::ProtectedAcknowledged(acknowledged) => {
+                self.protected_acknowledged = acknowledged;
+                Task::none()
+            }
+            Message::ConfirmProtectedRename => {
+                if !self.protected_acknowledged {
+                    return Task::none();
+                }
+                self.protected_warning = None;
+                self.review_conflicts_or_rename()
+            }
+            Message::CancelProtectedRename => {
+                self.protected_warning = None;
+                self.status_message = Some("Rename cancelled".to_string());
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
+            Message::ConflictPolicyChanged(policy) => {
+                self.conflict_policy = policy;
+                Task::none()
+            }
+            Message::ConflictChoiceChanged(index, policy) => {
+                if let Some(choice) = self
+                    .conflict_choices
+                    .as_mut()
+                    .and_then(|choices| choices.get_mut(index))
+                {
+                    choice.1 = policy;
+                }
+                Task::none()
+            }
+            Message::ConfirmConflicts => {
+                if let Some(choices) = self.conflict_choices.take() {
+                    self.conflict_resolutions = choices.into_iter().collect();
+                }
+                self.start_rename()
+            }
+            Message::CancelConflicts => {
+                self.conflict_choices = None;
+                self.status_message = Some("Rename cancelled".to_string());
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
+            Message::MaxNameWidthChanged(width) => {
+                if width.chars().all(|c| c.is_ascii_digit()) {
+                    self.max_name_width = width;
+                }
+                self.save_settings_async()
+            }
+            Message::PerFolderLimitChanged(limit) => {
+                if limit.chars().all(|c| c.is_ascii_digit()) {
+                    self.per_folder_limit = limit;
+                }
+                self.save_settings_async()
+            }
+            Message::ConfirmThresholdChanged(threshold) => {
+                if threshold.chars().all(|c| c.is_ascii_digit()) {
+                    self.confirm_threshold = threshold;
+                }
+                self.save_settings_async()
+            }
+            Message::IdleQuietChanged(seconds) => {
+                if seconds.chars().all(|c| c.is_ascii_digit()) {
+                    self.idle_quiet_secs = seconds;
+                }
+                self.save_settings_async()
+            }
+            Message::IdleRunToggled => {
+                if self.idle_run.take().is_some() || self.idle_execute_pending {
+                    self.idle_execute_pending = false;
+                    self.status_message = Some("Idle run cancelled".to_string());
+                    self.status_level = StatusLevel::Info;
+                    return Task::none();
+                }
+                if self.files.is_empty() || self.is_renaming {
+                    return Task::none();
+                }
+                let folders: BTreeSet<PathBuf> = self
+                    .files
+                    .iter()
+                    .filter_map(|f| f.path.parent().map(Path::to_path_buf))
+                    .collect();
+                self.status_message = Some(format!(
+                    "Armed: renaming once {} folder(s) go {} s without changes",
+                    folders.len(),
+                    self.idle_quiet()
+                ));
+                self.status_level = StatusLevel::Info;
+                let roots: Vec<PathBuf> = self
+                    .scanned_roots
+                    .iter()
+                    .filter(|root| folders.contains(*root))
+                    .cloned()
+                    .collect();
+                self.idle_run = Some(IdleRun {
+                    folders: folders.into_iter().collect(),
+                    roots: roots.clone(),
+                    baseline: None,
+                    fingerprint: None,
+                    quiet_since: Instant::now(),
+                    checking: false,
+                });
+                let order = self.sort_order;
+                Task::perform(
+                    async move {
+                        roots
+                            .iter()
+                            .filter_map(|root| {
+                                scan_directory(&root.to_string_lossy(), None, order).ok()
+                            })
+                            .flat_map(|scan| scan.files)
+                            .map(|entry| entry.path)
+                            .collect()
+                    },
+                    Message::IdleBaselineListed,
+                )
+            }
+            Message::IdleBaselineListed(paths) => {
+                if let Some(run) = self.idle_run.as_mut() {
+                    run.baseline = Some(paths);
+                }
+                Task::none()
+            }
+            Message::IdleTick => match self.idle_run.as_mut() {
+                Some(run) if !run.checking => {
+                    run.checking = true;
+                    let folders = run.folders.clone();
+                    Task::perform(
+                        async move { folder_fingerprint(&folders) },
+                        Message::IdleChecked,
+                    )
+                }
+                _ => Task::none(),
+            },
+            Message::IdleChecked(fingerprint) => {
+                let quiet = Duration::from_secs(self.idle_quiet());
+                let Some(run) = self.idle_run.as_mut() else {
+                    return Task::none();
+                };
+                run.checking = false;
+                if run.fingerprint != Some(fingerprint) {
+                    run.fingerprint = Some(fingerprint);
+                    run.quiet_since = Instant::now();
+                }
+                // A scan or rename in progress holds the run back until it's done, as does
+                // the listing that tells new files from old ones
+                if run.quiet_since.elapsed() < quiet
+                    || run.baseline.is_none()
+                    || self.is_renaming
+                    || self.is_scanning
+                {
+                    return Task::none();
+                }
+                let Some(run) = self.idle_run.take() else {
+                    return Task::none();
+                };
+                // Cleared while waiting; rescanning would bring the files back
+                if self.files.is_empty() {
+                    self.status_message =
+                        Some("Idle run cancelled: the file list is empty".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                self.idle_execute_pending = true;
+                self.status_message = Some(format!(
+                    "Folders quiet for {} s; rescanning before the rename",
+                    quiet.as_secs()
+                ));
+                self.status_level = StatusLevel::Info;
+                // Scanned folders are read again, so files written since arming can join
+                let roots = run.roots;
+                let baseline = run.baseline.unwrap_or_default();
+                let limit = self.per_folder_limit.parse().ok().filter(|&l| l > 0);
+                let order = self.sort_order;
+                self.is_scanning = true;
+                Task::perform(
+                    async move {
+                        roots
+                            .iter()
+                            .filter_map(|root| {
+                                scan_directory(&root.to_string_lossy(), limit, order).ok()
+                            })
+                            .flat_map(|scan| scan.files)
+                            .filter(|entry| !baseline.contains(&entry.path))
+                            .collect()
+                    },
+                    Message::IdleRescanned,
+                )
+            }
+            Message::IdleRescanned(entries) => {
+                self.is_scanning = false;
+                // Files moved or deleted while waiting would only fail the rename
+                self.files.retain(|f| f.path.exists());
+                let listed = self.files.len();
+                self.add_entries(entries);
+                let added = self.files.len() - listed;
+                self.clear_file_caches();
+                let preview = self.generate_preview();
+                if added > 0 {
+                    // New files are never renamed unattended; they're listed for a look first
+                    self.idle_execute_pending = false;
+                    self.idle_new_files = Some(added);
+                    self.status_message = Some(format!(
+                        "{} new file(s) appeared while waiting; confirm to rename them too",
+                        added
+                    ));
+                    self.status_level = StatusLevel::Warning;
+                    return Task::batch([preview, self.access_check_task()]);
+                }
+                Task::batch([preview, self.access_check_task(), self.run_idle_execute()])
+            }
+            Message::ConfirmIdleNewFiles => {
+                self.idle_new_files = None;
+                self.idle_execute_pending = true;
+                self.run_idle_execute()
+            }
+            Message::CancelIdleNewFiles => {
+                self.idle_new_files = None;
+                self.status_message =
+                    Some("Idle run cancelled; the new files stay listed".to_string());
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
             Message::ModeChanged(mode) => {
                 self.mode = mode;
-                self.generate_preview();
-                Task::none()
+                let offset = self
+                    .mode_states
+                    .get(&mode)
+                    .map_or(0.0, |state| state.preview_offset);
+                Task::batch([self.generate_preview(), scroll_previews_to(offset)])
             }
             Message::AddFolder => Task::perform(
                 async {
@@ -196,65 +2180,228 @@ impl FileRenamePlus {
                 Message::FolderSelected,
             ),
             Message::FolderSelected(path) => {
+                if self.is_renaming {
+                    return self.report_rename_running();
+                }
                 if let Some(path) = path {
+                    self.is_scanning = true;
+                    self.batch_started = Some(Instant::now());
                     self.status_message = Some("Scanning...".to_string());
-                    self.is_error = false;
+                    self.status_level = StatusLevel::Info;
                     let path_str = path.to_string_lossy().to_string();
+                    let limit = self.per_folder_limit.parse().ok().filter(|&l| l > 0);
+                    let order = self.sort_order;
                     Task::perform(
-                        async move { scan_directory(&path_str).map_err(|e| e.to_string()) },
-                        Message::ScanCompleted,
+                        async move { scan_directory(&path_str, limit, order) },
+                        move |result| match result {
+                            Err(ScanError::NotFound(_)) => Message::FolderMissing(path.clone()),
+                            result => Message::ScanCompleted(
+                                path.clone(),
+                                result.map_err(|e| {
+                                    ErrorDetails::from_error(&e.into(), vec![path.clone()])
+                                }),
+                            ),
+                        },
                     )
                 } else {
                     Task::none()
                 }
             }
-            Message::ScanCompleted(result) => {
-                match result {
-                    Ok(entries) => {
-                        for entry in entries {
-                            if self.files.len() >= MAX_FILES {
-                                self.status_message = Some(format!("Max {} files", MAX_FILES));
-                                break;
-                            }
-                            if !self.files.iter().any(|f| f.path == entry.path) {
-                                self.files.push(entry);
-                            }
-                        }
-                        self.status_message = Some(format!("Total: {} files", self.files.len()));
-                        self.is_error = false;
-                        self.generate_preview();
+            Message::RecentFolderSelected(folder) => {
+                let path = PathBuf::from(folder);
+                if path.is_dir() {
+                    return self.update(Message::FolderSelected(Some(path)));
+                }
+                self.update(Message::FolderMissing(path))
+            }
+            // Deleted or moved since it was picked; it's dropped from the recent folders
+            Message::FolderMissing(path) => {
+                self.is_scanning = false;
+                self.recent_folders.retain(|f| *f != path);
+                self.status_message = Some(format!("Folder no longer exists: {}", path.display()));
+                self.status_level = StatusLevel::Warning;
+                self.save_settings_async()
+            }
+            Message::ScanCompleted(folder, result) => match result {
+                Ok(scan) => {
+                    self.is_scanning = false;
+                    let loaded_preset = self.load_folder_preset(&folder);
+                    self.scanned_roots.insert(folder.clone());
+                    self.remember_folder(folder);
+                    let scanned = scan.files.len();
+                    self.add_entries(scan.files);
+                    self.template_suggestion = if self.template == DEFAULT_TEMPLATE {
+                        suggested_template(&self.files)
+                    } else {
+                        None
+                    };
+                    let mut total = format!("Total: {} files", self.files.len());
+                    if scan.stopped_early {
+                        total.push_str(&format!(
+                            " (stopped reading the folder after {} files; the rest were not scanned)",
+                            MAX_FILES
+                        ));
+                    } else if scan.skipped > 0 {
+                        total.push_str(&format!(
+                            " ({} more in the folder skipped by the file limit)",
+                            scan.skipped
+                        ));
+                    }
+                    if let Some(started) = self.batch_started.take() {
+                        total.push_str(&format!("; scanned in {}", batch_timing(started, scanned)));
                     }
-                    Err(e) => {
-                        self.status_message = Some(format!("Error: {}", e));
-                        self.is_error = true;
+                    let notification = self.notify_task("Scan finished", total.clone());
+                    if let Some(name) = loaded_preset {
+                        total.push_str(&format!("; loaded the folder's preset \"{}\"", name));
                     }
+                    self.status_message = Some(total);
+                    self.status_level = if scan.skipped > 0 || scan.stopped_early {
+                        StatusLevel::Warning
+                    } else {
+                        StatusLevel::Success
+                    };
+                    self.clear_file_caches();
+                    Task::batch([
+                        self.generate_preview(),
+                        self.access_check_task(),
+                        notification,
+                        self.save_settings_async(),
+                    ])
+                }
+                Err(e) => {
+                    self.is_scanning = false;
+                    let summary = e.to_string();
+                    self.show_error(e);
+                    self.notify_task("Scan failed", summary)
+                }
+            },
+            Message::ImportPathList => Task::perform(
+                async {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Import Path List")
+                        .add_filter("Text", &["txt", "lst"])
+                        .add_filter("All files", &["*"])
+                        .pick_file()
+                        .await
+                        .map(|f| f.path().to_path_buf())
+                },
+                Message::PathListSelected,
+            ),
+            Message::PathListSelected(path) => {
+                if self.is_renaming {
+                    return self.report_rename_running();
+                }
+                if let Some(path) = path {
+                    self.is_scanning = true;
+                    self.status_message = Some("Importing...".to_string());
+                    self.status_level = StatusLevel::Info;
+                    let order = if self.import_keep_order {
+                        SortOrder::AsAdded
+                    } else {
+                        self.sort_order
+                    };
+                    Task::perform(
+                        async move {
+                            import_path_list(&path, order)
+                                .map_err(|e| ErrorDetails::from_error(&e, vec![path.clone()]))
+                        },
+                        Message::PathListImported,
+                    )
+                } else {
+                    Task::none()
                 }
-                Task::none()
             }
+            Message::PathListImported(result) => match result {
+                Ok(import) => {
+                    self.is_scanning = false;
+                    self.add_entries(import.files);
+                    let total = format!("Total: {} files", self.files.len());
+                    self.status_message = Some(match import.invalid_lines.first() {
+                        Some(first) => format!(
+                            "{}; {} invalid line(s) — {}",
+                            total,
+                            import.invalid_lines.len(),
+                            first
+                        ),
+                        None => total,
+                    });
+                    self.status_level = if import.invalid_lines.is_empty() {
+                        StatusLevel::Success
+                    } else {
+                        StatusLevel::Warning
+                    };
+                    self.clear_file_caches();
+                    Task::batch([self.generate_preview(), self.access_check_task()])
+                }
+                Err(e) => {
+                    self.is_scanning = false;
+                    self.show_error(e);
+                    Task::none()
+                }
+            },
             Message::FileSelected(index) => {
-                self.selected_index = Some(index);
+                let before = (self.selected_index, self.marked_files.clone());
+                self.select_file(index);
+                let changed = before != (self.selected_index, self.marked_files.clone());
+                if changed && self.selection_only {
+                    return self.generate_preview();
+                }
                 Task::none()
             }
-            Message::MoveUp => {
-                if let Some(i) = self.selected_index {
-                    if i > 0 {
-                        self.files.swap(i, i - 1);
-                        self.selected_index = Some(i - 1);
-                        self.generate_preview();
-                    }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Task::none()
+            }
+            Message::SelectionOnlyToggled(enabled) => {
+                self.selection_only = enabled;
+                self.generate_preview()
+            }
+            Message::DragStarted(index) => {
+                self.focus = Some(FocusTarget::FileList);
+                self.dragging_index = Some(index);
+                self.drop_target = Some(index);
+                self.update(Message::FileSelected(index))
+            }
+            Message::DragHovered(index) => {
+                if self.dragging_index.is_some() {
+                    self.drop_target = Some(index);
                 }
                 Task::none()
             }
-            Message::MoveDown => {
-                if let Some(i) = self.selected_index {
-                    if i < self.files.len().saturating_sub(1) {
-                        self.files.swap(i, i + 1);
-                        self.selected_index = Some(i + 1);
-                        self.generate_preview();
+            Message::DragReleased => {
+                if let (Some(from), Some(to)) =
+                    (self.dragging_index.take(), self.drop_target.take())
+                {
+                    if from != to && from < self.files.len() && to < self.files.len() {
+                        if self.files[from].pinned {
+                            return self.report_pinned();
+                        }
+                        let path = self.files[from].path.clone();
+                        reorder_around_pins(&mut self.files, |files| {
+                            let entry = files.remove(from);
+                            files.insert(to, entry);
+                        });
+                        self.selected_index = self.files.iter().position(|f| f.path == path);
+                        return self.generate_preview();
                     }
                 }
                 Task::none()
             }
+            Message::MoveUp => self.move_selected(false),
+            Message::MoveDown => self.move_selected(true),
+            Message::TogglePin => {
+                let Some(file) = self.selected_index.and_then(|i| self.files.get_mut(i)) else {
+                    return Task::none();
+                };
+                file.pinned = !file.pinned;
+                self.status_message = Some(if file.pinned {
+                    format!("Pinned {}", file.name)
+                } else {
+                    format!("Unpinned {}", file.name)
+                });
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
             Message::RemoveFile => {
                 if let Some(i) = self.selected_index {
                     if i < self.files.len() {
@@ -266,368 +2413,4671 @@ impl FileRenamePlus {
                         } else {
                             Some(i)
                         };
-                        self.generate_preview();
+                        return self.generate_preview();
+                    }
+                }
+                Task::none()
+            }
+            Message::SortByName => {
+                // Keep the selected file selected wherever it lands
+                let selected = self
+                    .selected_index
+                    .and_then(|i| self.files.get(i))
+                    .map(|f| f.path.clone());
+                let order = self.sort_order;
+                reorder_around_pins(&mut self.files, |files| sort_files(files, order));
+                self.selected_index =
+                    selected.and_then(|path| self.files.iter().position(|f| f.path == path));
+                let reveal = self
+                    .selected_index
+                    .map_or_else(Task::none, |i| self.reveal_file(i));
+                Task::batch([self.generate_preview(), reveal])
+            }
+            Message::AnalyzeNames => {
+                let files = self.scoped_files();
+                if files.is_empty() {
+                    self.status_message = Some("Add files to analyze their names".to_string());
+                    self.status_level = StatusLevel::Info;
+                    return Task::none();
+                }
+                self.analysis = Some(analyze(&files));
+                self.show_settings = false;
+                Task::none()
+            }
+            Message::ApplySuggestion(i) => {
+                let Some(suggestion) = self.analysis.take().and_then(|mut a| {
+                    (i < a.suggestions.len()).then(|| a.suggestions.swap_remove(i))
+                }) else {
+                    return Task::none();
+                };
+                // Fills in the options so the usual preview can be checked before executing
+                match suggestion.action {
+                    SuggestedAction::FindReplace { find, replace } => {
+                        self.mode = AppMode::FindReplace;
+                        self.terms_mode = false;
+                        self.regex_mode = true;
+                        self.whole_word = false;
+                        self.find_pattern = find;
+                        self.find_caret = None;
+                        self.replace_with = replace;
+                    }
+                    SuggestedAction::Renumber { template, padding } => {
+                        self.mode = AppMode::Iteration;
+                        self.template = template;
+                        self.start_number = "1".to_string();
+                        self.padding = padding.to_string();
+                        self.match_existing_width = false;
+                    }
+                    SuggestedAction::Convert(convention) => {
+                        self.mode = AppMode::Convention;
+                        self.name_convention = convention;
                     }
                 }
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::CloseAnalysis => {
+                self.analysis = None;
                 Task::none()
             }
             Message::ClearFiles => {
-                self.files.clear();
+                // Pinned files survive the first Clear; clearing a pinned-only list empties it
+                let all_pinned = self.files.iter().all(|f| f.pinned);
+                let before = self.files.clone();
+                self.files.retain(|f| f.pinned && !all_pinned);
+                if self.files.len() < before.len() {
+                    self.cleared_files = Some(before);
+                }
                 self.selected_index = None;
-                self.previews.clear();
-                self.status_message = Some("All files cleared".to_string());
-                self.is_error = false;
-                Task::none()
+                self.marked_files.clear();
+                self.rename_progress.clear();
+                let task = self.generate_preview();
+                self.status_message = Some(if self.files.is_empty() {
+                    "All files cleared".to_string()
+                } else {
+                    format!(
+                        "Cleared; kept {} pinned file(s) (Clear again to remove them)",
+                        self.files.len()
+                    )
+                });
+                self.status_level = StatusLevel::Info;
+                task
             }
             Message::FindPatternChanged(p) => {
+                self.find_caret = Some(edit_caret(&self.find_pattern, &p));
                 self.find_pattern = p;
                 self.schedule_preview();
                 Task::none()
             }
+            Message::InsertRegexSnippet(snippet) => {
+                let caret = self
+                    .find_caret
+                    .unwrap_or_else(|| self.find_pattern.chars().count());
+                let (pattern, caret) = insert_at_caret(&self.find_pattern, caret, snippet.pattern);
+                self.find_pattern = pattern;
+                self.find_caret = Some(caret);
+                self.regex_mode = true;
+                let id = FocusTarget::FindInput.widget_id();
+                Task::batch([
+                    text_input::focus(id.clone()),
+                    text_input::move_cursor_to(id, caret),
+                    self.generate_preview(),
+                    self.save_settings_async(),
+                ])
+            }
             Message::ReplaceWithChanged(t) => {
                 self.replace_with = t;
                 self.schedule_preview();
                 Task::none()
             }
+            Message::SkipIfContainsChanged(t) => {
+                self.skip_if_contains = t;
+                self.schedule_preview();
+                Task::none()
+            }
+            Message::SwapFindReplace => {
+                std::mem::swap(&mut self.find_pattern, &mut self.replace_with);
+                self.find_caret = None;
+                self.generate_preview()
+            }
+            Message::RemoveCopyMarkers => {
+                // Fills in find/replace so the usual preview can be checked before executing
+                self.mode = AppMode::FindReplace;
+                self.terms_mode = false;
+                self.show_settings = false;
+                self.regex_mode = true;
+                self.find_pattern = COPY_MARKER_PATTERN.to_string();
+                self.find_caret = None;
+                self.replace_with = COPY_MARKER_REPLACEMENT.to_string();
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::WebSafeNames => {
+                // Picks the convention so the preview can be checked before executing
+                self.mode = AppMode::Convention;
+                self.show_settings = false;
+                self.name_convention = NameConvention::WebSafe;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::FolderPrefix => {
+                // Matches the start of every name and inserts the {folder} token there
+                self.mode = AppMode::FindReplace;
+                self.terms_mode = false;
+                self.show_settings = false;
+                self.regex_mode = true;
+                self.whole_word = false;
+                self.find_pattern = "^".to_string();
+                self.find_caret = None;
+                self.replace_with = format!("{}{}", FOLDER_TOKEN, self.folder_separator);
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
             Message::RegexModeToggled(e) => {
                 self.regex_mode = e;
-                self.generate_preview();
-                self.save_settings_async()
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ShowCapturesToggled(e) => {
+                self.show_captures = e;
+                self.refresh_capture_views();
+                Task::none()
             }
             Message::CaseSensitiveToggled(e) => {
                 self.case_sensitive = e;
-                self.generate_preview();
-                self.save_settings_async()
+                Task::batch([self.generate_preview(), self.save_settings_async()])
             }
-            Message::TemplateChanged(t) => {
-                self.template = t;
-                self.schedule_preview();
-                self.save_settings_async()
+            Message::LowercaseResultToggled(e) => {
+                self.lowercase_result = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
             }
-            Message::StartNumberChanged(n) => {
-                self.start_number = n;
-                self.schedule_preview();
-                self.save_settings_async()
+            Message::WholeWordToggled(e) => {
+                self.whole_word = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
             }
-            Message::PaddingChanged(p) => {
-                self.padding = p;
-                self.schedule_preview();
-                self.save_settings_async()
+            Message::CaseLocaleChanged(locale) => {
+                self.case_locale = locale;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
             }
-            Message::ExecuteRename => {
-                if self.previews.is_empty() {
-                    self.status_message = Some("No changes to apply".to_string());
-                    self.is_error = true;
+            Message::TermsModeToggled(e) => {
+                self.terms_mode = e;
+                self.generate_preview()
+            }
+            Message::TermInputChanged(t) => {
+                self.term_input = t;
+                Task::none()
+            }
+            Message::AddTerm => {
+                let term = std::mem::take(&mut self.term_input);
+                if term.is_empty() || self.find_terms.contains(&term) {
                     return Task::none();
                 }
-                for preview in &self.previews {
-                    if !can_modify_file(&preview.original_path) {
-                        self.status_message = Some(format!(
-                            "Access denied: {}",
-                            preview.original_path.display()
-                        ));
-                        self.is_error = true;
-                        return Task::none();
-                    }
+                if term.contains('\n') {
+                    self.status_message = Some("Terms can't contain line breaks".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
                 }
-                let previews = self.previews.clone();
-                Task::perform(
-                    async move { validate_and_rename(&previews).map_err(|e| e.to_string()) },
-                    Message::RenameCompleted,
-                )
+                self.find_terms.push(term);
+                let pattern =
+                    alternation_pattern(&self.find_terms, self.case_sensitive, self.case_locale);
+                if pattern.len() > MAX_PATTERN_LENGTH {
+                    self.term_input = self.find_terms.pop().unwrap_or_default();
+                    self.status_message = Some(format!(
+                        "Pattern too long (max {} chars)",
+                        MAX_PATTERN_LENGTH
+                    ));
+                    self.status_level = StatusLevel::Error;
+                    return Task::none();
+                }
+                self.generate_preview()
             }
-            Message::RenameCompleted(result) => {
-                match result {
-                    Ok(count) => {
-                        self.status_message = Some(format!("Renamed {} file(s)!", count));
-                        self.is_error = false;
-                        self.files.clear();
-                        self.previews.clear();
-                        self.selected_index = None;
-                    }
-                    Err(e) => {
-                        self.status_message = Some(format!("Error: {}", e));
-                        self.is_error = true;
-                    }
+            Message::RemoveTerm(index) => {
+                if index < self.find_terms.len() {
+                    self.find_terms.remove(index);
+                }
+                self.generate_preview()
+            }
+            Message::TermPresetSelected(name) => {
+                if let Some(preset) = self.term_presets.iter().find(|p| p.name == name) {
+                    self.find_terms = preset.terms.clone();
+                    self.preset_name = name;
                 }
+                self.generate_preview()
+            }
+            Message::TermPresetNameChanged(name) => {
+                self.preset_name = name.chars().take(MAX_PRESET_NAME_LENGTH).collect();
                 Task::none()
             }
-        }
-    }
-
-    // Generates rename preview based on current mode and settings
-    fn generate_preview(&mut self) {
-        self.previews.clear();
+            Message::SaveTermPreset => {
+                let name = self.preset_name.trim().to_string();
+                if name.is_empty() || self.find_terms.is_empty() {
+                    self.status_message = Some("Add terms and a preset name first".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                let preset = TermPreset {
+                    name: name.clone(),
+                    terms: self.find_terms.clone(),
+                };
+                match self.term_presets.iter_mut().find(|p| p.name == name) {
+                    Some(existing) => *existing = preset,
+                    None => {
+                        self.term_presets.push(preset);
+                        self.term_presets.sort_by(|a, b| a.name.cmp(&b.name));
+                    }
+                }
+                self.preset_name = name;
+                self.status_message = Some(format!("Saved preset \"{}\"", self.preset_name));
+                self.status_level = StatusLevel::Success;
+                self.save_settings_async()
+            }
+            Message::DeleteTermPreset => {
+                let before = self.term_presets.len();
+                self.term_presets.retain(|p| p.name != self.preset_name);
+                if self.term_presets.len() == before {
+                    return Task::none();
+                }
+                // Folders linked to the preset would otherwise point at nothing
+                let deleted = FolderOptions::Terms(self.preset_name.clone());
+                self.folder_presets.retain(|a| a.options != deleted);
+                self.status_message = Some(format!("Deleted preset \"{}\"", self.preset_name));
+                self.status_level = StatusLevel::Success;
+                self.preset_name.clear();
+                self.save_settings_async()
+            }
+            Message::ToggleFolderPreset => {
+                if !self.term_presets.iter().any(|p| p.name == self.preset_name) {
+                    self.status_message = Some("Choose a saved preset first".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                self.toggle_folder_link(FolderOptions::Terms(self.preset_name.clone()))
+            }
+            Message::ToggleFolderNumbering => match self.folder_numbering() {
+                Some(numbering) => self.toggle_folder_link(numbering),
+                None => {
+                    self.status_message = Some("Padding must be a number".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    Task::none()
+                }
+            },
+            Message::RemoveFolderPreset(index) => {
+                if index < self.folder_presets.len() {
+                    self.folder_presets.remove(index);
+                }
+                self.save_settings_async()
+            }
+            Message::UndoFolderPreset => {
+                let Some(undo) = self.folder_preset_undo.take() else {
+                    return Task::none();
+                };
+                self.mode = undo.mode;
+                self.terms_mode = undo.terms_mode;
+                self.find_terms = undo.find_terms;
+                self.preset_name = undo.preset_name;
+                self.template = undo.template;
+                self.padding = undo.padding;
+                self.status_message = Some(format!("Undid loading preset \"{}\"", undo.preset));
+                self.status_level = StatusLevel::Info;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ExtensionTemplatesChanged(templates) => {
+                self.extension_templates = templates;
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::AccessChecked(key, blocked) => {
+                // A check for a list that has changed since is dropped; a newer one follows
+                if key == file_set_key(&self.files) {
+                    self.blocked_files = blocked;
+                }
+                Task::none()
+            }
+            Message::HashProgress(generation, done) => {
+                if let Some((hashed, total)) = self.hashing.as_mut() {
+                    if generation == self.hash_generation {
+                        *hashed = done;
+                        self.status_message =
+                            Some(format!("Hashing files for {{hash}}: {} of {}", done, total));
+                        self.status_level = StatusLevel::Info;
+                    }
+                }
+                Task::none()
+            }
+            Message::HashesComputed(generation, hashes) => {
+                if generation != self.hash_generation {
+                    return Task::none();
+                }
+                self.hashing = None;
+                self.content_hashes.extend(hashes);
+                let preview = self.generate_preview();
+                Task::batch([preview, self.run_idle_execute()])
+            }
+            Message::AccessFilterChanged(filter) => {
+                self.access_filter = filter;
+                Task::none()
+            }
+            Message::NewExtensionChanged(extension) => {
+                self.new_extension = extension;
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::AddRule => {
+                if self.rename_rules.len() >= MAX_RULES {
+                    self.status_message = Some(format!("At most {} rules", MAX_RULES));
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                self.rename_rules.push(RenameRule::default());
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::RemoveRule(index) => {
+                if index < self.rename_rules.len() {
+                    self.rename_rules.remove(index);
+                }
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::MoveRuleUp(index) => {
+                if index > 0 && index < self.rename_rules.len() {
+                    self.rename_rules.swap(index - 1, index);
+                }
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::RuleChanged(index, field, value) => {
+                if let Some(rule) = self.rename_rules.get_mut(index) {
+                    match field {
+                        RuleField::Pattern => rule.pattern = value,
+                        RuleField::Template => rule.template = value,
+                    }
+                }
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::TemplateChanged(t) => {
+                self.template = t;
+                self.continue_start_number();
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::StartNumberChanged(n) => {
+                self.start_number = n;
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::PaddingChanged(p) => {
+                self.padding = p;
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::AddCounter => {
+                if self.counters.len() >= MAX_COUNTERS {
+                    self.status_message = Some(format!("At most {} counters", MAX_COUNTERS));
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                self.counters.push(Counter::default().into());
+                let token = format!("{{n{}}}", self.counters.len());
+                if !self.template.contains(&token) {
+                    self.template.push('_');
+                    self.template.push_str(&token);
+                }
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::RemoveCounter(index) => {
+                if index < self.counters.len() {
+                    self.counters.remove(index);
+                }
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::CounterChanged(index, field, value) => {
+                if let Some(counter) = self.counters.get_mut(index) {
+                    match field {
+                        CounterField::Start => counter.start = value,
+                        CounterField::Step => counter.step = value,
+                        CounterField::Padding => counter.padding = value,
+                    }
+                }
+                self.schedule_preview();
+                self.save_settings_async()
+            }
+            Message::SkipExistingToggled(e) => {
+                self.skip_existing = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::KeepNumberedToggled(e) => {
+                self.keep_numbered = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::MatchExistingWidthToggled(e) => {
+                self.match_existing_width = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::FitPaddingToggled(e) => {
+                self.fit_padding = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::CompoundExtensionsToggled(e) => {
+                self.compound_extensions = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ReverseNumberingToggled(e) => {
+                self.reverse_numbering = e;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ContinueNumberingToggled(e) => {
+                self.continue_numbering = e;
+                self.continue_start_number();
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ResetContinuation => {
+                if self
+                    .numbering_continuations
+                    .remove(&self.template)
+                    .is_none()
+                {
+                    return Task::none();
+                }
+                self.start_number = "1".to_string();
+                self.status_message = Some(format!("Numbering for {} starts over", self.template));
+                self.status_level = StatusLevel::Info;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ApplyTemplateSuggestion => match self.template_suggestion.take() {
+                Some(template) => self.update(Message::TemplateChanged(template.to_string())),
+                None => Task::none(),
+            },
+            Message::DismissTemplateSuggestion => {
+                self.template_suggestion = None;
+                Task::none()
+            }
+            Message::NumberGroupingChanged(grouping) => {
+                self.number_grouping = grouping;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::NameConventionChanged(convention) => {
+                self.name_convention = convention;
+                Task::batch([self.generate_preview(), self.save_settings_async()])
+            }
+            Message::ExecuteRename => {
+                // The status line already shows the running scan or rename
+                if self.is_renaming || self.is_scanning {
+                    return Task::none();
+                }
+                match self.preview_state {
+                    PreviewState::Ready => {}
+                    PreviewState::Running => {
+                        self.status_message = Some("Preview is still updating".to_string());
+                        self.status_level = StatusLevel::Warning;
+                        return Task::none();
+                    }
+                    PreviewState::TimedOut => {
+                        self.status_message =
+                            Some("Preview aborted; simplify the pattern first".to_string());
+                        self.status_level = StatusLevel::Warning;
+                        return Task::none();
+                    }
+                }
+                if self.changed_count() == 0 {
+                    self.status_message = Some("No changes to apply".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                if let Some(preview) = self.previews.iter().find(|p| p.invalid_reason.is_some()) {
+                    self.status_message = Some(format!(
+                        "Cannot rename {}: {}",
+                        preview.original_name,
+                        preview.invalid_reason.as_deref().unwrap_or_default()
+                    ));
+                    self.status_level = StatusLevel::Error;
+                    return Task::none();
+                }
+                // A new batch starts a fresh failure log
+                self.failed_renames.clear();
+                self.failure_log.clear();
+                // Recorded now, while the options still match the preview being renamed
+                self.pending_continuation =
+                    if self.mode == AppMode::Iteration && self.continue_numbering {
+                        Some((self.template.clone(), self.iteration_next))
+                    } else {
+                        None
+                    };
+                let count = self.changed_count();
+                let threshold = self.confirm_threshold.parse().unwrap_or(0);
+                if threshold > 0 && count > threshold {
+                    self.status_message = Some(format!(
+                        "{} files will be renamed; confirm to continue",
+                        count
+                    ));
+                    self.status_level = StatusLevel::Warning;
+                    self.large_batch_warning = Some(count);
+                    return Task::none();
+                }
+                self.check_protected_or_rename()
+            }
+            Message::PreviewFolderToggled(folder) => {
+                if !self.collapsed_folders.remove(&folder) {
+                    self.collapsed_folders.insert(folder);
+                }
+                Task::none()
+            }
+            Message::PreviewTreeToggled => {
+                self.preview_tree = !self.preview_tree;
+                self.save_settings_async()
+            }
+            Message::PreviewFilterToggled(filter) => {
+                if let Some(i) = self.preview_filters.iter().position(|&f| f == filter) {
+                    self.preview_filters.remove(i);
+                } else {
+                    self.preview_filters.push(filter);
+                }
+                Task::none()
+            }
+            Message::PreviewFiltersCleared => {
+                self.preview_filters.clear();
+                Task::none()
+            }
+            Message::PreviewFilterCycled => {
+                let next = match self.preview_filters.as_slice() {
+                    [] => Some(0),
+                    [only] => PreviewFilter::ALL
+                        .iter()
+                        .position(|f| f == only)
+                        .map(|i| i + 1)
+                        .filter(|&i| i < PreviewFilter::ALL.len()),
+                    _ => None,
+                };
+                self.preview_filters = next.map(|i| PreviewFilter::ALL[i]).into_iter().collect();
+                Task::none()
+            }
+            Message::ResultingListingToggled => {
+                self.preview_listing = !self.preview_listing;
+                self.refresh_folder_contents();
+                Task::none()
+            }
+            Message::AlignPreviewToggled => {
+                self.align_preview_names = !self.align_preview_names;
+                self.save_settings_async()
+            }
+            Message::PasteNames => iced::clipboard::read().map(Message::NamesPasted),
+            Message::VerifyClipboard => iced::clipboard::read().map(Message::ClipboardVerified),
+            Message::ClipboardVerified(clipboard) => {
+                self.verify_names(clipboard.unwrap_or_default());
+                Task::none()
+            }
+            Message::CloseNameCheck => {
+                self.name_check = None;
+                Task::none()
+            }
+            Message::RowMenuOpened(path, position) => {
+                self.row_menu = Some(RowMenu {
+                    path,
+                    position,
+                    editing: None,
+                });
+                Task::none()
+            }
+            Message::RowMenuClosed => {
+                self.row_menu = None;
+                Task::none()
+            }
+            Message::RowMenuAction(action) => self.row_menu_action(action),
+            Message::RowMenuEditChanged(name) => {
+                if let Some(menu) = &mut self.row_menu {
+                    menu.editing = Some(name);
+                }
+                Task::none()
+            }
+            Message::RowMenuEditSubmitted => {
+                if let Some(RowMenu {
+                    path,
+                    editing: Some(name),
+                    ..
+                }) = self.row_menu.take()
+                {
+                    self.edit_new_name(path, name.trim());
+                }
+                Task::none()
+            }
+            Message::NamesPasted(clipboard) => {
+                self.paste_names(clipboard.unwrap_or_default());
+                Task::none()
+            }
+            Message::ScriptFormatChanged(format) => {
+                self.script_format = format;
+                Task::none()
+            }
+            Message::ExportScript => {
+                if self.previews.is_empty() {
+                    self.status_message = Some("No changes to export".to_string());
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                let conflicts = self
+                    .previews
+                    .iter()
+                    .filter(|p| !p.conflicts.is_empty())
+                    .count();
+                if conflicts > 0 {
+                    self.status_message = Some(format!(
+                        "Resolve {} conflict(s) before exporting; the script would overwrite files",
+                        conflicts
+                    ));
+                    self.status_level = StatusLevel::Warning;
+                    return Task::none();
+                }
+                let extension = self.script_format.extension();
+                Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("Export Rename Script")
+                            .set_file_name(format!("rename.{}", extension))
+                            .add_filter("Script", &[extension])
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ScriptPathSelected,
+                )
+            }
+            Message::ScriptPathSelected(path) => {
+                if let Some(path) = path {
+                    let previews = self.previews.clone();
+                    let format = self.script_format;
+                    Task::perform(
+                        async move {
+                            export_script(&previews, format, &path)
+                                .map_err(|e| ErrorDetails::from_error(&e, vec![path.clone()]))
+                                .map(|()| path)
+                        },
+                        Message::ScriptExported,
+                    )
+                } else {
+                    Task::none()
+                }
+            }
+            Message::OpenDataFolder => {
+                match open_data_folder() {
+                    Ok(dir) => {
+                        self.status_message = Some(format!("Opened {}", dir.display()));
+                        self.status_level = StatusLevel::Info;
+                    }
+                    Err(e) => self.show_error(ErrorDetails::from_error(&e, Vec::new())),
+                }
+                Task::none()
+            }
+            Message::BackUpSettings => Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Back Up Settings")
+                        .set_file_name("file-rename-plus-settings.db")
+                        .add_filter("Settings database", &["db"])
+                        .save_file()
+                        .await
+                        .map(|f| f.path().to_path_buf())
+                },
+                Message::BackupPathSelected,
+            ),
+            Message::BackupPathSelected(path) => match path {
+                Some(path) => Task::perform(
+                    async move {
+                        backup_settings(&path)
+                            .map_err(|e| ErrorDetails::from_error(&e, vec![path.clone()]))
+                            .map(|()| path)
+                    },
+                    Message::SettingsBackedUp,
+                ),
+                None => Task::none(),
+            },
+            Message::SettingsBackedUp(result) => {
+                match result {
+                    Ok(path) => {
+                        self.status_message =
+                            Some(format!("Settings backed up to {}", path.display()));
+                        self.status_level = StatusLevel::Success;
+                    }
+                    Err(e) => self.show_error(e),
+                }
+                Task::none()
+            }
+            Message::RestoreSettings => Task::perform(
+                async move {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Restore Settings from Backup")
+                        .add_filter("Settings database", &["db"])
+                        .pick_file()
+                        .await
+                        .map(|f| f.path().to_path_buf())
+                },
+                Message::RestorePathSelected,
+            ),
+            Message::RestorePathSelected(path) => match path {
+                Some(path) => Task::perform(
+                    async move {
+                        restore_settings(&path)
+                            .map_err(|e| ErrorDetails::from_error(&e, vec![path.clone()]))
+                            .map(|()| path)
+                    },
+                    Message::SettingsRestored,
+                ),
+                None => Task::none(),
+            },
+            Message::SettingsRestored(result) => match result {
+                Ok(path) => {
+                    self.apply_settings(load_settings());
+                    self.status_message =
+                        Some(format!("Settings restored from {}", path.display()));
+                    self.status_level = StatusLevel::Success;
+                    self.generate_preview()
+                }
+                Err(e) => {
+                    self.show_error(e);
+                    Task::none()
+                }
+            },
+            Message::ScriptExported(result) => {
+                match result {
+                    Ok(path) => {
+                        self.status_message = Some(format!("Script saved: {}", path.display()));
+                        self.status_level = StatusLevel::Success;
+                    }
+                    Err(e) => self.show_error(e),
+                }
+                Task::none()
+            }
+            Message::RenameProgress(path, stage) => {
+                self.rename_progress.insert(path, stage);
+                let renamed = self
+                    .rename_progress
+                    .values()
+                    .filter(|&&s| s == RenameStage::Renamed)
+                    .count();
+                self.status_message = Some(format!(
+                    "Renamed {} of {} file(s)...",
+                    renamed,
+                    self.changed_count()
+                ));
+                Task::none()
+            }
+            Message::RenameCompleted(result) => match result {
+                Ok(report) => {
+                    self.is_renaming = false;
+                    self.settle_rename_progress(&report);
+                    self.apply_rename_locations(&report);
+                    let hook = self.post_rename_hook_task(&report);
+                    // The stashed list still has the old names
+                    self.cleared_files = None;
+                    self.selected_index = None;
+                    self.marked_files.clear();
+                    let mut summary = match report.failed.first() {
+                        None => format!("Renamed {} file(s)", report.renamed),
+                        Some(first) => format!(
+                            "Renamed {}, failed {} ({})",
+                            report.renamed,
+                            report.failed.len(),
+                            first.error
+                        ),
+                    };
+                    if !report.overwritten.is_empty() {
+                        summary.push_str(&format!(
+                            "; overwrote {} existing file(s)",
+                            report.overwritten.len()
+                        ));
+                    }
+                    if !report.trashed.is_empty() {
+                        summary.push_str(&format!(
+                            "; moved {} existing file(s) to the recycle bin",
+                            report.trashed.len()
+                        ));
+                    }
+                    if !report.skipped.is_empty() {
+                        summary.push_str(&format!(
+                            "; skipped {} file(s) whose name was taken",
+                            report.skipped.len()
+                        ));
+                    }
+                    if !report.verify_failed.is_empty() {
+                        summary.push_str(&format!(
+                            "; {} file(s) failed verification",
+                            report.verify_failed.len()
+                        ));
+                    }
+                    if !report.unrecorded.is_empty() {
+                        summary.push_str(&format!(
+                            "; couldn't record the original name of {} file(s)",
+                            report.unrecorded.len()
+                        ));
+                    }
+                    if let Some(started) = self.batch_started.take() {
+                        summary
+                            .push_str(&format!("; took {}", batch_timing(started, report.renamed)));
+                    }
+                    let clean = report.failed.is_empty() && report.verify_failed.is_empty();
+                    self.status_level = if !clean {
+                        StatusLevel::Error
+                    } else if !report.skipped.is_empty() || !report.unrecorded.is_empty() {
+                        StatusLevel::Warning
+                    } else {
+                        StatusLevel::Success
+                    };
+                    self.status_message = Some(if clean {
+                        format!("{}!", summary)
+                    } else {
+                        summary.clone()
+                    });
+                    self.failure_log.extend(
+                        report
+                            .failed
+                            .iter()
+                            .map(|f| format!("{}: {}", f.preview.original_name, f.error)),
+                    );
+                    if report.failed.is_empty() {
+                        self.failure_log.clear();
+                    }
+                    if !clean {
+                        let verify_lines = report
+                            .verify_failed
+                            .iter()
+                            .map(|v| format!("{}: verify failed: {}", v.path.display(), v.reason));
+                        self.append_error_details(ErrorDetails {
+                            lines: report
+                                .failed
+                                .iter()
+                                .map(|f| format!("{}: {}", f.preview.original_name, f.error))
+                                .chain(verify_lines)
+                                .collect(),
+                            paths: report
+                                .failed
+                                .iter()
+                                .map(|f| f.preview.original_path.clone())
+                                .chain(report.verify_failed.iter().map(|v| v.path.clone()))
+                                .collect(),
+                            // Each file failed on its own, possibly for different reasons
+                            kind: ErrorKind::Other,
+                        });
+                    }
+                    self.failed_renames = report.failed;
+                    // Nothing is left to apply until the options change again; input typed
+                    // during the rename is still waiting in the debounce
+                    self.previews.clear();
+                    self.clear_file_caches();
+                    // Numbering carries on only once the whole batch went through; failed
+                    // files keep the continuation pending for a retry
+                    let continued = match self.pending_continuation.take() {
+                        Some((template, next)) if self.failed_renames.is_empty() => {
+                            if template == self.template {
+                                self.start_number = next.to_string();
+                            }
+                            self.numbering_continuations.insert(template, next);
+                            self.save_settings_async()
+                        }
+                        pending => {
+                            self.pending_continuation = pending;
+                            Task::none()
+                        }
+                    };
+                    Task::batch([
+                        continued,
+                        hook,
+                        self.access_check_task(),
+                        self.notify_task("Rename finished", summary),
+                    ])
+                }
+                Err(details) => {
+                    self.is_renaming = false;
+                    // Refused before any file moved
+                    self.rename_progress.clear();
+                    let summary = details.to_string();
+                    if !self.failed_renames.is_empty() {
+                        self.failure_log.push(format!("Retry: {}", summary));
+                    }
+                    self.show_error(details);
+                    self.notify_task("Rename failed", summary)
+                }
+            },
+            Message::RetryFailedRenames => {
+                if self.is_renaming || self.is_scanning {
+                    return Task::none();
+                }
+                // Retried from where each file is now, toward its recorded target name
+                self.previews = self
+                    .failed_renames
+                    .iter()
+                    .map(|f| f.preview.clone())
+                    .collect();
+                let fold_case = self.fold_case();
+                mark_disk_conflicts(&mut self.previews, fold_case);
+                self.start_rename()
+            }
+            Message::DismissFailedRenames => {
+                self.failed_renames.clear();
+                self.pending_continuation = None;
+                self.failure_log.clear();
+                self.previews.clear();
+                self.status_message = None;
+                self.status_level = StatusLevel::Info;
+                Task::none()
+            }
+            Message::ErrorDetailsToggled => {
+                self.error_details_open = !self.error_details_open;
+                Task::none()
+            }
+            Message::CopyErrorDetails => match &self.error_details {
+                Some(details) => {
+                    let content = details.to_clipboard_text();
+                    self.copy_to_clipboard("error details", content)
+                }
+                None => Task::none(),
+            },
+            Message::CopyStatus => match self.status_message.clone() {
+                Some(status) => self.copy_to_clipboard("status message", status),
+                None => Task::none(),
+            },
+            Message::CopyNoticeTick => {
+                if self.copy_notice.as_ref().is_some_and(|(_, copied)| {
+                    copied.elapsed() >= Duration::from_millis(COPY_NOTICE_MS)
+                }) {
+                    self.copy_notice = None;
+                }
+                Task::none()
+            }
+            Message::DismissErrorDetails => {
+                self.error_details = None;
+                self.error_details_open = false;
+                Task::none()
+            }
+        }
+    }
+
+    // Moves keyboard focus to the target, syncing iced's text input focus
+    fn set_focus(&mut self, target: FocusTarget) -> Task<Message> {
+        self.focus = Some(target);
+        if target == FocusTarget::FileList
+            && self.selected_index.is_none()
+            && !self.files.is_empty()
+        {
+            self.selected_index = Some(0);
+            if self.selection_only {
+                return Task::batch([focus_input(target), self.generate_preview()]);
+            }
+        }
+        focus_input(target)
+    }
+
+    // Activates the focused control as if it were clicked (Enter / Space)
+    fn activate(&mut self, target: FocusTarget) -> Task<Message> {
+        let message = match target {
+            // Rescans the most recent folder
+            FocusTarget::RecentFolderPicker => match self.recent_folders.first() {
+                Some(folder) => Message::FolderSelected(Some(folder.clone())),
+                None => return Task::none(),
+            },
+            FocusTarget::RemoveCopyMarkers => Message::RemoveCopyMarkers,
+            FocusTarget::FolderPrefix => Message::FolderPrefix,
+            FocusTarget::WebSafeNames => Message::WebSafeNames,
+            FocusTarget::ThemeToggle => Message::ToggleTheme,
+            FocusTarget::SettingsToggle => Message::ToggleSettings,
+            FocusTarget::ModePicker => Message::ModeChanged(match self.mode {
+                AppMode::FindReplace => AppMode::Iteration,
+                AppMode::Iteration => AppMode::Convention,
+                AppMode::Convention => AppMode::ByExtension,
+                AppMode::ByExtension => AppMode::ChangeExtension,
+                AppMode::ChangeExtension => AppMode::Rules,
+                AppMode::Rules => AppMode::FindReplace,
+            }),
+            FocusTarget::AddFolder => Message::AddFolder,
+            FocusTarget::ImportPathList => Message::ImportPathList,
+            FocusTarget::SortByName => Message::SortByName,
+            FocusTarget::AnalyzeNames => Message::AnalyzeNames,
+            FocusTarget::ApplySuggestion(i) => Message::ApplySuggestion(i),
+            FocusTarget::CloseAnalysis => Message::CloseAnalysis,
+            FocusTarget::ClearFiles => Message::ClearFiles,
+            FocusTarget::MoveUp => Message::MoveUp,
+            FocusTarget::MoveDown => Message::MoveDown,
+            FocusTarget::RemoveFile => Message::RemoveFile,
+            FocusTarget::PinFile => Message::TogglePin,
+            FocusTarget::PreviewTreeToggle => Message::PreviewTreeToggled,
+            FocusTarget::ResultingListingToggle => Message::ResultingListingToggled,
+            FocusTarget::RunWhenIdle => Message::IdleRunToggled,
+            FocusTarget::PreviewFilters => Message::PreviewFilterCycled,
+            FocusTarget::AlignPreviewToggle => Message::AlignPreviewToggled,
+            FocusTarget::PasteNames => Message::PasteNames,
+            FocusTarget::VerifyClipboard => Message::VerifyClipboard,
+            FocusTarget::CloseNameCheck => Message::CloseNameCheck,
+            FocusTarget::ScriptFormatPicker => {
+                Message::ScriptFormatChanged(match self.script_format {
+                    ScriptFormat::Shell => ScriptFormat::Batch,
+                    ScriptFormat::Batch => ScriptFormat::Shell,
+                })
+            }
+            FocusTarget::ExportScript => Message::ExportScript,
+            FocusTarget::SwapFindReplace => Message::SwapFindReplace,
+            FocusTarget::RegexToggle => Message::RegexModeToggled(!self.regex_mode),
+            FocusTarget::CaseToggle => Message::CaseSensitiveToggled(!self.case_sensitive),
+            FocusTarget::LowercaseToggle => Message::LowercaseResultToggled(!self.lowercase_result),
+            FocusTarget::WholeWordToggle => Message::WholeWordToggled(!self.whole_word),
+            FocusTarget::ShowCapturesToggle => Message::ShowCapturesToggled(!self.show_captures),
+            FocusTarget::CaseLocalePicker => Message::CaseLocaleChanged(match self.case_locale {
+                CaseLocale::Unicode => CaseLocale::Turkic,
+                CaseLocale::Turkic => CaseLocale::Unicode,
+            }),
+            FocusTarget::TermsModeToggle => Message::TermsModeToggled(!self.terms_mode),
+            FocusTarget::TermPresetPicker => {
+                // Steps through saved presets in order
+                let next = self
+                    .term_presets
+                    .iter()
+                    .position(|p| p.name == self.preset_name)
+                    .map_or(0, |i| (i + 1) % self.term_presets.len());
+                match self.term_presets.get(next) {
+                    Some(preset) => Message::TermPresetSelected(preset.name.clone()),
+                    None => return Task::none(),
+                }
+            }
+            FocusTarget::SaveTermPreset => Message::SaveTermPreset,
+            FocusTarget::DeleteTermPreset => Message::DeleteTermPreset,
+            FocusTarget::ToggleFolderPreset => Message::ToggleFolderPreset,
+            FocusTarget::ToggleFolderNumbering => Message::ToggleFolderNumbering,
+            FocusTarget::UndoFolderPreset => Message::UndoFolderPreset,
+            FocusTarget::SkipExistingToggle => Message::SkipExistingToggled(!self.skip_existing),
+            FocusTarget::KeepNumberedToggle => Message::KeepNumberedToggled(!self.keep_numbered),
+            FocusTarget::MatchWidthToggle => {
+                Message::MatchExistingWidthToggled(!self.match_existing_width)
+            }
+            FocusTarget::FitPaddingToggle => Message::FitPaddingToggled(!self.fit_padding),
+            FocusTarget::CompoundExtensionsToggle => {
+                Message::CompoundExtensionsToggled(!self.compound_extensions)
+            }
+            FocusTarget::ReverseNumberingToggle => {
+                Message::ReverseNumberingToggled(!self.reverse_numbering)
+            }
+            FocusTarget::ContinueNumberingToggle => {
+                Message::ContinueNumberingToggled(!self.continue_numbering)
+            }
+            FocusTarget::ResetContinuation => Message::ResetContinuation,
+            FocusTarget::ApplyTemplateSuggestion => Message::ApplyTemplateSuggestion,
+            FocusTarget::DismissTemplateSuggestion => Message::DismissTemplateSuggestion,
+            FocusTarget::NumberGroupingPicker => {
+                Message::NumberGroupingChanged(match self.number_grouping {
+                    NumberGrouping::None => NumberGrouping::Folder,
+                    NumberGrouping::Folder => NumberGrouping::Extension,
+                    NumberGrouping::Extension => NumberGrouping::None,
+                })
+            }
+            FocusTarget::AddCounter => Message::AddCounter,
+            FocusTarget::AddRule => Message::AddRule,
+            FocusTarget::ConventionPicker => {
+                Message::NameConventionChanged(match self.name_convention {
+                    NameConvention::Snake => NameConvention::Kebab,
+                    NameConvention::Kebab => NameConvention::Camel,
+                    NameConvention::Camel => NameConvention::Pascal,
+                    NameConvention::Pascal => NameConvention::WebSafe,
+                    NameConvention::WebSafe => NameConvention::Snake,
+                })
+            }
+            FocusTarget::SelectionOnlyToggle => Message::SelectionOnlyToggled(!self.selection_only),
+            FocusTarget::Execute => Message::ExecuteRename,
+            FocusTarget::OrderedCommitToggle => Message::OrderedCommitToggled(!self.ordered_commit),
+            FocusTarget::SortOrderPicker => Message::SortOrderChanged(match self.sort_order {
+                SortOrder::Natural => SortOrder::Lexicographic,
+                SortOrder::Lexicographic => SortOrder::AsAdded,
+                SortOrder::AsAdded => SortOrder::Natural,
+            }),
+            FocusTarget::PreviewArrowPicker => {
+                Message::PreviewArrowChanged(match self.preview_arrow {
+                    PreviewArrow::Ascii => PreviewArrow::Arrow,
+                    PreviewArrow::Arrow => PreviewArrow::DoubleArrow,
+                    PreviewArrow::DoubleArrow => PreviewArrow::Ascii,
+                })
+            }
+            FocusTarget::TruncateNamesToggle => Message::TruncateNamesToggled(!self.truncate_names),
+            FocusTarget::ImportKeepOrderToggle => {
+                Message::ImportKeepOrderToggled(!self.import_keep_order)
+            }
+            FocusTarget::ProbeDirectoriesToggle => {
+                Message::ProbeDirectoriesToggled(!self.probe_directories)
+            }
+            FocusTarget::VerifyRenamesToggle => Message::VerifyRenamesToggled(!self.verify_renames),
+            FocusTarget::RecordOriginalNamesToggle => {
+                Message::RecordOriginalNamesToggled(!self.record_original_names)
+            }
+            FocusTarget::OpenDataFolder => Message::OpenDataFolder,
+            FocusTarget::BackUpSettings => Message::BackUpSettings,
+            FocusTarget::RestoreSettings => Message::RestoreSettings,
+            FocusTarget::NumberDuplicatesToggle => {
+                Message::NumberDuplicatesToggled(!self.number_duplicates)
+            }
+            FocusTarget::StrictCaseToggle => Message::StrictCaseToggled(!self.strict_case_check),
+            FocusTarget::NotifyToggle => Message::NotifyToggled(!self.notify_on_completion),
+            FocusTarget::CancelLargeBatch => Message::CancelLargeBatch,
+            FocusTarget::CancelClearFiles => Message::CancelClearFiles,
+            FocusTarget::ConfirmClearFiles => Message::ConfirmClearFiles,
+            FocusTarget::CopyStatus => Message::CopyStatus,
+            FocusTarget::RestoreClearedFiles => Message::RestoreClearedFiles,
+            FocusTarget::ConfirmLargeBatch => Message::ConfirmLargeBatch,
+            FocusTarget::CancelIdleNewFiles => Message::CancelIdleNewFiles,
+            FocusTarget::ConfirmIdleNewFiles => Message::ConfirmIdleNewFiles,
+            FocusTarget::CancelProtected => Message::CancelProtectedRename,
+            FocusTarget::AcknowledgeProtected => {
+                Message::ProtectedAcknowledged(!self.protected_acknowledged)
+            }
+            FocusTarget::ConfirmProtected => Message::ConfirmProtectedRename,
+            FocusTarget::CancelConflicts => Message::CancelConflicts,
+            FocusTarget::ConfirmConflicts => Message::ConfirmConflicts,
+            FocusTarget::ConflictPolicyPicker => {
+                Message::ConflictPolicyChanged(match self.conflict_policy {
+                    ConflictPolicy::Abort => ConflictPolicy::Skip,
+                    ConflictPolicy::Skip => ConflictPolicy::Trash,
+                    ConflictPolicy::Trash => ConflictPolicy::Overwrite,
+                    ConflictPolicy::Overwrite => ConflictPolicy::Abort,
+                })
+            }
+            FocusTarget::DismissFailures => Message::DismissFailedRenames,
+            FocusTarget::ErrorDetailsToggle => Message::ErrorDetailsToggled,
+            FocusTarget::ErrorSuggestion => {
+                match self.error_details.as_ref().and_then(ErrorDetails::suggestion) {
+                    Some((_, message)) => message,
+                    None => return Task::none(),
+                }
+            }
+            FocusTarget::CopyErrorDetails => Message::CopyErrorDetails,
+            FocusTarget::DismissErrorDetails => Message::DismissErrorDetails,
+            // Steps through the groups, then back to all files
+            FocusTarget::ExtensionGroups => {
+                let groups = parse_extension_groups(&self.extension_groups);
+                let next = self.active_extension_group(&groups).map_or(0, |i| i + 1);
+                Message::ExtensionGroupSelected((next < groups.len()).then_some(next))
+            }
+            FocusTarget::AccessFilterPicker => Message::AccessFilterChanged(match self.access_filter {
+                AccessFilter::All => AccessFilter::Writable,
+                AccessFilter::Writable => AccessFilter::Locked,
+                AccessFilter::Locked => AccessFilter::All,
+            }),
+            FocusTarget::RetryFailed => Message::RetryFailedRenames,
+            FocusTarget::FileList
+            | FocusTarget::PreviewList
+            // The snippet menu is opened with the mouse
+            | FocusTarget::RegexSnippetPicker
+            | FocusTarget::FindInput
+            | FocusTarget::ReplaceInput
+            | FocusTarget::SkipIfContainsInput
+            | FocusTarget::TemplateInput
+            | FocusTarget::ExtensionTemplatesInput
+            | FocusTarget::NewExtensionInput
+            | FocusTarget::StartInput
+            | FocusTarget::PaddingInput
+            | FocusTarget::PreviewBudgetInput
+            | FocusTarget::MaxNameWidthInput
+            | FocusTarget::PerFolderLimitInput
+            | FocusTarget::ConfirmThresholdInput
+            | FocusTarget::IdleQuietInput
+            | FocusTarget::ProtectedRootsInput
+            | FocusTarget::ExtensionFilterInput
+            | FocusTarget::ExtensionGroupsInput
+            | FocusTarget::FolderSeparatorInput
+            | FocusTarget::PostRenameHookInput
+            | FocusTarget::TermPresetNameInput => return Task::none(),
+        };
+        self.update(message)
+    }
+
+    // Wraps a control with the focus ring used for keyboard navigation
+    fn focusable<'a>(
+        &self,
+        target: FocusTarget,
+        content: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        container(content)
+            .padding(SPACING_XS)
+            .style(focus_ring_style(self.focus == Some(target)))
+            .into()
+    }
+
+    // Content hashes of the files for {hash}, empty when `source` doesn't use it
+    fn hashes_for(&self, files: &[FileEntry], source: &str) -> HashMap<PathBuf, String> {
+        if !uses_content_hash(source) {
+            return HashMap::new();
+        }
+        files
+            .iter()
+            .filter_map(|f| {
+                let hash = self.content_hashes.get(&f.path)?.clone()?;
+                Some((f.path.clone(), hash))
+            })
+            .collect()
+    }
+
+    // Iteration previews for the current options, with the start number that would carry
+    // the sequence on after them
+    fn iteration_previews(&self) -> anyhow::Result<(Vec<RenamePreview>, u32)> {
+        let files = self.scoped_files();
+        let taken = self
+            .skip_existing
+            .then(|| existing_paths(&files, self.fold_case()));
+        // The width is read from the folder of the first file
+        let padding = self
+            .match_existing_width
+            .then(|| files.first().and_then(|f| f.path.parent()))
+            .flatten()
+            .and_then(|dir| detect_existing_number_width(dir, &self.template))
+            .unwrap_or_else(|| self.padding.parse().unwrap_or(3));
+        let options = NumberingOptions {
+            start_number: self.start_number.parse().unwrap_or(1),
+            padding,
+            counters: self.counters.iter().map(CounterFields::parse).collect(),
+            compound_extensions: self.compound_extensions,
+            reverse: self.reverse_numbering,
+            grouping: self.number_grouping,
+            keep_matching: self.keep_numbered,
+            fit_padding: self.fit_padding,
+            hashes: Arc::new(self.hashes_for(&files, &self.template)),
+            fold_case: self.fold_case(),
+        };
+        apply_iteration_numbering_continued(&files, &self.template, &options, taken.as_ref())
+    }
+
+    // Pre-fills the start number from the last successful run of the current template
+    fn continue_start_number(&mut self) {
+        if !self.continue_numbering {
+            return;
+        }
+        if let Some(next) = self.numbering_continuations.get(&self.template) {
+            self.start_number = next.to_string();
+        }
+    }
+
+    // Generates rename preview based on current mode and settings.
+    // Find/replace matching runs in the background under a time budget.
+    fn generate_preview(&mut self) -> Task<Message> {
+        // The listed files are moving; the preview is rebuilt once the rename reports back
+        if self.is_renaming {
+            self.schedule_preview();
+            return Task::none();
+        }
+        // Any in-flight find/replace job is now stale
+        self.preview_generation = self.preview_generation.wrapping_add(1);
+        self.preview_state = PreviewState::Ready;
+        // A pending confirmation no longer matches what would be renamed
+        self.large_batch_warning = None;
+        self.name_check = None;
+        self.protected_warning = None;
+        self.conflict_choices = None;
+        self.clear_warning = None;
+        self.protected_folders = self.protected_folders_of(self.files.iter().map(|f| &f.path));
+        self.protected_notice = self.protected_folders.first().cloned();
+        self.refresh_capture_views();
+        if self.mode == AppMode::Iteration {
+            self.template_lint =
+                lint_template(&self.template, self.counters.len(), self.number_grouping);
+        }
+        let files_key = file_set_key(&self.files);
+        if files_key != self.mode_states_key {
+            self.mode_states.clear();
+            self.mode_states_key = files_key;
+            // Asks the filesystem, so only when the files change
+            self.network_notice = network_folder(self.files.iter().map(|f| f.path.as_path()));
+            let listed: HashSet<&Path> = self.files.iter().map(|f| f.path.as_path()).collect();
+            self.marked_files
+                .retain(|path| listed.contains(path.as_path()));
+        }
+        self.refresh_folder_contents();
+
         if self.files.is_empty() {
+            self.previews.clear();
+            return Task::none();
+        }
+        if self.mode == AppMode::FindReplace && self.find_params().0.is_empty() {
+            self.previews.clear();
+            self.restore_edits();
+            self.status_message = Some("Enter a pattern to find".to_string());
+            return Task::none();
+        }
+        // {hash} needs every file's contents read first, which runs in the background
+        let hash_source = match self.mode {
+            AppMode::FindReplace => Some(self.find_params().1),
+            AppMode::Iteration => Some(self.template.clone()),
+            _ => None,
+        };
+        if hash_source.is_some_and(|source| uses_content_hash(&source)) {
+            let missing: Vec<PathBuf> = self
+                .scoped_files()
+                .iter()
+                .filter(|f| !self.content_hashes.contains_key(&f.path))
+                .map(|f| f.path.clone())
+                .collect();
+            if !missing.is_empty() {
+                self.previews.clear();
+                // A pass already running regenerates the preview when it ends
+                return match self.hashing {
+                    Some(_) => Task::none(),
+                    None => self.content_hash_task(missing),
+                };
+            }
+        }
+
+        // Skipping taken numbers and matching their width read the folders, so a changed
+        // listing invalidates the cached numbering
+        if self.mode == AppMode::Iteration && (self.skip_existing || self.match_existing_width) {
+            let stamp = listing_stamp(&self.scoped_files());
+            if stamp != self.listing_stamp {
+                self.listing_stamp = stamp;
+                self.preview_cache.remove(&AppMode::Iteration);
+            }
+        }
+        // Reuse the last result for this mode when neither the list nor its parameters changed
+        self.preview_key = self.preview_cache_key();
+        if let Some((key, previews)) = self.preview_cache.get(&self.mode) {
+            if *key == self.preview_key {
+                self.previews = previews.clone();
+                self.restore_edits();
+                self.report_preview_status();
+                return Task::none();
+            }
+        }
+        #[cfg(test)]
+        {
+            self.preview_computations += 1;
+        }
+
+        match self.mode {
+            AppMode::FindReplace => {
+                self.preview_state = PreviewState::Running;
+                let generation = self.preview_generation;
+                let files = self.scoped_files().to_vec();
+                let (pattern, replacement, use_regex) = self.find_params();
+                let skip_if_contains = self.skip_if_contains.clone();
+                let hashes = self.hashes_for(&files, &replacement);
+                let options = FindOptions {
+                    use_regex,
+                    case_sensitive: self.case_sensitive,
+                    case_locale: self.case_locale,
+                    whole_word: self.whole_word,
+                    lowercase: self.lowercase_result,
+                };
+                let number_duplicates = self.number_duplicates;
+                let fold_case = self.fold_case();
+                let deadline = Instant::now() + Duration::from_millis(self.preview_budget_ms());
+                Task::perform(
+                    async move {
+                        match apply_find_replace(
+                            &files,
+                            &pattern,
+                            &replacement,
+                            &skip_if_contains,
+                            options,
+                            &hashes,
+                            Some(deadline),
+                        ) {
+                            Ok(mut p) => {
+                                finish_previews(&mut p, number_duplicates, fold_case);
+                                PreviewOutcome::Ready(p)
+                            }
+                            Err(e) if e.is::<PreviewTimeout>() => PreviewOutcome::TimedOut,
+                            Err(e) => {
+                                PreviewOutcome::Failed(ErrorDetails::from_error(&e, Vec::new()))
+                            }
+                        }
+                    },
+                    move |outcome| Message::PreviewComputed(generation, outcome),
+                )
+            }
+            AppMode::Iteration => {
+                self.previews.clear();
+                match self.iteration_previews() {
+                    Ok((mut p, next)) => {
+                        finish_previews(&mut p, self.number_duplicates, self.fold_case());
+                        self.previews = p;
+                        self.iteration_next = next;
+                        self.preview_cache
+                            .insert(self.mode, (self.preview_key, self.previews.clone()));
+                        self.restore_edits();
+                        self.report_preview_status();
+                    }
+                    Err(e) => self.show_error(ErrorDetails::from_error(&e, Vec::new())),
+                }
+                Task::none()
+            }
+            AppMode::ByExtension => {
+                self.previews.clear();
+                let templates = parse_extension_templates(&self.extension_templates);
+                let options = NumberingOptions {
+                    start_number: self.start_number.parse().unwrap_or(1),
+                    padding: self.padding.parse().unwrap_or(3),
+                    counters: self.counters.iter().map(CounterFields::parse).collect(),
+                    ..NumberingOptions::default()
+                };
+                match apply_extension_templates(&self.scoped_files(), &templates, &options, None) {
+                    Ok(mut p) => {
+                        finish_previews(&mut p, self.number_duplicates, self.fold_case());
+                        self.previews = p;
+                        self.preview_cache
+                            .insert(self.mode, (self.preview_key, self.previews.clone()));
+                        self.restore_edits();
+                        self.report_preview_status();
+                    }
+                    Err(e) => self.show_error(ErrorDetails::from_error(&e, Vec::new())),
+                }
+                Task::none()
+            }
+            AppMode::Convention => {
+                let mut p = apply_convention(&self.scoped_files(), self.name_convention);
+                finish_previews(&mut p, self.number_duplicates, self.fold_case());
+                self.previews = p;
+                self.preview_cache
+                    .insert(self.mode, (self.preview_key, self.previews.clone()));
+                self.restore_edits();
+                self.report_preview_status();
+                Task::none()
+            }
+            AppMode::Rules => {
+                self.previews.clear();
+                match apply_rules(&self.scoped_files(), &self.rename_rules) {
+                    Ok(mut p) => {
+                        finish_previews(&mut p, self.number_duplicates, self.fold_case());
+                        self.previews = p;
+                        self.preview_cache
+                            .insert(self.mode, (self.preview_key, self.previews.clone()));
+                        self.restore_edits();
+                        self.report_preview_status();
+                    }
+                    Err(e) => self.show_error(ErrorDetails::from_error(&e, Vec::new())),
+                }
+                Task::none()
+            }
+            AppMode::ChangeExtension => {
+                let mut p = apply_extension_change(
+                    &self.scoped_files(),
+                    &self.new_extension,
+                    self.compound_extensions,
+                );
+                finish_previews(&mut p, self.number_duplicates, self.fold_case());
+                self.previews = p;
+                self.preview_cache
+                    .insert(self.mode, (self.preview_key, self.previews.clone()));
+                self.restore_edits();
+                self.report_preview_status();
+                Task::none()
+            }
+        }
+    }
+
+    // Re-reads the previewed folders for the resulting listing, only while it's shown
+    fn refresh_folder_contents(&mut self) {
+        self.folder_contents = if self.preview_listing {
+            folder_contents(self.files.iter().map(|f| f.path.as_path()))
+        } else {
+            HashMap::new()
+        };
+    }
+
+    // Whether on-disk names are compared ignoring letter case
+    fn fold_case(&self) -> bool {
+        CASE_INSENSITIVE_OS || self.strict_case_check
+    }
+
+    // Puts this mode's pasted names back over a regenerated preview
+    fn restore_edits(&mut self) {
+        let files = self.scoped_files().into_owned();
+        if let Some(state) = self.mode_states.get(&self.mode) {
+            apply_edits(&mut self.previews, &files, &state.edits);
+            let fold_case = self.fold_case();
+            mark_disk_conflicts(&mut self.previews, fold_case);
+        }
+    }
+
+    // Applies a finished background find/replace preview unless it was superseded
+    fn apply_preview_outcome(&mut self, generation: u64, outcome: PreviewOutcome) {
+        if generation != self.preview_generation {
+            return;
+        }
+        match outcome {
+            PreviewOutcome::Ready(p) => {
+                self.preview_state = PreviewState::Ready;
+                self.preview_cache
+                    .insert(self.mode, (self.preview_key, p.clone()));
+                self.previews = p;
+                self.restore_edits();
+                self.report_preview_status();
+            }
+            PreviewOutcome::TimedOut => {
+                // Previous results stay visible but can't be executed
+                self.preview_state = PreviewState::TimedOut;
+                self.status_message = Some("Pattern too slow — preview aborted".to_string());
+                self.status_level = StatusLevel::Warning;
+            }
+            PreviewOutcome::Failed(e) => {
+                self.preview_state = PreviewState::Ready;
+                self.previews.clear();
+                self.show_error(e);
+            }
+        }
+    }
+
+    // Pattern, replacement and regex flag for find/replace. The "match any of" terms
+    // become an escaped alternation whose replacement is taken literally.
+    fn find_params(&self) -> (String, String, bool) {
+        if self.terms_mode {
+            let pattern =
+                alternation_pattern(&self.find_terms, self.case_sensitive, self.case_locale);
+            (pattern, self.replace_with.replace('$', "$$"), true)
+        } else {
+            (
+                self.find_pattern.clone(),
+                self.replace_with.clone(),
+                self.regex_mode,
+            )
+        }
+    }
+
+    // Recomputes the capture view for the typed regex; "match any of" terms have no groups
+    fn refresh_capture_views(&mut self) {
+        self.capture_views = if self.show_captures && self.regex_mode && !self.terms_mode {
+            let options = FindOptions {
+                use_regex: true,
+                case_sensitive: self.case_sensitive,
+                case_locale: self.case_locale,
+                whole_word: self.whole_word,
+                lowercase: false,
+            };
+            let files = self.scoped_files();
+            capture_views(&files, &self.find_pattern, options, CAPTURE_VIEW_FILES)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+    }
+
+    // Files the preview covers: just the selected one with "selection only" on, else all,
+    // less those excluded from their row menu
+    fn scoped_files(&self) -> Cow<'_, [FileEntry]> {
+        let marked = self.selection_only && !self.marked_files.is_empty();
+        let files = match self
+            .selected_index
+            .filter(|_| self.selection_only && !marked)
+        {
+            Some(i) if i < self.files.len() => std::slice::from_ref(&self.files[i]),
+            _ => &self.files,
+        };
+        let extensions = parse_extension_list(&self.extension_filter);
+        if !marked && extensions.is_empty() && self.excluded.is_empty() {
+            return Cow::Borrowed(files);
+        }
+        files
+            .iter()
+            .filter(|f| !marked || self.marked_files.contains(&f.path))
+            .filter(|f| extensions.is_empty() || matches_extensions(&f.path, &extensions))
+            .filter(|f| !self.excluded.contains(&f.path))
+            .cloned()
+            .collect()
+    }
+
+    // A plain click selects one file, Ctrl-click adds or drops one, and Shift-click
+    // selects the run from the file clicked before
+    fn select_file(&mut self, index: usize) {
+        let Some(path) = self.files.get(index).map(|f| f.path.clone()) else {
+            return;
+        };
+        if self.modifiers.shift() {
+            if let Some(anchor) = self.selected_index.filter(|&i| i < self.files.len()) {
+                let run = anchor.min(index)..=anchor.max(index);
+                self.marked_files = self.files[run].iter().map(|f| f.path.clone()).collect();
+                return;
+            }
+        } else if self.modifiers.command() {
+            if self.marked_files.is_empty() {
+                if let Some(current) = self.selected_index.and_then(|i| self.files.get(i)) {
+                    self.marked_files.insert(current.path.clone());
+                }
+            }
+            if self.marked_files.insert(path.clone()) {
+                self.selected_index = Some(index);
+                return;
+            }
+            self.marked_files.remove(&path);
+            // The current row stays one that is still selected
+            if let Some(i) = self
+                .files
+                .iter()
+                .position(|f| self.marked_files.contains(&f.path))
+            {
+                self.selected_index = Some(i);
+                return;
+            }
+        }
+        self.marked_files.clear();
+        self.selected_index = Some(index);
+    }
+
+    // Whether the row at `index` is part of the selection
+    fn is_selected(&self, index: usize) -> bool {
+        if self.marked_files.is_empty() {
+            return self.selected_index == Some(index);
+        }
+        self.files
+            .get(index)
+            .is_some_and(|f| self.marked_files.contains(&f.path))
+    }
+
+    // Group whose extensions are exactly the current filter
+    fn active_extension_group(&self, groups: &[ExtensionGroup]) -> Option<usize> {
+        let extensions = parse_extension_list(&self.extension_filter);
+        groups.iter().position(|g| g.extensions == extensions)
+    }
+
+    // Hash of the previewed files, their order and the active mode's parameters. Files
+    // rewritten in place keep their key; rescans and renames clear the cache instead.
+    fn preview_cache_key(&self) -> u64 {
+        let files = self.scoped_files();
+        let hashes: Vec<_> = files
+            .iter()
+            .map(|f| self.content_hashes.get(&f.path))
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        (self.mode, self.number_duplicates, self.fold_case()).hash(&mut hasher);
+        for file in files.iter() {
+            file.path.hash(&mut hasher);
+        }
+        match self.mode {
+            AppMode::FindReplace => (
+                self.find_params(),
+                &self.skip_if_contains,
+                self.case_sensitive,
+                self.case_locale,
+                self.whole_word,
+                self.lowercase_result,
+                &hashes,
+            )
+                .hash(&mut hasher),
+            AppMode::Iteration => (
+                &self.template,
+                &self.start_number,
+                &self.padding,
+                &self.counters,
+                self.skip_existing,
+                self.keep_numbered,
+                self.match_existing_width,
+                self.fit_padding,
+                self.compound_extensions,
+                self.reverse_numbering,
+                self.number_grouping,
+                &hashes,
+            )
+                .hash(&mut hasher),
+            AppMode::Convention => self.name_convention.hash(&mut hasher),
+            AppMode::ChangeExtension => {
+                (&self.new_extension, self.compound_extensions).hash(&mut hasher)
+            }
+            AppMode::Rules => self.rename_rules.hash(&mut hasher),
+            AppMode::ByExtension => (
+                &self.extension_templates,
+                &self.start_number,
+                &self.padding,
+                &self.counters,
+            )
+                .hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+
+    // Summarizes the current previews in the status line
+    fn report_preview_status(&mut self) {
+        self.status_message = Some(match self.mode {
+            AppMode::FindReplace if self.previews.is_empty() => "No matches".to_string(),
+            AppMode::FindReplace => {
+                let replacements: usize = self.previews.iter().map(|p| p.match_count).sum();
+                format!(
+                    "{} file(s) matched, {} replacement(s)",
+                    self.previews.len(),
+                    replacements
+                )
+            }
+            AppMode::Iteration => format!(
+                "{} of {} file(s) will change",
+                self.changed_count(),
+                self.previews.len()
+            ),
+            AppMode::Convention if self.previews.is_empty() => {
+                "All names already follow the convention".to_string()
+            }
+            AppMode::Convention => format!("{} file(s) to convert", self.previews.len()),
+            AppMode::ByExtension if self.previews.is_empty() => {
+                "No file has a mapped extension".to_string()
+            }
+            AppMode::ByExtension => format!(
+                "{} file(s) matched a template, {} will change",
+                self.previews.len(),
+                self.changed_count()
+            ),
+            AppMode::ChangeExtension if self.previews.is_empty() => {
+                "Every extension is already as given".to_string()
+            }
+            AppMode::ChangeExtension => format!("{} file(s) to change", self.previews.len()),
+            AppMode::Rules if self.previews.is_empty() => "No rule matches any file".to_string(),
+            AppMode::Rules => format!(
+                "{} file(s) matched a rule, {} will change",
+                self.previews.len(),
+                self.changed_count()
+            ),
+        });
+        let scoped = self.scoped_files().len();
+        if scoped < self.files.len() {
+            if let Some(status) = &mut self.status_message {
+                status.push_str(&format!(
+                    " (previewing {} of {} files)",
+                    scoped,
+                    self.files.len()
+                ));
+            }
+        }
+        self.status_level = StatusLevel::Info;
+        self.report_conflicts();
+        self.report_invalid_names();
+    }
+
+    // Adds a count of each kind of conflict to the status line
+    fn report_conflicts(&mut self) {
+        let count = |reason| {
+            self.previews
+                .iter()
+                .filter(|p| p.conflicts.contains(&reason))
+                .count()
+        };
+        let parts: Vec<String> = [
+            (
+                ConflictReason::DuplicateInBatch,
+                "duplicate name(s) in the batch",
+            ),
+            (
+                ConflictReason::ExistsOnDisk,
+                "name(s) taken by existing files",
+            ),
+            (
+                ConflictReason::CaseCollision,
+                "case collision(s) with existing files",
+            ),
+        ]
+        .into_iter()
+        .map(|(reason, label)| (count(reason), label))
+        .filter(|&(n, _)| n > 0)
+        .map(|(n, label)| format!("{} {}", n, label))
+        .collect();
+        if parts.is_empty() {
             return;
         }
+        if let Some(status) = &mut self.status_message {
+            status.push_str(&format!("; {}", parts.join(", ")));
+        }
+        self.status_level = StatusLevel::Warning;
+    }
+
+    // Previews whose name actually changes; the rest are left alone when renaming
+    fn changed_count(&self) -> usize {
+        self.previews
+            .iter()
+            .filter(|p| p.new_name != p.original_name.as_str())
+            .count()
+    }
+
+    // Moves the selected file past its nearest unpinned neighbour; pinned files stay put
+    fn move_selected(&mut self, down: bool) -> Task<Message> {
+        let Some(i) = self.selected_index.filter(|&i| i < self.files.len()) else {
+            return Task::none();
+        };
+        if self.files[i].pinned {
+            return self.report_pinned();
+        }
+        let neighbour = if down {
+            (i + 1..self.files.len()).find(|&j| !self.files[j].pinned)
+        } else {
+            (0..i).rev().find(|&j| !self.files[j].pinned)
+        };
+        let Some(j) = neighbour else {
+            return Task::none();
+        };
+        self.files.swap(i, j);
+        self.selected_index = Some(j);
+        Task::batch([self.generate_preview(), self.reveal_file(j)])
+    }
+
+    fn report_pinned(&mut self) -> Task<Message> {
+        self.status_message = Some("Pinned files stay in place; unpin to move".to_string());
+        self.status_level = StatusLevel::Warning;
+        Task::none()
+    }
+
+    // Points list entries at where the rename really left each file, so failed and skipped
+    // files keep their names. Listed files that were trashed or overwritten are dropped.
+    fn apply_rename_locations(&mut self, report: &RenameReport) {
+        let locations: HashMap<&Path, &PathBuf> = report
+            .locations
+            .iter()
+            .map(|(source, now)| (source.as_path(), now))
+            .collect();
+        let gone: HashSet<&Path> = report
+            .trashed
+            .iter()
+            .chain(&report.overwritten)
+            .map(PathBuf::as_path)
+            .collect();
+        let files = std::mem::take(&mut self.files);
+        self.files = files
+            .into_iter()
+            .filter_map(|entry| match locations.get(entry.path.as_path()) {
+                Some(&now) if *now != entry.path => Some(relocated_entry(&entry, now.clone())),
+                Some(_) => Some(entry),
+                None => (!gone.contains(entry.path.as_path())).then_some(entry),
+            })
+            .collect();
+    }
+
+    fn report_rename_running(&mut self) -> Task<Message> {
+        self.status_message = Some("Wait for the rename to finish before adding files".to_string());
+        self.status_level = StatusLevel::Warning;
+        Task::none()
+    }
+
+    // Shows an error in the status line and adds its details to the panel
+    fn show_error(&mut self, details: ErrorDetails) {
+        self.status_message = Some(format!("{}: {}", details.kind, details));
+        self.status_level = StatusLevel::Error;
+        self.append_error_details(details);
+    }
+
+    // Adds to the details already shown, so a retry or a later error doesn't hide earlier
+    // failures; the panel empties only when dismissed
+    fn append_error_details(&mut self, details: ErrorDetails) {
+        match &mut self.error_details {
+            Some(shown) => shown.append(details),
+            None => self.error_details = Some(details),
+        }
+    }
+
+    // Surfaces invalid resulting names in the status line
+    fn report_invalid_names(&mut self) {
+        let invalid: Vec<&str> = self
+            .previews
+            .iter()
+            .filter_map(|p| p.invalid_reason.as_deref())
+            .collect();
+        if let Some(first) = invalid.first() {
+            self.status_message = Some(format!("{} invalid name(s): {}", invalid.len(), first));
+            self.status_level = StatusLevel::Error;
+        }
+    }
+
+    // Renders main application view
+    pub fn view(&self) -> Element<'_, Message> {
+        let content = row![self.view_file_list(), self.view_preview()].spacing(SPACING_MD);
+        let base = container(
+            column![
+                self.view_header(),
+                vertical_space().height(SPACING_MD),
+                content,
+                vertical_space().height(SPACING_MD),
+                self.view_options(),
+                vertical_space().height(SPACING_MD),
+                self.view_protected_notice(),
+                self.view_network_notice(),
+                self.view_hook_notice(),
+                self.view_status(),
+                self.view_error_details(),
+            ]
+            .padding(SPACING_LG),
+        )
+        .width(Fill)
+        .height(Fill);
+        match &self.row_menu {
+            Some(menu) => stack![base, self.view_row_menu(menu)].into(),
+            None => base.into(),
+        }
+    }
+
+    // The open row menu over the whole window; a click or right click outside it closes it
+    fn view_row_menu<'a>(&'a self, menu: &'a RowMenu) -> Element<'a, Message> {
+        let excluded = self.excluded.contains(&menu.path);
+        let listed = self.files.iter().any(|f| f.path == menu.path);
+        let new_name = self
+            .previews
+            .iter()
+            .find(|p| p.original_path == menu.path)
+            .map(|p| p.new_name.as_str());
+        let actions = [
+            (RowAction::Reveal, true),
+            (RowAction::Open, true),
+            (RowAction::CopyOldName, true),
+            (RowAction::CopyNewName, new_name.is_some()),
+            (RowAction::CopyPath, true),
+            (RowAction::ToggleExclude, listed),
+            (RowAction::EditNewName, listed && !excluded),
+            (RowAction::Remove, listed),
+        ];
+        let mut items = Column::new();
+        let mut item_count = 0;
+        for (action, available) in actions {
+            if available {
+                items = items.push(
+                    button(text(action.label(excluded)).size(FONT_SM))
+                        .on_press(Message::RowMenuAction(action))
+                        .style(button::text)
+                        .width(Fill),
+                );
+                item_count += 1;
+            }
+        }
+        if let Some(name) = &menu.editing {
+            items = items.push(
+                text_input("New name", name)
+                    .id(edit_input_id())
+                    .on_input(Message::RowMenuEditChanged)
+                    .on_submit(Message::RowMenuEditSubmitted)
+                    .size(FONT_SM),
+            );
+            item_count += 1;
+        }
+        // Kept inside the window so a click near an edge doesn't push items off screen
+        let height = item_count as f32 * ROW_MENU_ITEM_HEIGHT + 2.0 * SPACING_XS as f32;
+        let left = menu
+            .position
+            .x
+            .min(self.window_width - ROW_MENU_WIDTH)
+            .max(0.0);
+        let top = menu.position.y.min(self.window_height - height).max(0.0);
+        let panel = container(items)
+            .padding(SPACING_XS)
+            .width(ROW_MENU_WIDTH)
+            .style(container::bordered_box);
+        opaque(
+            mouse_area(
+                container(panel)
+                    .padding(Padding::ZERO.top(top).left(left))
+                    .width(Fill)
+                    .height(Fill),
+            )
+            .on_press(Message::RowMenuClosed)
+            .on_right_press(Message::RowMenuClosed),
+        )
+    }
+
+    fn view_header(&self) -> Element<'_, Message> {
+        let theme_label = if self.dark_mode {
+            "Light Mode"
+        } else {
+            "Dark Mode"
+        };
+        let settings_label = if self.show_settings {
+            "Close Settings"
+        } else {
+            "Settings"
+        };
+        let recent: Vec<String> = self
+            .recent_folders
+            .iter()
+            .map(|folder| folder.display().to_string())
+            .collect();
+        row![
+            text("File Rename Plus").size(FONT_XL),
+            horizontal_space(),
+            self.focusable(
+                FocusTarget::RecentFolderPicker,
+                pick_list(recent, None::<String>, Message::RecentFolderSelected)
+                    .placeholder("Recent folders")
+                    .width(150)
+            ),
+            self.focusable(
+                FocusTarget::RemoveCopyMarkers,
+                button("Remove Copy Markers").on_press(Message::RemoveCopyMarkers)
+            ),
+            self.focusable(
+                FocusTarget::FolderPrefix,
+                button("Add Folder Prefix").on_press(Message::FolderPrefix)
+            ),
+            self.focusable(
+                FocusTarget::WebSafeNames,
+                button("Web-Safe Names").on_press(Message::WebSafeNames)
+            ),
+            self.focusable(
+                FocusTarget::ThemeToggle,
+                button(theme_label).on_press(Message::ToggleTheme)
+            ),
+            self.focusable(
+                FocusTarget::SettingsToggle,
+                button(settings_label).on_press(Message::ToggleSettings)
+            ),
+            text("  Mode: ").size(FONT_LG),
+            self.focusable(
+                FocusTarget::ModePicker,
+                pick_list(AppMode::ALL, Some(self.mode), Message::ModeChanged).width(200)
+            ),
+        ]
+        .align_y(Center)
+        .into()
+    }
+
+    fn view_file_list(&self) -> Element<'_, Message> {
+        let header = row![
+            text("Files").size(FONT_LG),
+            horizontal_space(),
+            self.focusable(
+                FocusTarget::AddFolder,
+                button("Add Folder (Ctrl+O)").on_press(Message::AddFolder)
+            ),
+            self.focusable(
+                FocusTarget::ImportPathList,
+                button("Import List").on_press(Message::ImportPathList)
+            ),
+            self.focusable(
+                FocusTarget::SortByName,
+                button(sort_button_label(self.sort_order)).on_press(Message::SortByName)
+            ),
+            self.focusable(
+                FocusTarget::AnalyzeNames,
+                button("Analyze").on_press(Message::AnalyzeNames)
+            ),
+            self.focusable(
+                FocusTarget::ClearFiles,
+                button("Clear").on_press(Message::ClearFiles)
+            )
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center);
+
+        let budget = pane_char_budget(self.window_width);
+        let interaction = if self.dragging_index.is_some() {
+            mouse::Interaction::Grabbing
+        } else {
+            mouse::Interaction::Pointer
+        };
+        let link_peers = hard_link_peers(&self.files);
+        let extensions = parse_extension_list(&self.extension_filter);
+        let file_buttons: Vec<Element<'_, Message>> = self
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| {
+                self.access_filter
+                    .shows(self.blocked_files.contains_key(&f.path))
+            })
+            .map(|(i, f)| {
+                let is_drop_target = self.dragging_index.is_some() && self.drop_target == Some(i);
+                let mut badges: Vec<Element<'_, Message>> = Vec::new();
+                if f.pinned {
+                    badges.push(badge(
+                        "[PIN]",
+                        "Pinned: keeps its place when sorting or moving, and survives Clear"
+                            .to_string(),
+                        COLOR_INFO,
+                    ));
+                }
+                let filtered_out = !matches_extensions(&f.path, &extensions);
+                if filtered_out {
+                    badges.push(badge(
+                        "[SKIP]",
+                        "Extension not in the filter: left out of the preview and rename"
+                            .to_string(),
+                        COLOR_MUTED_DARK,
+                    ));
+                } else if self.excluded.contains(&f.path) {
+                    badges.push(badge(
+                        "[SKIP]",
+                        "Excluded from its right-click menu: left out of the preview and rename"
+                            .to_string(),
+                        COLOR_MUTED_DARK,
+                    ));
+                }
+                if f.path
+                    .parent()
+                    .is_some_and(|folder| self.protected_folders.contains(folder))
+                {
+                    badges.push(badge(
+                        "[SYS]",
+                        "In a protected system folder: renaming asks for confirmation".to_string(),
+                        COLOR_ERROR,
+                    ));
+                }
+                if let Some(reason) = self.blocked_files.get(&f.path) {
+                    badges.push(badge(
+                        "[LOCK]",
+                        format!("Can't be renamed: {}", reason),
+                        COLOR_CONFLICT,
+                    ));
+                }
+                if let Some(&stage) = self.rename_progress.get(&f.path) {
+                    let (label, detail, color) = stage_badge(stage);
+                    badges.push(badge(label, detail.to_string(), color));
+                }
+                let name_color =
+                    (filtered_out || self.excluded.contains(&f.path)).then_some(COLOR_MUTED_DARK);
+                if let Some(peers) = link_peers.get(&i) {
+                    let names: Vec<&str> =
+                        peers.iter().map(|&j| self.files[j].name.as_str()).collect();
+                    badges.push(badge(
+                        "[LINK]",
+                        format!("Same file (hard link) as: {}", names.join(", ")),
+                        COLOR_INFO,
+                    ));
+                }
+                let label: Element<'_, Message> = if badges.is_empty() {
+                    fitted_text(f.name.as_str(), budget, name_color)
+                } else {
+                    let name_budget = budget.saturating_sub(7 * badges.len());
+                    Row::with_children(
+                        [
+                            fitted_text(f.name.as_str(), name_budget, name_color),
+                            horizontal_space().into(),
+                        ]
+                        .into_iter()
+                        .chain(badges),
+                    )
+                    .spacing(SPACING_SM)
+                    .into()
+                };
+                let row_content = container(label)
+                    .padding([SPACING_SM, SPACING_MD])
+                    .width(Fill)
+                    .style(list_row_style(self.is_selected(i), is_drop_target));
+                let path = f.path.clone();
+                let offset = self.file_list_offset;
+                right_click_area(
+                    mouse_area(row_content)
+                        .on_press(Message::DragStarted(i))
+                        .on_enter(Message::DragHovered(i))
+                        .interaction(interaction),
+                    move |position| {
+                        Message::RowMenuOpened(
+                            path.clone(),
+                            Point::new(position.x, position.y - offset),
+                        )
+                    },
+                )
+            })
+            .collect();
+
+        let file_list = if file_buttons.is_empty() && !self.files.is_empty() {
+            column![text("No files match the access filter.").size(FONT_SM)]
+        } else if file_buttons.is_empty() {
+            column![text("No files. Click 'Add Folder'.").size(FONT_SM)]
+        } else {
+            Column::with_children(file_buttons).spacing(SPACING_XS)
+        };
+
+        let selected_pinned = self
+            .selected_index
+            .and_then(|i| self.files.get(i))
+            .is_some_and(|f| f.pinned);
+        let pin_label = if selected_pinned { "Unpin" } else { "Pin" };
+        let controls = row![
+            self.focusable(FocusTarget::MoveUp, button("Up").on_press(Message::MoveUp)),
+            self.focusable(
+                FocusTarget::MoveDown,
+                button("Down").on_press(Message::MoveDown)
+            ),
+            self.focusable(
+                FocusTarget::PinFile,
+                button(pin_label).on_press(Message::TogglePin)
+            ),
+            self.focusable(
+                FocusTarget::RemoveFile,
+                button("Remove (Del)").on_press(Message::RemoveFile)
+            )
+        ]
+        .spacing(SPACING_SM);
+
+        let groups = parse_extension_groups(&self.extension_groups);
+        let active = self.active_extension_group(&groups);
+        let group_buttons = Row::with_children(groups.into_iter().enumerate().map(|(i, group)| {
+            button(text(group.name).size(FONT_SM))
+                .on_press(Message::ExtensionGroupSelected(Some(i)))
+                .style(if active == Some(i) {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .into()
+        }))
+        .push(
+            button(text("All").size(FONT_SM))
+                .on_press(Message::ExtensionGroupSelected(None))
+                .style(if self.extension_filter.trim().is_empty() {
+                    button::primary
+                } else {
+                    button::secondary
+                }),
+        )
+        .spacing(SPACING_XS)
+        .wrap();
+        let filter = row![
+            self.focusable(
+                FocusTarget::ExtensionFilterInput,
+                text_input("Extensions, e.g. jpg, png", &self.extension_filter)
+                    .id(FocusTarget::ExtensionFilterInput.widget_id())
+                    .on_input(Message::ExtensionFilterChanged)
+                    .size(FONT_SM)
+                    .width(170)
+            ),
+            self.focusable(FocusTarget::ExtensionGroups, group_buttons),
+            horizontal_space(),
+            self.view_access_filter(),
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center);
+
+        column![
+            header,
+            horizontal_rule(1),
+            self.focusable(
+                FocusTarget::FileList,
+                scrollable(file_list)
+                    .id(file_list_id())
+                    .on_scroll(|viewport| Message::FileListScrolled(viewport.absolute_offset().y))
+                    .height(LIST_HEIGHT)
+            ),
+            horizontal_rule(1),
+            controls,
+            filter
+        ]
+        .spacing(SPACING_MD)
+        .width(Fill)
+        .into()
+    }
+
+    // Count of files that can't be renamed, with the picker narrowing the list to them
+    fn view_access_filter(&self) -> Element<'_, Message> {
+        let locked = self
+            .files
+            .iter()
+            .filter(|f| self.blocked_files.contains_key(&f.path))
+            .count();
+        row![
+            text(format!("{} locked", locked))
+                .size(FONT_SM)
+                .color(if locked > 0 {
+                    COLOR_CONFLICT
+                } else {
+                    COLOR_MUTED_DARK
+                }),
+            self.focusable(
+                FocusTarget::AccessFilterPicker,
+                pick_list(
+                    AccessFilter::ALL,
+                    Some(self.access_filter),
+                    Message::AccessFilterChanged
+                )
+                .text_size(FONT_SM)
+            )
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center)
+        .into()
+    }
+
+    fn view_preview(&self) -> Element<'_, Message> {
+        let tree = if self.preview_tree {
+            preview_tree(&self.previews)
+        } else {
+            Vec::new()
+        };
+        let folders = tree
+            .iter()
+            .filter(|row| matches!(row, TreeRow::Folder { .. }))
+            .count();
+        // Spaces padding the stems are display only; the names themselves are untouched
+        let aligned = if self.align_preview_names {
+            let names: Vec<&str> = self.previews.iter().map(|p| p.new_name.as_str()).collect();
+            align_stems(&names, self.compound_extensions)
+        } else {
+            Vec::new()
+        };
+        let items: Vec<Element<'_, Message>> = if self.previews.is_empty() {
+            vec![text("Preview appears here after configuring options.")
+                .size(FONT_SM)
+                .into()]
+        } else if !self.preview_listing && !self.previews.iter().any(|p| self.preview_shown(p)) {
+            vec![text("No rows match the selected filters.")
+                .size(FONT_SM)
+                .into()]
+        } else if self.preview_listing {
+            self.view_resulting_listing()
+        } else if folders <= 1 {
+            // Flat list, or a tree with a single folder to show
+            self.previews
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| self.preview_shown(p))
+                .map(|(i, p)| self.view_preview_row(p, aligned.get(i), 0))
+                .collect()
+        } else {
+            // Folders nest under the one they all share; each can be folded away
+            let budget = self.preview_name_budget();
+            let mut items = Vec::with_capacity(tree.len());
+            // Depth of the collapsed folder whose contents are being skipped
+            let mut hidden_below: Option<usize> = None;
+            for row in tree {
+                let depth = row.depth();
+                if hidden_below.is_some_and(|d| depth > d) {
+                    continue;
+                }
+                hidden_below = None;
+                let indent = depth * TREE_INDENT_CHARS;
+                match row {
+                    TreeRow::File { index, .. } if !self.preview_shown(&self.previews[index]) => {}
+                    TreeRow::File { index, .. } => items.push(self.view_preview_row(
+                        &self.previews[index],
+                        aligned.get(index),
+                        indent,
+                    )),
+                    TreeRow::Folder {
+                        path,
+                        label,
+                        files,
+                        conflicts,
+                        ..
+                    } => {
+                        let collapsed = self.collapsed_folders.contains(&path);
+                        if collapsed {
+                            hidden_below = Some(depth);
+                        }
+                        let marker = if collapsed { "▸ " } else { "▾ " };
+                        let summary = if conflicts > 0 {
+                            format!("{} file(s), {} conflict(s)", files, conflicts)
+                        } else {
+                            format!("{} file(s)", files)
+                        };
+                        let label_budget =
+                            budget.saturating_sub(summary.chars().count() + 3 + indent);
+                        let summary_color = if conflicts > 0 {
+                            COLOR_CONFLICT
+                        } else {
+                            COLOR_MUTED_DARK
+                        };
+                        items.push(
+                            row![
+                                Space::with_width(indent_width(indent)),
+                                button(
+                                    row![
+                                        text(marker).size(FONT_SM),
+                                        text(elide_middle(&label, label_budget).unwrap_or(label))
+                                            .size(FONT_SM),
+                                        horizontal_space(),
+                                        text(summary).size(FONT_SM).color(summary_color)
+                                    ]
+                                    .align_y(Center),
+                                )
+                                .on_press(Message::PreviewFolderToggled(path))
+                                .style(button::secondary)
+                                .width(Fill)
+                            ]
+                            .into(),
+                        );
+                    }
+                }
+            }
+            items
+        };
+
+        let header = row![
+            text("Preview").size(FONT_LG),
+            horizontal_space(),
+            self.focusable(
+                FocusTarget::PreviewTreeToggle,
+                button(if self.preview_tree {
+                    "Flat View"
+                } else {
+                    "Tree View"
+                })
+                .on_press(Message::PreviewTreeToggled)
+            ),
+            self.focusable(
+                FocusTarget::ResultingListingToggle,
+                button(if self.preview_listing {
+                    "Rename Pairs"
+                } else {
+                    "Resulting Listing"
+                })
+                .on_press(Message::ResultingListingToggled)
+            ),
+            self.focusable(
+                FocusTarget::AlignPreviewToggle,
+                button(if self.align_preview_names {
+                    "Plain Names"
+                } else {
+                    "Align Names"
+                })
+                .on_press(Message::AlignPreviewToggled)
+            ),
+            self.focusable(
+                FocusTarget::PasteNames,
+                button("Paste Names").on_press(Message::PasteNames)
+            ),
+            self.focusable(
+                FocusTarget::VerifyClipboard,
+                button("Verify Against Clipboard").on_press_maybe(
+                    (!self.previews.is_empty()).then_some(Message::VerifyClipboard)
+                )
+            ),
+            self.focusable(
+                FocusTarget::ScriptFormatPicker,
+                pick_list(
+                    ScriptFormat::ALL,
+                    Some(self.script_format),
+                    Message::ScriptFormatChanged
+                )
+            ),
+            self.focusable(
+                FocusTarget::ExportScript,
+                button("Export Script").on_press(Message::ExportScript)
+            )
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center);
+
+        column![
+            header,
+            horizontal_rule(1),
+            self.view_preview_filters(),
+            self.focusable(
+                FocusTarget::PreviewList,
+                scrollable(Column::with_children(items).spacing(8))
+                    .id(preview_list_id())
+                    .on_scroll(|viewport| {
+                        Message::PreviewListScrolled(viewport.absolute_offset().y)
+                    })
+                    .height(LIST_HEIGHT)
+            )
+        ]
+        .spacing(SPACING_MD)
+        .width(Fill)
+        .into()
+    }
+
+    fn preview_shown(&self, preview: &RenamePreview) -> bool {
+        self.preview_filters.is_empty() || self.preview_filters.iter().any(|f| f.matches(preview))
+    }
+
+    // Chips narrowing the preview to rows in some state, each with its count. What Execute
+    // does is spelled out while rows are hidden, so a filter can't hide part of the rename.
+    fn view_preview_filters(&self) -> Element<'_, Message> {
+        let chip = |label: String, active: bool, message: Message| {
+            button(text(label).size(FONT_SM))
+                .on_press(message)
+                .style(if active {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .into()
+        };
+        let chips = std::iter::once(chip(
+            format!("All ({})", self.previews.len()),
+            self.preview_filters.is_empty(),
+            Message::PreviewFiltersCleared,
+        ))
+        .chain(PreviewFilter::ALL.into_iter().map(|filter| {
+            let count = self.previews.iter().filter(|p| filter.matches(p)).count();
+            chip(
+                format!("{} ({})", filter, count),
+                self.preview_filters.contains(&filter),
+                Message::PreviewFilterToggled(filter),
+            )
+        }));
+        let chips = self.focusable(
+            FocusTarget::PreviewFilters,
+            Row::with_children(chips).spacing(SPACING_XS).wrap(),
+        );
+        let shown = self
+            .previews
+            .iter()
+            .filter(|p| self.preview_shown(p))
+            .count();
+        if self.preview_filters.is_empty() || shown == self.previews.len() {
+            return chips;
+        }
+        column![
+            chips,
+            text(format!(
+                "Showing {} of {} rows; Execute still renames all {} changed file(s)",
+                shown,
+                self.previews.len(),
+                self.changed_count()
+            ))
+            .size(FONT_SM)
+            .color(COLOR_INFO)
+        ]
+        .spacing(SPACING_XS)
+        .into()
+    }
 
-        match self.mode {
-            AppMode::FindReplace => {
-                if self.find_pattern.is_empty() {
-                    self.status_message = Some("Enter a pattern to find".to_string());
-                    return;
-                }
-                match apply_find_replace(
-                    &self.files,
-                    &self.find_pattern,
-                    &self.replace_with,
-                    self.regex_mode,
-                    self.case_sensitive,
-                ) {
-                    Ok(p) => {
-                        self.previews = p;
-                        self.status_message = Some(if self.previews.is_empty() {
-                            "No matches".to_string()
-                        } else {
-                            format!("{} file(s) matched", self.previews.len())
-                        });
-                        self.is_error = false;
-                    }
-                    Err(e) => {
-                        self.status_message = Some(format!("Error: {}", e));
-                        self.is_error = true;
+    // Every previewed folder as it will be listed after the rename: the files on disk, the
+    // renamed ones under their new names and highlighted, in natural order
+    fn view_resulting_listing(&self) -> Vec<Element<'_, Message>> {
+        let budget = self.preview_name_budget();
+        let fit = |name: &str, max_chars: usize| {
+            text(elide_middle(name, max_chars).unwrap_or_else(|| name.to_string())).size(FONT_SM)
+        };
+        let mut items = Vec::new();
+        for listing in resulting_listing(&self.previews, &self.folder_contents) {
+            let renamed = listing
+                .entries
+                .iter()
+                .filter(|e| e.renamed_from.is_some())
+                .count();
+            let summary = format!("{} entries, {} renamed", listing.entries.len(), renamed);
+            let label = listing.folder.display().to_string();
+            items.push(
+                row![
+                    fit(&label, budget.saturating_sub(summary.chars().count() + 1)),
+                    horizontal_space(),
+                    text(summary).size(FONT_SM).color(COLOR_MUTED_DARK)
+                ]
+                .into(),
+            );
+            for entry in listing.entries {
+                let indent = TREE_INDENT_CHARS;
+                let name_budget = budget.saturating_sub(indent);
+                let line: Element<'_, Message> = match &entry.renamed_from {
+                    Some(old) => {
+                        let was = format!("  (was {})", old);
+                        row![
+                            fit(&entry.name, name_budget * 2 / 3).color(COLOR_SUCCESS),
+                            fit(&was, name_budget - name_budget * 2 / 3).color(COLOR_MUTED_DARK)
+                        ]
+                        .into()
                     }
-                }
+                    None => fit(&entry.name, name_budget).into(),
+                };
+                items.push(row![Space::with_width(indent_width(indent)), line].into());
             }
-            AppMode::Iteration => {
-                match apply_iteration_numbering(
-                    &self.files,
-                    &self.template,
-                    self.start_number.parse().unwrap_or(1),
-                    self.padding.parse().unwrap_or(3),
-                ) {
-                    Ok(p) => {
-                        self.previews = p;
-                        self.status_message =
-                            Some(format!("{} file(s) ready", self.previews.len()));
-                        self.is_error = false;
-                    }
-                    Err(e) => {
-                        self.status_message = Some(format!("Error: {}", e));
-                        self.is_error = true;
-                    }
+        }
+        items
+    }
+
+    // `indent` is in characters, taken from the name budget. `aligned` is the new name with
+    // its stem padded to line up with the others, shown instead when it fits.
+    fn view_preview_row<'a>(
+        &self,
+        p: &'a RenamePreview,
+        aligned: Option<&String>,
+        indent: usize,
+    ) -> Element<'a, Message> {
+        let budget = self.preview_name_budget().saturating_sub(indent);
+        let arrow = format!("  {} ", self.preview_arrow.glyph());
+        let arrow_len = arrow.chars().count();
+        let (conflict, label_len): (Element<'_, Message>, usize) =
+            if let Some(reason) = &p.invalid_reason {
+                (badge(" [INVALID]", reason.clone(), COLOR_CONFLICT), 10)
+            } else if !p.conflicts.is_empty() {
+                let reasons: Vec<String> = p.conflicts.iter().map(|r| r.to_string()).collect();
+                (badge(" [CONFLICT]", reasons.join("\n"), COLOR_CONFLICT), 11)
+            } else {
+                (text("").into(), 0)
+            };
+        let (stage, stage_len): (Element<'_, Message>, usize) =
+            match self.rename_progress.get(&p.original_path) {
+                Some(&stage) => {
+                    let (label, detail, color) = stage_badge(stage);
+                    (
+                        badge(label, detail.to_string(), color),
+                        label.chars().count() + 1,
+                    )
                 }
-            }
+                None => (text("").into(), 0),
+            };
+        let (unlisted, unlisted_len): (Element<'_, Message>, usize) = match &self.name_check {
+            Some(check) if check.unexpected.iter().any(|(_, to)| *to == p.new_name) => (
+                badge(
+                    " [NOT LISTED]",
+                    "The clipboard list doesn't have this name".to_string(),
+                    COLOR_CONFLICT,
+                ),
+                13,
+            ),
+            _ => (text("").into(), 0),
+        };
+        let (warning, warning_len): (Element<'_, Message>, usize) = if p.warnings.is_empty() {
+            (text("").into(), 0)
+        } else {
+            let reasons: Vec<String> = p.warnings.iter().map(|w| w.to_string()).collect();
+            let color = if self.dark_mode {
+                COLOR_WARNING
+            } else {
+                COLOR_WARNING_LIGHT
+            };
+            (badge(" [WARN]", reasons.join("\n"), color), 7)
+        };
+        let (edited, edited_len): (Element<'_, Message>, usize) = if p.edited {
+            (text(" [EDITED]").size(FONT_SM).color(COLOR_INFO).into(), 9)
+        } else if p.new_name == *p.original_name {
+            // Left out of the rename
+            (
+                text(" [UNCHANGED]")
+                    .size(FONT_SM)
+                    .color(COLOR_MUTED_DARK)
+                    .into(),
+                12,
+            )
+        } else {
+            (text("").into(), 0)
+        };
+        let new_budget = budget.saturating_sub(
+            arrow_len + label_len + unlisted_len + warning_len + edited_len + stage_len,
+        );
+        let new_name: Element<'_, Message> = match aligned {
+            Some(aligned) if aligned.chars().count() <= new_budget => text(aligned.clone())
+                .size(FONT_SM)
+                .font(Font::MONOSPACE)
+                .color(COLOR_SUCCESS)
+                .into(),
+            _ => fitted_text(&p.new_name, new_budget, Some(COLOR_SUCCESS)),
+        };
+        let original: Element<'_, Message> = if p.match_count > 0 {
+            let count = format!("×{}", p.match_count);
+            let count_len = count.chars().count() + 1;
+            row![
+                fitted_text(
+                    p.original_name.as_str(),
+                    budget.saturating_sub(count_len),
+                    None
+                ),
+                horizontal_space(),
+                text(count).size(FONT_SM).color(COLOR_INFO)
+            ]
+            .into()
+        } else {
+            fitted_text(p.original_name.as_str(), budget, None)
+        };
+        let offset = self
+            .mode_states
+            .get(&self.mode)
+            .map_or(0.0, |state| state.preview_offset);
+        let row = row![
+            Space::with_width(indent_width(indent)),
+            column![
+                original,
+                row![
+                    text(arrow).size(FONT_SM).color(COLOR_INFO),
+                    new_name,
+                    conflict,
+                    unlisted,
+                    warning,
+                    edited,
+                    horizontal_space(),
+                    stage
+                ]
+            ]
+            .spacing(SPACING_XS)
+        ];
+        // The scrollable reports positions in its content, which is shifted by the offset
+        right_click_area(row, move |position| {
+            Message::RowMenuOpened(
+                p.original_path.clone(),
+                Point::new(position.x, position.y - offset),
+            )
+        })
+    }
+
+    fn view_options(&self) -> Element<'_, Message> {
+        match self.options_panel() {
+            OptionsPanel::Mode(AppMode::FindReplace) => self.view_find_replace_options(),
+            OptionsPanel::Mode(AppMode::Iteration) => self.view_iteration_options(),
+            OptionsPanel::Mode(AppMode::Convention) => self.view_convention_options(),
+            OptionsPanel::Mode(AppMode::ByExtension) => self.view_by_extension_options(),
+            OptionsPanel::Mode(AppMode::ChangeExtension) => self.view_change_extension_options(),
+            OptionsPanel::Mode(AppMode::Rules) => self.view_rules_options(),
+            OptionsPanel::FindAnyOf => self.view_find_any_of_options(),
+            OptionsPanel::Settings => self.view_settings(),
+            OptionsPanel::LargeBatch => self.view_large_batch_warning(),
+            OptionsPanel::IdleNewFiles => self.view_idle_new_files(),
+            OptionsPanel::ClearFiles => self.view_clear_warning(),
+            OptionsPanel::ProtectedWarning => self.view_protected_warning(),
+            OptionsPanel::Conflicts => self.view_conflicts(),
+            OptionsPanel::RenameFailures => self.view_rename_failures(),
+            OptionsPanel::Analysis(_) => self.view_analysis(),
+            OptionsPanel::NameCheck => self.view_name_check(),
         }
     }
 
-    // Renders main application view
-    pub fn view(&self) -> Element<'_, Message> {
-        let content = row![self.view_file_list(), self.view_preview()].spacing(SPACING_MD);
-        container(
+    fn view_protected_notice(&self) -> Element<'_, Message> {
+        match &self.protected_notice {
+            // As administrator or root the system won't refuse these renames, so say it louder
+            Some(folder) if self.running_as_admin => text(format!(
+                "Warning: running as administrator, and some files are in the protected \
+                 system folder {}",
+                folder.display()
+            ))
+            .size(FONT_LG)
+            .color(COLOR_ERROR)
+            .into(),
+            Some(folder) => text(format!(
+                "Warning: some files are in the protected system folder {}",
+                folder.display()
+            ))
+            .size(FONT_SM)
+            .color(COLOR_ERROR)
+            .into(),
+            None => Space::new(0, 0).into(),
+        }
+    }
+
+    // Always on screen while set, since it runs a program outside the app after every rename
+    fn view_hook_notice(&self) -> Element<'_, Message> {
+        let command = self.post_rename_hook.trim();
+        if command.is_empty() {
+            return Space::new(0, 0).into();
+        }
+        text(format!(
+            "After each rename, the external command \"{}\" runs with a JSON file of the results",
+            command
+        ))
+        .size(FONT_SM)
+        .color(if self.dark_mode {
+            COLOR_WARNING
+        } else {
+            COLOR_WARNING_LIGHT
+        })
+        .into()
+    }
+
+    fn view_network_notice(&self) -> Element<'_, Message> {
+        match &self.network_notice {
+            Some(folder) => text(format!(
+                "Some files are on the network share {}: renames there aren't atomic, so each \
+                 one is checked afterwards",
+                folder.display()
+            ))
+            .size(FONT_SM)
+            .color(if self.dark_mode {
+                COLOR_WARNING
+            } else {
+                COLOR_WARNING_LIGHT
+            })
+            .into(),
+            None => Space::new(0, 0).into(),
+        }
+    }
+
+    fn view_large_batch_warning(&self) -> Element<'_, Message> {
+        let count = self.large_batch_warning.unwrap_or_default();
+        row![
             column![
-                self.view_header(),
-                vertical_space().height(SPACING_MD),
-                content,
-                vertical_space().height(SPACING_MD),
-                self.view_options(),
-                vertical_space().height(SPACING_MD),
-                self.view_status(),
+                text(format!("Rename {} files?", count)).size(FONT_LG),
+                text(format!(
+                    "This is more than the {} set in Settings; check the preview first.",
+                    self.confirm_threshold
+                ))
+                .size(FONT_SM),
             ]
-            .padding(SPACING_LG),
-        )
-        .width(Fill)
-        .height(Fill)
+            .spacing(SPACING_SM),
+            horizontal_space(),
+            self.focusable(
+                FocusTarget::CancelLargeBatch,
+                button("Cancel").on_press(Message::CancelLargeBatch)
+            ),
+            self.focusable(
+                FocusTarget::ConfirmLargeBatch,
+                button(text(format!("Rename {} Files", count)).size(FONT_LG))
+                    .on_press(Message::ConfirmLargeBatch)
+            ),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
         .into()
     }
 
-    fn view_header(&self) -> Element<'_, Message> {
-        let theme_label = if self.dark_mode {
-            "Light Mode"
-        } else {
-            "Dark Mode"
-        };
+    fn view_idle_new_files(&self) -> Element<'_, Message> {
+        let count = self.idle_new_files.unwrap_or_default();
         row![
-            text("File Rename Plus").size(FONT_XL),
+            column![
+                text(format!("{} new file(s) joined the list", count)).size(FONT_LG),
+                text("They appeared while the idle run waited; check the preview first.")
+                    .size(FONT_SM),
+            ]
+            .spacing(SPACING_SM),
             horizontal_space(),
-            button(theme_label).on_press(Message::ToggleTheme),
-            text("  Mode: ").size(FONT_LG),
-            pick_list(
-                vec![AppMode::FindReplace, AppMode::Iteration],
-                Some(self.mode),
-                Message::ModeChanged
-            )
-            .width(200),
+            self.focusable(
+                FocusTarget::CancelIdleNewFiles,
+                button("Cancel").on_press(Message::CancelIdleNewFiles)
+            ),
+            self.focusable(
+                FocusTarget::ConfirmIdleNewFiles,
+                button(text("Rename Including Them").size(FONT_LG))
+                    .on_press(Message::ConfirmIdleNewFiles)
+            ),
         ]
+        .spacing(SPACING_MD)
         .align_y(Center)
         .into()
     }
 
-    fn view_file_list(&self) -> Element<'_, Message> {
-        let header = row![
-            text("Files").size(FONT_LG),
+    fn view_clear_warning(&self) -> Element<'_, Message> {
+        let count = self.clear_warning.unwrap_or_default();
+        row![
+            column![
+                text(format!("Clear {} files?", count)).size(FONT_LG),
+                text("They can be brought back with Restore until new files are added.")
+                    .size(FONT_SM),
+            ]
+            .spacing(SPACING_SM),
             horizontal_space(),
-            button("Add Folder (Ctrl+O)").on_press(Message::AddFolder),
-            button("Clear").on_press(Message::ClearFiles)
+            self.focusable(
+                FocusTarget::CancelClearFiles,
+                button("Cancel").on_press(Message::CancelClearFiles)
+            ),
+            self.focusable(
+                FocusTarget::ConfirmClearFiles,
+                button(text(format!("Clear {} Files", count)).size(FONT_LG))
+                    .on_press(Message::ConfirmClearFiles)
+                    .style(button::danger)
+            ),
         ]
-        .spacing(SPACING_SM)
-        .align_y(Center);
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
 
-        let file_buttons: Vec<Element<'_, Message>> = self
-            .files
-            .iter()
-            .enumerate()
-            .map(|(i, f)| {
-                let btn = button(text(f.name.as_str()).size(FONT_SM))
-                    .width(Fill)
-                    .on_press(Message::FileSelected(i));
-                if self.selected_index == Some(i) {
-                    btn.style(button::primary).into()
-                } else {
-                    btn.style(button::secondary).into()
-                }
-            })
-            .collect();
+    fn view_protected_warning(&self) -> Element<'_, Message> {
+        let folder = self
+            .protected_warning
+            .as_deref()
+            .map(|f| f.display().to_string())
+            .unwrap_or_default();
+        row![
+            column![
+                text(format!("{} is a protected system folder", folder))
+                    .size(FONT_LG)
+                    .color(COLOR_ERROR),
+                text("Renaming files here can break the operating system or installed programs.")
+                    .size(FONT_SM),
+            ]
+            .push_maybe(self.running_as_admin.then(|| {
+                text("Running as administrator: the system won't refuse any of these renames.")
+                    .size(FONT_SM)
+                    .color(COLOR_ERROR)
+            }))
+            .spacing(SPACING_SM),
+            horizontal_space(),
+            self.focusable(
+                FocusTarget::CancelProtected,
+                button("Cancel").on_press(Message::CancelProtectedRename)
+            ),
+            self.focusable(
+                FocusTarget::AcknowledgeProtected,
+                checkbox("I understand", self.protected_acknowledged)
+                    .on_toggle(Message::ProtectedAcknowledged)
+                    .text_size(FONT_SM)
+            ),
+            self.focusable(
+                FocusTarget::ConfirmProtected,
+                button(text("Rename Anyway").size(FONT_LG))
+                    .on_press_maybe(
+                        self.protected_acknowledged
+                            .then_some(Message::ConfirmProtectedRename)
+                    )
+                    .style(button::danger)
+            ),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
 
-        let file_list = if file_buttons.is_empty() {
-            column![text("No files. Click 'Add Folder'.").size(FONT_SM)]
+    fn view_conflicts(&self) -> Element<'_, Message> {
+        let choices = self.conflict_choices.as_deref().unwrap_or_default();
+        let count = |policy| choices.iter().filter(|(_, p)| *p == policy).count();
+        let destructive = count(ConflictPolicy::Trash) + count(ConflictPolicy::Overwrite) > 0;
+        let list = Column::with_children(choices.iter().enumerate().map(|(i, (path, policy))| {
+            row![
+                pick_list(ConflictPolicy::ALL, Some(*policy), move |p| {
+                    Message::ConflictChoiceChanged(i, p)
+                })
+                .text_size(FONT_SM),
+                text(path.display().to_string()).size(FONT_SM),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(SPACING_XS);
+        row![
+            column![
+                text(format!(
+                    "{} new name(s) are taken by existing files",
+                    choices.len()
+                ))
+                .size(FONT_LG)
+                .color(COLOR_ERROR),
+                text(format!(
+                    "Overwrite {}, move to recycle bin {}, skip {}",
+                    count(ConflictPolicy::Overwrite),
+                    count(ConflictPolicy::Trash),
+                    count(ConflictPolicy::Skip)
+                ))
+                .size(FONT_SM),
+                scrollable(list).height(90),
+            ]
+            .spacing(SPACING_SM)
+            .width(Fill),
+            self.focusable(
+                FocusTarget::CancelConflicts,
+                button("Cancel").on_press(Message::CancelConflicts)
+            ),
+            self.focusable(
+                FocusTarget::ConfirmConflicts,
+                button(text("Confirm and Rename").size(FONT_LG))
+                    .on_press(Message::ConfirmConflicts)
+                    .style(if destructive {
+                        button::danger
+                    } else {
+                        button::success
+                    })
+            ),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
+
+    fn view_rename_failures(&self) -> Element<'_, Message> {
+        let budget = line_char_budget(self.window_width) * 2 / 3;
+        let log = Column::with_children(
+            self.failure_log
+                .iter()
+                .map(|line| fitted_text(line, budget, Some(COLOR_ERROR))),
+        )
+        .spacing(SPACING_XS);
+        row![
+            column![
+                text(format!(
+                    "{} file(s) could not be renamed",
+                    self.failed_renames.len()
+                ))
+                .size(FONT_LG)
+                .color(COLOR_ERROR),
+                scrollable(log).height(60),
+            ]
+            .spacing(SPACING_SM)
+            .width(Fill),
+            self.focusable(
+                FocusTarget::DismissFailures,
+                button("Dismiss")
+                    .style(button::secondary)
+                    .on_press(Message::DismissFailedRenames)
+            ),
+            self.focusable(
+                FocusTarget::RetryFailed,
+                button(text("Retry Failed").size(FONT_LG))
+                    .on_press(Message::RetryFailedRenames)
+                    .style(button::success)
+            ),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
+
+    // Each kind of difference under its own heading; rows renamed to a name that isn't
+    // listed are also tagged in the preview
+    fn view_name_check(&self) -> Element<'_, Message> {
+        let Some(check) = &self.name_check else {
+            return Space::new(0, 0).into();
+        };
+        let budget = line_char_budget(self.window_width) * 2 / 3;
+        let arrow = self.preview_arrow.glyph();
+        let sections = [
+            ("Listed but no file gets the name", check.missing.clone()),
+            (
+                "Renamed to a name that isn't listed",
+                check
+                    .unexpected
+                    .iter()
+                    .map(|(from, to)| format!("{} {} {}", from, arrow, to))
+                    .collect(),
+            ),
+            ("Listed more than once", check.repeated.clone()),
+        ];
+        let heading_color = if self.dark_mode {
+            COLOR_WARNING
         } else {
-            Column::with_children(file_buttons).spacing(SPACING_XS)
+            COLOR_WARNING_LIGHT
+        };
+        let mut body = Column::new().spacing(SPACING_XS);
+        for (heading, lines) in sections {
+            if lines.is_empty() {
+                continue;
+            }
+            body = body.push(
+                text(format!("{} ({})", heading, lines.len()))
+                    .size(FONT_SM)
+                    .color(heading_color),
+            );
+            for line in lines {
+                let line = elide_middle(&line, budget).unwrap_or(line);
+                body = body.push(text(line).size(FONT_SM));
+            }
+        }
+        let (title, color) = if check.is_match() {
+            (
+                format!(
+                    "All {} new name(s) match the {} listed",
+                    self.previews.len(),
+                    check.listed
+                ),
+                COLOR_SUCCESS,
+            )
+        } else {
+            (
+                format!(
+                    "{} new name(s) checked against {} listed name(s)",
+                    self.previews.len(),
+                    check.listed
+                ),
+                COLOR_ERROR,
+            )
+        };
+        row![
+            column![
+                text(title).size(FONT_LG).color(color),
+                scrollable(body.width(Fill)).height(90),
+            ]
+            .spacing(SPACING_SM)
+            .width(Fill),
+            self.focusable(
+                FocusTarget::CloseNameCheck,
+                button("Close")
+                    .style(button::secondary)
+                    .on_press(Message::CloseNameCheck)
+            ),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
+
+    // Placeholders the template uses, then each problem with the column it starts at
+    fn view_template_lint(&self) -> Element<'_, Message> {
+        let lint = &self.template_lint;
+        let muted = if self.dark_mode {
+            COLOR_MUTED_DARK
+        } else {
+            COLOR_MUTED_LIGHT
+        };
+        let color = if self.dark_mode {
+            COLOR_WARNING
+        } else {
+            COLOR_WARNING_LIGHT
         };
+        let placeholders = (!lint.placeholders.is_empty()).then(|| {
+            text(format!("Uses {}", lint.placeholders.join(", ")))
+                .size(FONT_SM)
+                .color(muted)
+        });
+        let problems = lint.problems.iter().map(|problem| {
+            let line = match problem.column {
+                Some(column) => format!("Col {}: {}", column, problem.message),
+                None => problem.message.clone(),
+            };
+            text(line).size(FONT_SM).color(color).into()
+        });
+        Column::new()
+            .push_maybe(placeholders)
+            .extend(problems)
+            .spacing(SPACING_XS)
+            .width(200)
+            .into()
+    }
 
-        let controls = row![
-            button("Up").on_press(Message::MoveUp),
-            button("Down").on_press(Message::MoveDown),
-            button("Remove (Del)").on_press(Message::RemoveFile)
+    fn view_analysis(&self) -> Element<'_, Message> {
+        let Some(analysis) = &self.analysis else {
+            return Space::new(0, 0).into();
+        };
+        let observations = analysis
+            .observations()
+            .into_iter()
+            .map(|line| text(line).size(FONT_SM).into());
+        let suggestions = analysis.suggestions.iter().enumerate().map(|(i, s)| {
+            row![
+                self.focusable(
+                    FocusTarget::ApplySuggestion(i),
+                    button(text("Apply").size(FONT_SM))
+                        .padding([SPACING_XS, SPACING_SM])
+                        .on_press(Message::ApplySuggestion(i))
+                ),
+                text(&s.message).size(FONT_SM),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Center)
+            .into()
+        });
+        let body = Column::with_children(observations)
+            .push_maybe(analysis.suggestions.is_empty().then(|| {
+                text("No renames to suggest")
+                    .size(FONT_SM)
+                    .color(COLOR_MUTED_DARK)
+            }))
+            .extend(suggestions)
+            .spacing(SPACING_XS);
+        row![
+            column![
+                text(format!("Analysis of {} name(s)", analysis.file_count)).size(FONT_LG),
+                scrollable(body.width(Fill)).height(90),
+            ]
+            .spacing(SPACING_SM)
+            .width(Fill),
+            self.focusable(
+                FocusTarget::CloseAnalysis,
+                button("Close")
+                    .style(button::secondary)
+                    .on_press(Message::CloseAnalysis)
+            ),
         ]
-        .spacing(SPACING_SM);
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
 
+    fn view_settings(&self) -> Element<'_, Message> {
         column![
-            header,
-            horizontal_rule(1),
-            scrollable(file_list).height(LIST_HEIGHT),
-            horizontal_rule(1),
-            controls
+            row![
+                column![
+                    text("Preview time limit (ms):").size(FONT_SM),
+                    text_input("2000", &self.preview_budget)
+                        .id(FocusTarget::PreviewBudgetInput.widget_id())
+                        .on_input(Message::PreviewBudgetChanged)
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Preview arrow:").size(FONT_SM),
+                    self.focusable(
+                        FocusTarget::PreviewArrowPicker,
+                        pick_list(
+                            PreviewArrow::ALL,
+                            Some(self.preview_arrow),
+                            Message::PreviewArrowChanged
+                        )
+                    )
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Max name width (chars):").size(FONT_SM),
+                    text_input("Fit", &self.max_name_width)
+                        .id(FocusTarget::MaxNameWidthInput.widget_id())
+                        .on_input_maybe(self.truncate_names.then_some(Message::MaxNameWidthChanged))
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Max files per folder:").size(FONT_SM),
+                    text_input("No limit", &self.per_folder_limit)
+                        .id(FocusTarget::PerFolderLimitInput.widget_id())
+                        .on_input(Message::PerFolderLimitChanged)
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Confirm renames over (files):").size(FONT_SM),
+                    text_input("Never", &self.confirm_threshold)
+                        .id(FocusTarget::ConfirmThresholdInput.widget_id())
+                        .on_input(Message::ConfirmThresholdChanged)
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Run when idle after (s):").size(FONT_SM),
+                    text_input(&DEFAULT_IDLE_QUIET_SECS.to_string(), &self.idle_quiet_secs)
+                        .id(FocusTarget::IdleQuietInput.widget_id())
+                        .on_input(Message::IdleQuietChanged)
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("File order:").size(FONT_SM),
+                    self.focusable(
+                        FocusTarget::SortOrderPicker,
+                        pick_list(
+                            SortOrder::ALL,
+                            Some(self.sort_order),
+                            Message::SortOrderChanged
+                        )
+                    )
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Extra protected folders:").size(FONT_SM),
+                    text_input(PROTECTED_ROOTS_PLACEHOLDER, &self.protected_roots)
+                        .id(FocusTarget::ProtectedRootsInput.widget_id())
+                        .on_input(Message::ProtectedRootsChanged)
+                        .width(220)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Folder prefix separator:").size(FONT_SM),
+                    text_input("None", &self.folder_separator)
+                        .id(FocusTarget::FolderSeparatorInput.widget_id())
+                        .on_input(Message::FolderSeparatorChanged)
+                        .width(120)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Run after each rename (command):").size(FONT_SM),
+                    text_input("None", &self.post_rename_hook)
+                        .id(FocusTarget::PostRenameHookInput.widget_id())
+                        .on_input(Message::PostRenameHookChanged)
+                        .width(260)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    text("Extension groups:").size(FONT_SM),
+                    text_input("Images: jpg png; Video: mp4 mkv", &self.extension_groups)
+                        .id(FocusTarget::ExtensionGroupsInput.widget_id())
+                        .on_input(Message::ExtensionGroupsChanged)
+                        .width(260)
+                ]
+                .spacing(SPACING_SM),
+            ]
+            .spacing(SPACING_LG)
+            .align_y(Bottom)
+            .wrap(),
+            row![
+                column![
+                    self.focusable(
+                        FocusTarget::OrderedCommitToggle,
+                        checkbox(
+                            "Rename in dependency order (temp names only for cycles)",
+                            self.ordered_commit
+                        )
+                        .on_toggle(Message::OrderedCommitToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::ImportKeepOrderToggle,
+                        checkbox("Keep imported list order", self.import_keep_order)
+                            .on_toggle(Message::ImportKeepOrderToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::NumberDuplicatesToggle,
+                        checkbox(
+                            "Number duplicate names: photo (1).jpg, photo (2).jpg",
+                            self.number_duplicates
+                        )
+                        .on_toggle(Message::NumberDuplicatesToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::StrictCaseToggle,
+                        checkbox(
+                            "Flag names differing only in letter case (always on for Windows \
+                             and macOS)",
+                            self.strict_case_check || CASE_INSENSITIVE_OS
+                        )
+                        .on_toggle_maybe(
+                            (!CASE_INSENSITIVE_OS).then_some(Message::StrictCaseToggled)
+                        )
+                    ),
+                ],
+                column![
+                    self.focusable(
+                        FocusTarget::TruncateNamesToggle,
+                        checkbox("Shorten long names in preview", self.truncate_names)
+                            .on_toggle(Message::TruncateNamesToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::NotifyToggle,
+                        checkbox(
+                            "Notify when scans and renames finish",
+                            self.notify_on_completion
+                        )
+                        .on_toggle(Message::NotifyToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::ProbeDirectoriesToggle,
+                        checkbox(
+                            "Test folder access with a temp file if the OS can't tell",
+                            self.probe_directories
+                        )
+                        .on_toggle(Message::ProbeDirectoriesToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::VerifyRenamesToggle,
+                        checkbox("Verify renamed files afterwards", self.verify_renames)
+                            .on_toggle(Message::VerifyRenamesToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::RecordOriginalNamesToggle,
+                        checkbox(
+                            "Record each file's original name",
+                            self.record_original_names
+                        )
+                        .on_toggle(Message::RecordOriginalNamesToggled)
+                    ),
+                ],
+            ]
+            .spacing(SPACING_LG),
+            row![
+                self.focusable(
+                    FocusTarget::OpenDataFolder,
+                    button("Open Data Folder").on_press(Message::OpenDataFolder)
+                ),
+                self.focusable(
+                    FocusTarget::BackUpSettings,
+                    button("Back Up Settings...").on_press(Message::BackUpSettings)
+                ),
+                self.focusable(
+                    FocusTarget::RestoreSettings,
+                    button("Restore from Backup...").on_press(Message::RestoreSettings)
+                ),
+            ]
+            .spacing(SPACING_SM),
         ]
         .spacing(SPACING_MD)
-        .width(Fill)
         .into()
     }
 
-    fn view_preview(&self) -> Element<'_, Message> {
-        let items: Vec<Element<'_, Message>> = if self.previews.is_empty() {
-            vec![text("Preview appears here after configuring options.")
+    fn view_find_replace_options(&self) -> Element<'_, Message> {
+        let options = row![
+            row![
+                column![
+                    field_label(
+                        "Find:",
+                        &self.find_pattern,
+                        "",
+                        Message::FindPatternChanged(String::new())
+                    ),
+                    text_input("Pattern...", &self.find_pattern)
+                        .id(FocusTarget::FindInput.widget_id())
+                        .on_input(Message::FindPatternChanged)
+                        .width(210),
+                    self.focusable(
+                        FocusTarget::RegexSnippetPicker,
+                        pick_list(REGEX_SNIPPETS, None::<RegexSnippet>, |s| {
+                            Message::InsertRegexSnippet(s)
+                        })
+                        .placeholder("Insert regex snippet...")
+                        .text_size(FONT_SM)
+                        .width(210)
+                    ),
+                ]
+                .spacing(SPACING_SM),
+                self.focusable(
+                    FocusTarget::SwapFindReplace,
+                    button("Swap").on_press(Message::SwapFindReplace)
+                ),
+                column![
+                    field_label(
+                        "Replace:",
+                        &self.replace_with,
+                        "",
+                        Message::ReplaceWithChanged(String::new())
+                    ),
+                    text_input("Replacement...", &self.replace_with)
+                        .id(FocusTarget::ReplaceInput.widget_id())
+                        .on_input(Message::ReplaceWithChanged)
+                        .width(210),
+                    self.view_skip_if_contains_input(),
+                ]
+                .spacing(SPACING_SM),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Bottom),
+            column![
+                row![
+                    self.focusable(
+                        FocusTarget::RegexToggle,
+                        checkbox("Regex", self.regex_mode).on_toggle(Message::RegexModeToggled)
+                    ),
+                    self.focusable(
+                        FocusTarget::TermsModeToggle,
+                        checkbox("Any of", self.terms_mode).on_toggle(Message::TermsModeToggled)
+                    ),
+                    self.view_whole_word_toggle(),
+                ]
+                .spacing(SPACING_SM),
+                row![
+                    self.focusable(
+                        FocusTarget::CaseToggle,
+                        checkbox("Case Sensitive", self.case_sensitive)
+                            .on_toggle(Message::CaseSensitiveToggled)
+                    ),
+                    self.view_lowercase_toggle(),
+                ]
+                .spacing(SPACING_SM),
+                row![
+                    self.focusable(
+                        FocusTarget::CaseLocalePicker,
+                        pick_list(
+                            CaseLocale::ALL,
+                            Some(self.case_locale),
+                            Message::CaseLocaleChanged
+                        )
+                        .text_size(FONT_SM)
+                    ),
+                    self.focusable(
+                        FocusTarget::ShowCapturesToggle,
+                        checkbox("Show groups", self.show_captures)
+                            .on_toggle(Message::ShowCapturesToggled)
+                    ),
+                ]
+                .spacing(SPACING_SM)
+                .align_y(Center),
+            ]
+            .spacing(SPACING_SM),
+            horizontal_space(),
+            self.view_execute_button(),
+        ]
+        .spacing(SPACING_LG)
+        .align_y(Center);
+        column![options]
+            .push_maybe(self.view_capture_views())
+            .spacing(SPACING_MD)
+            .into()
+    }
+
+    // Matching names with each regex group's text highlighted, then `$1 = ...` per group
+    fn view_capture_views(&self) -> Option<Element<'_, Message>> {
+        if !self.show_captures {
+            return None;
+        }
+        if !self.regex_mode {
+            return Some(
+                text("Groups are shown for regex patterns")
+                    .size(FONT_SM)
+                    .color(COLOR_MUTED_DARK)
+                    .into(),
+            );
+        }
+        if self.capture_views.is_empty() {
+            return Some(
+                text("No name matches the pattern")
+                    .size(FONT_SM)
+                    .color(COLOR_MUTED_DARK)
+                    .into(),
+            );
+        }
+        let color = |group: usize| match group {
+            0 => GROUP_COLORS[0],
+            i => GROUP_COLORS[1 + (i - 1) % (GROUP_COLORS.len() - 1)],
+        };
+        let rows = self.capture_views.iter().map(|view| {
+            let name: Vec<_> = view
+                .parts
+                .iter()
+                .map(|(part, group)| span(part.as_str()).background_maybe(group.map(color)))
+                .collect();
+            let legend: Vec<_> = view
+                .groups
+                .iter()
+                .enumerate()
+                .flat_map(|(i, captured)| {
+                    let value = captured
+                        .as_deref()
+                        .map_or("(no match)".to_string(), |c| format!("'{}'", c));
+                    [
+                        span(format!("  ${}", i + 1)).background(color(i + 1)),
+                        span(format!(" {}", value)),
+                    ]
+                })
+                .collect();
+            row![
+                rich_text(name).size(FONT_SM),
+                rich_text(legend).size(FONT_SM)
+            ]
+            .spacing(SPACING_MD)
+            .into()
+        });
+        Some(Column::with_children(rows).spacing(SPACING_XS).into())
+    }
+
+    fn view_lowercase_toggle(&self) -> Element<'_, Message> {
+        self.focusable(
+            FocusTarget::LowercaseToggle,
+            checkbox("Lowercase result", self.lowercase_result)
+                .on_toggle(Message::LowercaseResultToggled),
+        )
+    }
+
+    // Shared by the plain and "match any of" layouts, under the replacement
+    fn view_skip_if_contains_input(&self) -> Element<'_, Message> {
+        text_input("Skip names containing...", &self.skip_if_contains)
+            .id(FocusTarget::SkipIfContainsInput.widget_id())
+            .on_input(Message::SkipIfContainsChanged)
+            .size(FONT_SM)
+            .width(210)
+            .into()
+    }
+
+    fn view_whole_word_toggle(&self) -> Element<'_, Message> {
+        self.focusable(
+            FocusTarget::WholeWordToggle,
+            checkbox("Whole word", self.whole_word).on_toggle(Message::WholeWordToggled),
+        )
+    }
+
+    fn view_find_any_of_options(&self) -> Element<'_, Message> {
+        let chips: Vec<Element<'_, Message>> = if self.find_terms.is_empty() {
+            vec![text("Press Enter to add each string to match")
                 .size(FONT_SM)
+                .color(COLOR_MUTED_DARK)
                 .into()]
         } else {
-            self.previews
+            self.find_terms
                 .iter()
-                .map(|p| {
-                    let conflict = if p.has_conflict {
-                        text(" [CONFLICT]").color(COLOR_CONFLICT)
-                    } else {
-                        text("")
-                    };
-                    column![
-                        text(p.original_name.as_str()).size(FONT_SM),
-                        row![
-                            text("  -> ").size(FONT_SM).color(COLOR_INFO),
-                            text(&p.new_name).size(FONT_SM).color(COLOR_SUCCESS),
-                            conflict
-                        ]
-                    ]
-                    .spacing(SPACING_XS)
-                    .into()
+                .enumerate()
+                .map(|(i, term)| {
+                    button(text(format!("{} ×", term)).size(FONT_SM))
+                        .padding([SPACING_XS, SPACING_SM])
+                        .style(button::secondary)
+                        .on_press(Message::RemoveTerm(i))
+                        .into()
                 })
                 .collect()
         };
 
+        // Read-only view of the generated regex
+        let pattern = self.find_params().0;
+        let budget = line_char_budget(self.window_width) / 2;
+        let pattern = elide_middle(&pattern, budget).unwrap_or(pattern);
+        let selected_preset = self
+            .term_presets
+            .iter()
+            .find(|p| p.name == self.preset_name)
+            .map(|p| p.name.clone());
+        let preset_names: Vec<String> = self.term_presets.iter().map(|p| p.name.clone()).collect();
+        // Links the chosen preset to the last scanned folder, or unlinks it when already linked
+        let link_label = self.folder_link_label(&FolderOptions::Terms(self.preset_name.clone()));
+        let link_enabled = selected_preset.is_some() && !self.recent_folders.is_empty();
+        let associations =
+            self.view_folder_links(|options| matches!(options, FolderOptions::Terms(_)));
+        let undo = self.view_folder_preset_undo();
+
         column![
-            text("Preview").size(FONT_LG),
-            horizontal_rule(1),
-            scrollable(Column::with_children(items).spacing(8)).height(LIST_HEIGHT)
+            row![
+                column![
+                    text("Find any of:").size(FONT_SM),
+                    text_input("Add a string...", &self.term_input)
+                        .id(FocusTarget::FindInput.widget_id())
+                        .on_input(Message::TermInputChanged)
+                        .on_submit(Message::AddTerm)
+                        .width(210)
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    field_label(
+                        "Replace:",
+                        &self.replace_with,
+                        "",
+                        Message::ReplaceWithChanged(String::new())
+                    ),
+                    text_input("Replacement...", &self.replace_with)
+                        .id(FocusTarget::ReplaceInput.widget_id())
+                        .on_input(Message::ReplaceWithChanged)
+                        .width(210),
+                    self.view_skip_if_contains_input(),
+                ]
+                .spacing(SPACING_SM),
+                column![
+                    row![
+                        self.focusable(
+                            FocusTarget::TermsModeToggle,
+                            checkbox("Any of", self.terms_mode)
+                                .on_toggle(Message::TermsModeToggled)
+                        ),
+                        self.view_whole_word_toggle(),
+                    ]
+                    .spacing(SPACING_SM),
+                    row![
+                        self.focusable(
+                            FocusTarget::CaseToggle,
+                            checkbox("Case Sensitive", self.case_sensitive)
+                                .on_toggle(Message::CaseSensitiveToggled)
+                        ),
+                        self.view_lowercase_toggle(),
+                    ]
+                    .spacing(SPACING_SM),
+                    self.focusable(
+                        FocusTarget::CaseLocalePicker,
+                        pick_list(
+                            CaseLocale::ALL,
+                            Some(self.case_locale),
+                            Message::CaseLocaleChanged
+                        )
+                        .text_size(FONT_SM)
+                    )
+                ]
+                .spacing(SPACING_SM),
+                horizontal_space(),
+                self.view_execute_button(),
+            ]
+            .spacing(SPACING_LG)
+            .align_y(Center),
+            Row::with_children(chips).spacing(SPACING_XS).wrap(),
+            row![
+                text(format!("Pattern: {}", pattern))
+                    .size(FONT_SM)
+                    .color(COLOR_INFO),
+                horizontal_space(),
+                self.focusable(
+                    FocusTarget::TermPresetPicker,
+                    pick_list(preset_names, selected_preset, Message::TermPresetSelected)
+                        .placeholder("Presets")
+                        .text_size(FONT_SM)
+                ),
+                text_input("Preset name", &self.preset_name)
+                    .id(FocusTarget::TermPresetNameInput.widget_id())
+                    .on_input(Message::TermPresetNameChanged)
+                    .on_submit(Message::SaveTermPreset)
+                    .size(FONT_SM)
+                    .width(140),
+                self.focusable(
+                    FocusTarget::SaveTermPreset,
+                    button(text("Save").size(FONT_SM)).on_press(Message::SaveTermPreset)
+                ),
+                self.focusable(
+                    FocusTarget::DeleteTermPreset,
+                    button(text("Delete").size(FONT_SM))
+                        .style(button::secondary)
+                        .on_press(Message::DeleteTermPreset)
+                ),
+                self.focusable(
+                    FocusTarget::ToggleFolderPreset,
+                    button(text(link_label).size(FONT_SM))
+                        .style(button::secondary)
+                        .on_press_maybe(link_enabled.then_some(Message::ToggleFolderPreset))
+                ),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Center),
         ]
-        .spacing(SPACING_MD)
-        .width(Fill)
+        .push_maybe(undo)
+        .push(associations)
+        .spacing(SPACING_SM)
         .into()
     }
 
-    fn view_options(&self) -> Element<'_, Message> {
-        match self.mode {
-            AppMode::FindReplace => self.view_find_replace_options(),
-            AppMode::Iteration => self.view_iteration_options(),
+    fn folder_link_label(&self, options: &FolderOptions) -> &'static str {
+        match self.recent_folders.first() {
+            Some(folder)
+                if self
+                    .folder_presets
+                    .iter()
+                    .any(|a| a.folder == *folder && a.options == *options) =>
+            {
+                "Unlink Folder"
+            }
+            _ => "Link Folder",
         }
     }
 
-    fn view_find_replace_options(&self) -> Element<'_, Message> {
-        row![
+    // Removable chips for the folder links the filter picks out
+    fn view_folder_links(&self, filter: impl Fn(&FolderOptions) -> bool) -> Element<'_, Message> {
+        let budget = line_char_budget(self.window_width) / 4;
+        let chips: Vec<Element<'_, Message>> = self
+            .folder_presets
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| filter(&a.options))
+            .map(|(i, a)| {
+                let folder = a.folder.display().to_string();
+                let folder = elide_middle(&folder, budget).unwrap_or(folder);
+                button(text(format!("{} → {} ×", folder, a.options)).size(FONT_SM))
+                    .padding([SPACING_XS, SPACING_SM])
+                    .style(button::secondary)
+                    .on_press(Message::RemoveFolderPreset(i))
+                    .into()
+            })
+            .collect();
+        Row::with_children(chips).spacing(SPACING_XS).wrap().into()
+    }
+
+    fn view_folder_preset_undo(&self) -> Option<Element<'_, Message>> {
+        let undo = self.folder_preset_undo.as_ref()?;
+        Some(
+            row![
+                text(format!("Loaded \"{}\" for this folder", undo.preset))
+                    .size(FONT_SM)
+                    .color(COLOR_INFO),
+                self.focusable(
+                    FocusTarget::UndoFolderPreset,
+                    button(text("Undo").size(FONT_SM))
+                        .on_press(Message::UndoFolderPreset)
+                        .style(button::secondary)
+                ),
+            ]
+            .spacing(SPACING_MD)
+            .align_y(Center)
+            .into(),
+        )
+    }
+
+    fn view_iteration_options(&self) -> Element<'_, Message> {
+        let options = row![
+            column![
+                field_label(
+                    "Template ({n}, {n1}…):",
+                    &self.template,
+                    DEFAULT_TEMPLATE,
+                    Message::TemplateChanged(DEFAULT_TEMPLATE.to_string())
+                ),
+                text_input("photo_{n}", &self.template)
+                    .id(FocusTarget::TemplateInput.widget_id())
+                    .on_input(Message::TemplateChanged)
+                    .width(200),
+                self.view_template_lint()
+            ]
+            .spacing(SPACING_SM),
+            column![
+                self.view_start_label(),
+                text_input("1", &self.start_number)
+                    .id(FocusTarget::StartInput.widget_id())
+                    .on_input(Message::StartNumberChanged)
+                    .width(80)
+            ]
+            .spacing(SPACING_SM),
+            column![
+                self.view_padding_label(),
+                text_input("3", &self.padding)
+                    .id(FocusTarget::PaddingInput.widget_id())
+                    .on_input(Message::PaddingChanged)
+                    .width(80)
+            ]
+            .spacing(SPACING_SM),
             column![
-                text("Find:").size(FONT_SM),
-                text_input("Pattern...", &self.find_pattern)
-                    .on_input(Message::FindPatternChanged)
-                    .width(250)
+                text("Count per ({g}):").size(FONT_SM),
+                self.focusable(
+                    FocusTarget::NumberGroupingPicker,
+                    pick_list(
+                        NumberGrouping::ALL,
+                        Some(self.number_grouping),
+                        Message::NumberGroupingChanged
+                    )
+                    .text_size(FONT_SM)
+                )
             ]
             .spacing(SPACING_SM),
             column![
-                text("Replace:").size(FONT_SM),
-                text_input("Replacement...", &self.replace_with)
-                    .on_input(Message::ReplaceWithChanged)
-                    .width(250)
+                self.focusable(
+                    FocusTarget::MatchWidthToggle,
+                    checkbox("Match width used in folder", self.match_existing_width)
+                        .on_toggle(Message::MatchExistingWidthToggled)
+                ),
+                self.focusable(
+                    FocusTarget::FitPaddingToggle,
+                    checkbox("Pad to the largest number", self.fit_padding)
+                        .on_toggle(Message::FitPaddingToggled)
+                ),
+                self.focusable(
+                    FocusTarget::SkipExistingToggle,
+                    checkbox("Skip used numbers", self.skip_existing)
+                        .on_toggle(Message::SkipExistingToggled)
+                ),
+                self.focusable(
+                    FocusTarget::KeepNumberedToggle,
+                    checkbox("Keep names already numbered", self.keep_numbered)
+                        .on_toggle(Message::KeepNumberedToggled)
+                ),
+                self.focusable(
+                    FocusTarget::CompoundExtensionsToggle,
+                    checkbox("Treat .tar.gz as one extension", self.compound_extensions)
+                        .on_toggle(Message::CompoundExtensionsToggled)
+                ),
+                self.focusable(
+                    FocusTarget::ReverseNumberingToggle,
+                    checkbox("Reverse numbering only", self.reverse_numbering)
+                        .on_toggle(Message::ReverseNumberingToggled)
+                ),
+                self.focusable(
+                    FocusTarget::ContinueNumberingToggle,
+                    checkbox("Continue from last run", self.continue_numbering)
+                        .on_toggle(Message::ContinueNumberingToggled)
+                ),
+            ],
+            horizontal_space(),
+            self.view_execute_button(),
+        ]
+        .spacing(SPACING_LG)
+        .align_y(Center);
+        let continuation = self
+            .numbering_continuations
+            .get(&self.template)
+            .filter(|_| self.continue_numbering)
+            .map(|next| {
+                row![
+                    text(format!(
+                        "{} continuing from {} after the last rename",
+                        self.template, next
+                    ))
+                    .size(FONT_SM)
+                    .color(COLOR_INFO),
+                    self.focusable(
+                        FocusTarget::ResetContinuation,
+                        button(text("Reset").size(FONT_SM))
+                            .on_press(Message::ResetContinuation)
+                            .style(button::secondary)
+                    ),
+                ]
+                .spacing(SPACING_MD)
+                .align_y(Center)
+            });
+        let suggestion = self
+            .template_suggestion
+            .filter(|_| self.template == DEFAULT_TEMPLATE)
+            .map(|template| {
+                row![
+                    text(format!("Most files here suit the template {}", template))
+                        .size(FONT_SM)
+                        .color(COLOR_INFO),
+                    self.focusable(
+                        FocusTarget::ApplyTemplateSuggestion,
+                        button(text("Use It").size(FONT_SM))
+                            .on_press(Message::ApplyTemplateSuggestion)
+                    ),
+                    self.focusable(
+                        FocusTarget::DismissTemplateSuggestion,
+                        button(text("Dismiss").size(FONT_SM))
+                            .on_press(Message::DismissTemplateSuggestion)
+                            .style(button::secondary)
+                    ),
+                ]
+                .spacing(SPACING_MD)
+                .align_y(Center)
+            });
+        // Links the template and padding to the last scanned folder
+        let numbering = self.folder_numbering();
+        let link_label = numbering
+            .as_ref()
+            .map_or("Link Folder", |n| self.folder_link_label(n));
+        let link_enabled = numbering.is_some() && !self.recent_folders.is_empty();
+        let folder_link = row![
+            self.focusable(
+                FocusTarget::ToggleFolderNumbering,
+                button(text(link_label).size(FONT_SM))
+                    .style(button::secondary)
+                    .on_press_maybe(link_enabled.then_some(Message::ToggleFolderNumbering))
+            ),
+            self.view_folder_links(|options| matches!(options, FolderOptions::Numbering { .. })),
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center);
+        column![options]
+            .push_maybe(continuation)
+            .push_maybe(suggestion)
+            .push_maybe(self.view_folder_preset_undo())
+            .push(folder_link)
+            .push(self.view_counters())
+            .spacing(SPACING_MD)
+            .into()
+    }
+
+    // One row per {n1}, {n2}... counter: start, step and padding
+    fn view_counters(&self) -> Element<'_, Message> {
+        let field = |i: usize, kind: CounterField, placeholder: &'static str, value: &str| {
+            text_input(placeholder, value)
+                .on_input(move |v| Message::CounterChanged(i, kind, v))
+                .size(FONT_SM)
+                .width(70)
+        };
+        let rows = self.counters.iter().enumerate().map(|(i, counter)| {
+            row![
+                text(format!("{{n{}}}", i + 1)).size(FONT_SM).width(40),
+                field(i, CounterField::Start, "Start", &counter.start),
+                field(i, CounterField::Step, "Step", &counter.step),
+                field(i, CounterField::Padding, "Padding", &counter.padding),
+                button(text("Remove").size(FONT_SM))
+                    .on_press(Message::RemoveCounter(i))
+                    .style(button::secondary),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Center)
+            .into()
+        });
+        let header = row![
+            self.focusable(
+                FocusTarget::AddCounter,
+                button(text("Add Counter").size(FONT_SM)).on_press_maybe(
+                    (self.counters.len() < MAX_COUNTERS).then_some(Message::AddCounter)
+                )
+            ),
+            text("Counters advance together, one step per file: {n1}_{n2} → 1_100, 2_110…")
+                .size(FONT_SM)
+                .color(COLOR_MUTED_DARK),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center);
+        Column::with_children(std::iter::once(header.into()).chain(rows))
+            .spacing(SPACING_XS)
+            .into()
+    }
+
+    fn view_convention_options(&self) -> Element<'_, Message> {
+        row![
+            column![
+                text("Convert to:").size(FONT_SM),
+                self.focusable(
+                    FocusTarget::ConventionPicker,
+                    pick_list(
+                        NameConvention::ALL,
+                        Some(self.name_convention),
+                        Message::NameConventionChanged
+                    )
+                    .width(200)
+                ),
             ]
             .spacing(SPACING_SM),
+            text("Words split at _, -, spaces and capitals; extensions are kept")
+                .size(FONT_SM)
+                .color(COLOR_MUTED_DARK),
+            horizontal_space(),
+            self.view_execute_button(),
+        ]
+        .spacing(SPACING_LG)
+        .align_y(Center)
+        .into()
+    }
+
+    fn view_change_extension_options(&self) -> Element<'_, Message> {
+        row![
             column![
-                checkbox("Regex", self.regex_mode).on_toggle(Message::RegexModeToggled),
-                checkbox("Case Sensitive", self.case_sensitive)
-                    .on_toggle(Message::CaseSensitiveToggled)
+                text("New extension (empty removes it):").size(FONT_SM),
+                text_input("txt", &self.new_extension)
+                    .id(FocusTarget::NewExtensionInput.widget_id())
+                    .on_input(Message::NewExtensionChanged)
+                    .width(120)
             ]
             .spacing(SPACING_SM),
+            self.focusable(
+                FocusTarget::CompoundExtensionsToggle,
+                checkbox("Treat .tar.gz as one extension", self.compound_extensions)
+                    .on_toggle(Message::CompoundExtensionsToggled)
+            ),
+            text("Names starting with a dot, such as .gitignore, have no extension")
+                .size(FONT_SM)
+                .color(COLOR_MUTED_DARK),
             horizontal_space(),
-            button(text("Execute (Ctrl+Enter)").size(FONT_LG))
-                .on_press(Message::ExecuteRename)
-                .style(button::success),
+            self.view_execute_button(),
         ]
         .spacing(SPACING_LG)
         .align_y(Center)
         .into()
     }
 
-    fn view_iteration_options(&self) -> Element<'_, Message> {
+    // One row per rule, in the order they're tried: pattern, template, move up and remove
+    fn view_rules_options(&self) -> Element<'_, Message> {
+        let field = |i: usize, kind: RuleField, placeholder: &'static str, value: &str| {
+            text_input(placeholder, value)
+                .on_input(move |v| Message::RuleChanged(i, kind, v))
+                .size(FONT_SM)
+                .font(Font::MONOSPACE)
+                .width(240)
+        };
+        let rows = self.rename_rules.iter().enumerate().map(|(i, rule)| {
+            row![
+                text(format!("{}.", i + 1)).size(FONT_SM).width(24),
+                field(i, RuleField::Pattern, r"^IMG_(\d+)\.jpg$", &rule.pattern),
+                text("→").size(FONT_SM).color(COLOR_INFO),
+                field(i, RuleField::Template, "photo_$1.jpg", &rule.template),
+                button(text("Up").size(FONT_SM))
+                    .on_press_maybe((i > 0).then_some(Message::MoveRuleUp(i)))
+                    .style(button::secondary),
+                button(text("Remove").size(FONT_SM))
+                    .on_press(Message::RemoveRule(i))
+                    .style(button::secondary),
+            ]
+            .spacing(SPACING_SM)
+            .align_y(Center)
+            .into()
+        });
+        let header = row![
+            self.focusable(
+                FocusTarget::AddRule,
+                button(text("Add Rule").size(FONT_SM)).on_press_maybe(
+                    (self.rename_rules.len() < MAX_RULES).then_some(Message::AddRule)
+                )
+            ),
+            text(
+                "The first rule whose regex matches a name wins; $1, ${name} insert its groups. \
+                 Files no rule matches are left unchanged"
+            )
+            .size(FONT_SM)
+            .color(COLOR_MUTED_DARK),
+            horizontal_space(),
+            self.view_execute_button(),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center);
+        column![
+            header,
+            container(scrollable(Column::with_children(rows).spacing(SPACING_XS))).max_height(150),
+        ]
+        .spacing(SPACING_SM)
+        .into()
+    }
+
+    fn view_by_extension_options(&self) -> Element<'_, Message> {
         row![
             column![
-                text("Template ({n}):").size(FONT_SM),
-                text_input("photo_{n}", &self.template)
-                    .on_input(Message::TemplateChanged)
-                    .width(200)
+                text("Templates by extension ({n}, {n1}…):").size(FONT_SM),
+                text_input(
+                    "jpg png: image_{n}; pdf: doc_{n}",
+                    &self.extension_templates
+                )
+                .id(FocusTarget::ExtensionTemplatesInput.widget_id())
+                .on_input(Message::ExtensionTemplatesChanged)
+                .width(320)
             ]
             .spacing(SPACING_SM),
             column![
-                text("Start:").size(FONT_SM),
+                self.view_start_label(),
                 text_input("1", &self.start_number)
+                    .id(FocusTarget::StartInput.widget_id())
                     .on_input(Message::StartNumberChanged)
                     .width(80)
             ]
             .spacing(SPACING_SM),
             column![
-                text("Padding:").size(FONT_SM),
+                self.view_padding_label(),
                 text_input("3", &self.padding)
+                    .id(FocusTarget::PaddingInput.widget_id())
                     .on_input(Message::PaddingChanged)
                     .width(80)
             ]
             .spacing(SPACING_SM),
+            text("Each mapping counts on its own; other files are left unchanged")
+                .size(FONT_SM)
+                .color(COLOR_MUTED_DARK),
             horizontal_space(),
-            button(text("Execute (Ctrl+Enter)").size(FONT_LG))
-                .on_press(Message::ExecuteRename)
-                .style(button::success),
+            self.view_execute_button(),
         ]
         .spacing(SPACING_LG)
         .align_y(Center)
         .into()
     }
 
-    fn view_status(&self) -> Element<'_, Message> {
-        let color = if self.is_error {
-            COLOR_ERROR
+    fn view_start_label(&self) -> Element<'_, Message> {
+        let default = Settings::default().start_number.to_string();
+        field_label(
+            "Start:",
+            &self.start_number,
+            &default,
+            Message::StartNumberChanged(default.clone()),
+        )
+    }
+
+    fn view_padding_label(&self) -> Element<'_, Message> {
+        let default = Settings::default().padding.to_string();
+        field_label(
+            "Padding:",
+            &self.padding,
+            &default,
+            Message::PaddingChanged(default.clone()),
+        )
+    }
+
+    fn view_execute_button(&self) -> Element<'_, Message> {
+        let idle = !self.is_renaming && !self.is_scanning;
+        let execute_label = if self.is_renaming {
+            "Renaming…"
         } else {
-            COLOR_MUTED_DARK
+            "Execute (Ctrl+Enter)"
         };
-        container(
-            text(self.status_message.as_deref().unwrap_or("Ready"))
+        row![
+            text("If a name is taken:").size(FONT_SM),
+            self.focusable(
+                FocusTarget::ConflictPolicyPicker,
+                pick_list(
+                    ConflictPolicy::ALL,
+                    Some(self.conflict_policy),
+                    Message::ConflictPolicyChanged
+                )
+                .text_size(FONT_SM)
+            ),
+            self.focusable(
+                FocusTarget::SelectionOnlyToggle,
+                checkbox("Apply to selection only", self.selection_only)
+                    .on_toggle(Message::SelectionOnlyToggled)
+            ),
+            self.focusable(
+                FocusTarget::Execute,
+                button(text(execute_label).size(FONT_LG))
+                    .on_press_maybe(idle.then_some(Message::ExecuteRename))
+                    .style(button::success),
+            ),
+            self.view_idle_run(),
+        ]
+        .spacing(SPACING_MD)
+        .align_y(Center)
+        .into()
+    }
+
+    // "Run When Idle" arms the rename; while armed, a countdown that restarts whenever
+    // the folders change, and a way to cancel
+    fn view_idle_run(&self) -> Element<'_, Message> {
+        let armed = self.idle_run.is_some() || self.idle_execute_pending;
+        let toggle = self.focusable(
+            FocusTarget::RunWhenIdle,
+            button(
+                text(if armed {
+                    "Cancel Idle Run"
+                } else {
+                    "Run When Idle"
+                })
+                .size(FONT_SM),
+            )
+            .on_press_maybe(
+                (armed || !self.files.is_empty() && !self.is_renaming)
+                    .then_some(Message::IdleRunToggled),
+            )
+            .style(if armed {
+                button::danger
+            } else {
+                button::secondary
+            }),
+        );
+        let Some(run) = &self.idle_run else {
+            return toggle;
+        };
+        let remaining = self
+            .idle_quiet()
+            .saturating_sub(run.quiet_since.elapsed().as_secs());
+        let color = if self.dark_mode {
+            COLOR_WARNING
+        } else {
+            COLOR_WARNING_LIGHT
+        };
+        row![
+            text(format!("Armed: runs in {} s without changes", remaining))
                 .size(FONT_SM)
                 .color(color),
+            toggle
+        ]
+        .spacing(SPACING_SM)
+        .align_y(Center)
+        .into()
+    }
+
+    fn view_status(&self) -> Element<'_, Message> {
+        if let Some((notice, _)) = &self.copy_notice {
+            let color = StatusLevel::Success.color(self.dark_mode);
+            return container(
+                row![
+                    text(StatusLevel::Success.icon()).color(color),
+                    text(notice).size(FONT_SM).color(color),
+                ]
+                .spacing(SPACING_SM)
+                .align_y(Center),
+            )
+            .padding(SPACING_MD)
+            .width(Fill)
+            .into();
+        }
+        let color = self.status_level.color(self.dark_mode);
+        let chevron = (self.status_level == StatusLevel::Error && self.error_details.is_some())
+            .then(|| {
+                let label = if self.error_details_open {
+                    "▾ Details"
+                } else {
+                    "▸ Details"
+                };
+                self.focusable(
+                    FocusTarget::ErrorDetailsToggle,
+                    button(text(label).size(FONT_SM))
+                        .on_press(Message::ErrorDetailsToggled)
+                        .style(button::text),
+                )
+            });
+        container(
+            row![
+                text(self.status_level.icon()).color(color),
+                fitted_text(
+                    self.status_message.as_deref().unwrap_or("Ready"),
+                    line_char_budget(self.window_width).saturating_sub(20),
+                    Some(color),
+                ),
+            ]
+            .push_maybe(chevron)
+            .push_maybe(self.status_message.as_ref().map(|_| {
+                self.focusable(
+                    FocusTarget::CopyStatus,
+                    button(text("Copy").size(FONT_SM))
+                        .on_press(Message::CopyStatus)
+                        .style(button::text),
+                )
+            }))
+            .push_maybe(self.cleared_files.as_ref().map(|_| {
+                self.focusable(
+                    FocusTarget::RestoreClearedFiles,
+                    button(text("Restore").size(FONT_SM))
+                        .on_press(Message::RestoreClearedFiles)
+                        .style(button::text),
+                )
+            }))
+            .spacing(SPACING_SM)
+            .align_y(Center),
+        )
+        .padding(SPACING_MD)
+        .width(Fill)
+        .into()
+    }
+
+    // Full error chain and affected paths; long lines wrap instead of overflowing
+    fn view_error_details(&self) -> Element<'_, Message> {
+        let Some(details) = self
+            .error_details
+            .as_ref()
+            .filter(|_| self.error_details_open)
+        else {
+            return Space::new(0, 0).into();
+        };
+        let color = details.color(self.dark_mode);
+        let lines = details.lines.iter().map(|line| {
+            text(line)
+                .size(FONT_SM)
+                .font(Font::MONOSPACE)
+                .color(color)
+                .wrapping(text::Wrapping::WordOrGlyph)
+                .into()
+        });
+        let paths = details.paths.iter().map(|path| {
+            text(path.display().to_string())
+                .size(FONT_SM)
+                .font(Font::MONOSPACE)
+                .wrapping(text::Wrapping::WordOrGlyph)
+                .into()
+        });
+        let mut body = Column::new()
+            .push(text(details.kind.to_string()).size(FONT_LG).color(color))
+            .push_maybe(details.kind.hint().map(|hint| text(hint).size(FONT_SM)))
+            .extend(lines)
+            .spacing(SPACING_XS);
+        if !details.paths.is_empty() {
+            body = body
+                .push(text("Affected path(s):").size(FONT_SM))
+                .push(Column::with_children(paths).spacing(SPACING_XS));
+        }
+        let suggestion = details.suggestion().map(|(label, message)| {
+            self.focusable(
+                FocusTarget::ErrorSuggestion,
+                button(text(label).size(FONT_SM)).on_press(message),
+            )
+        });
+        container(
+            row![
+                scrollable(body.width(Fill)).height(90),
+                column![]
+                    .push_maybe(suggestion)
+                    .push(self.focusable(
+                        FocusTarget::CopyErrorDetails,
+                        button(text("Copy").size(FONT_SM)).on_press(Message::CopyErrorDetails)
+                    ))
+                    .push(
+                        self.focusable(
+                            FocusTarget::DismissErrorDetails,
+                            button(text("Dismiss").size(FONT_SM))
+                                .on_press(Message::DismissErrorDetails)
+                                .style(button::secondary)
+                        )
+                    )
+                    .spacing(SPACING_XS),
+            ]
+            .spacing(SPACING_MD),
         )
         .padding(SPACING_MD)
         .width(Fill)
+        .style(container::rounded_box)
         .into()
     }
 }
+
+// Char position just past the text changed between two versions of an input's value,
+// which is where the caret sits after typing, pasting or deleting
+fn edit_caret(old: &str, new: &str) -> usize {
+    let prefix = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let new_len = new.chars().count();
+    let room = old.chars().count().min(new_len) - prefix;
+    let suffix = old
+        .chars()
+        .rev()
+        .zip(new.chars().rev())
+        .take(room)
+        .take_while(|(a, b)| a == b)
+        .count();
+    new_len - suffix
+}
+
+// Inserts `snippet` at a char position, returning the new text and the caret after it
+fn insert_at_caret(text: &str, caret: usize, snippet: &str) -> (String, usize) {
+    let byte = text
+        .char_indices()
+        .nth(caret)
+        .map_or(text.len(), |(i, _)| i);
+    let inserted = format!("{}{}{}", &text[..byte], snippet, &text[byte..]);
+    (
+        inserted,
+        text[..byte].chars().count() + snippet.chars().count(),
+    )
+}
+
+// "1.2 s, 850 files/s" for a batch of `files` that began at `started`
+fn batch_timing(started: Instant, files: usize) -> String {
+    let elapsed = started.elapsed();
+    let duration = if elapsed < Duration::from_secs(1) {
+        format!("{} ms", elapsed.as_millis())
+    } else {
+        format!("{:.1} s", elapsed.as_secs_f64())
+    };
+    match elapsed.as_secs_f64() {
+        secs if secs > 0.0 => format!("{}, {:.0} files/s", duration, files as f64 / secs),
+        _ => duration,
+    }
+}
+
+fn sort_button_label(order: SortOrder) -> &'static str {
+    match order {
+        SortOrder::Natural | SortOrder::Lexicographic => "Sort by Name",
+        SortOrder::AsAdded => "Sort as Added",
+    }
+}
+
+// Changes when a file is added to, removed from or renamed in any of the files' folders:
+// each folder's own modification time, one stat per folder
+fn listing_stamp(files: &[FileEntry]) -> u64 {
+    let folders: BTreeSet<&Path> = files.iter().filter_map(|f| f.path.parent()).collect();
+    let mut hasher = DefaultHasher::new();
+    for folder in folders {
+        (
+            folder,
+            std::fs::metadata(folder).and_then(|m| m.modified()).ok(),
+        )
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// Identifies the set of listed files regardless of their order
+fn file_set_key(files: &[FileEntry]) -> u64 {
+    let mut paths: Vec<&PathBuf> = files.iter().map(|f| &f.path).collect();
+    paths.sort();
+    let mut hasher = DefaultHasher::new();
+    paths.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Every context level of an error, outermost first, one per line
+fn error_chain(error: &anyhow::Error) -> String {
+    error
+        .chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Checks access, holds back locked files and renames the rest, telling `events` how each
+// file fares. Runs on the rename thread.
+fn run_rename(
+    previews: &[RenamePreview],
+    ordered: bool,
+    probe_fallback: bool,
+    verify: bool,
+    record: bool,
+    resolutions: &HashMap<PathBuf, ConflictPolicy>,
+    events: &mpsc::UnboundedSender<(PathBuf, RenameStage)>,
+) -> Result<RenameReport, ErrorDetails> {
+    let paths = previews.iter().map(|p| p.original_path.as_path());
+    let denied = unmodifiable_files(paths, probe_fallback);
+    if !denied.is_empty() {
+        return Err(ErrorDetails::new(&access_denied_message(&denied), denied)
+            .with_kind(ErrorKind::PermissionDenied));
+    }
+    // Files open in another program are reported as failures so the rest still go
+    let locked = locked_files(previews.iter().map(|p| p.original_path.as_path()));
+    let held: HashMap<PathBuf, String> = locked
+        .into_iter()
+        .map(|file| {
+            let reason = file.reason();
+            (file.path, reason)
+        })
+        .collect();
+    let (runnable, held_back) = hold_back(previews, &held);
+    for failed in &held_back {
+        let _ = events.unbounded_send((failed.preview.original_path.clone(), RenameStage::Failed));
+    }
+    let sizes = if verify {
+        file_sizes(&runnable)
+    } else {
+        HashMap::new()
+    };
+    let notify = |path: &Path, stage| {
+        let _ = events.unbounded_send((path.to_path_buf(), stage));
+    };
+    let mut report = validate_and_rename(&runnable, ordered, resolutions, notify).map_err(|e| {
+        let path = e.path().to_path_buf();
+        ErrorDetails::from_error(&e.into(), vec![path])
+    })?;
+    if verify {
+        verify_renames(&mut report, &runnable, &sizes);
+    }
+    if record {
+        report.unrecorded = record_original_names(&report, &runnable);
+    }
+    report.failed.extend(held_back);
+    Ok(report)
+}
+
+// Badge label, tooltip and color marking a file's stage in a rename
+fn stage_badge(stage: RenameStage) -> (&'static str, &'static str, Color) {
+    match stage {
+        RenameStage::Renaming => ("[...]", "Being renamed", COLOR_INFO),
+        RenameStage::Renamed => ("[OK]", "Renamed", COLOR_SUCCESS),
+        RenameStage::Failed => (
+            "[FAIL]",
+            "Not renamed; the failure list says why",
+            COLOR_ERROR,
+        ),
+    }
+}
+
+// Summarizes files without write access, e.g. "3 file(s) not writable: a.txt, b.txt, c.txt"
+fn access_denied_message(denied: &[PathBuf]) -> String {
+    let names: Vec<String> = denied
+        .iter()
+        .take(MAX_DENIED_LISTED)
+        .map(|path| {
+            path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().to_string(),
+            )
+        })
+        .collect();
+    let mut message = format!(
+        "{} file(s) not writable: {}",
+        denied.len(),
+        names.join(", ")
+    );
+    if denied.len() > MAX_DENIED_LISTED {
+        message.push_str(&format!(" and {} more", denied.len() - MAX_DENIED_LISTED));
+    }
+    message
+}
+
+// Post-processing shared by all modes: optional duplicate numbering, then on-disk conflicts
+fn finish_previews(previews: &mut [RenamePreview], number_duplicates: bool, fold_case: bool) {
+    if number_duplicates {
+        resolve_conflicts_with_suffix(previews);
+    }
+    mark_disk_conflicts(previews, fold_case);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_details_accumulate_across_batches() {
+        let mut shown =
+            ErrorDetails::new("a.txt: Permission denied", vec![PathBuf::from("/x/a.txt")])
+                .with_kind(ErrorKind::PermissionDenied);
+        shown.append(
+            ErrorDetails::new("a.txt: Permission denied", vec![PathBuf::from("/x/a.txt")])
+                .with_kind(ErrorKind::PermissionDenied),
+        );
+        assert_eq!(shown.kind, ErrorKind::PermissionDenied);
+        assert!(shown.suggestion().is_some());
+
+        shown.append(
+            ErrorDetails::new(
+                "Retry\nTarget exists: /x/b.txt",
+                vec![PathBuf::from("/x/b.txt")],
+            )
+            .with_kind(ErrorKind::Conflict),
+        );
+        assert_eq!(
+            shown.lines,
+            [
+                "a.txt: Permission denied",
+                "a.txt: Permission denied",
+                "Retry",
+                "Target exists: /x/b.txt"
+            ]
+        );
+        // Each path is listed once, and the mixed failures no longer share a fix
+        assert_eq!(
+            shown.paths,
+            [PathBuf::from("/x/a.txt"), PathBuf::from("/x/b.txt")]
+        );
+        assert_eq!(shown.kind, ErrorKind::Other);
+        assert!(shown.suggestion().is_none());
+    }
+
+    fn listed(names: &[&str]) -> FileRenamePlus {
+        let mut app = FileRenamePlus::from_settings(Settings::default());
+        app.files = names
+            .iter()
+            .map(|name| FileEntry {
+                path: PathBuf::from("/x").join(name),
+                name: Arc::new(name.to_string()),
+                identity: None,
+                pinned: false,
+                added: 0,
+            })
+            .collect();
+        app
+    }
+
+    fn scoped_names(app: &FileRenamePlus) -> Vec<String> {
+        app.scoped_files()
+            .iter()
+            .map(|f| f.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn selection_only_covers_every_selected_file() {
+        let mut app = listed(&["a", "b", "c", "d", "e"]);
+        app.selection_only = true;
+        app.select_file(1);
+        assert_eq!(scoped_names(&app), ["b"]);
+
+        app.modifiers = keyboard::Modifiers::COMMAND;
+        app.select_file(3);
+        assert_eq!(scoped_names(&app), ["b", "d"]);
+        // Dropping the current row leaves another selected one current
+        app.select_file(3);
+        assert_eq!(scoped_names(&app), ["b"]);
+        assert_eq!(app.selected_index, Some(1));
+
+        app.modifiers = keyboard::Modifiers::SHIFT;
+        app.select_file(4);
+        assert_eq!(scoped_names(&app), ["b", "c", "d", "e"]);
+        assert!(app.is_selected(2) && !app.is_selected(0));
+
+        app.selection_only = false;
+        assert_eq!(scoped_names(&app).len(), 5);
+
+        app.selection_only = true;
+        app.modifiers = keyboard::Modifiers::default();
+        app.select_file(0);
+        assert_eq!(scoped_names(&app), ["a"]);
+    }
+
+    #[test]
+    fn unchanged_files_reuse_the_cached_preview() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("My File.txt");
+        std::fs::write(&path, "a").unwrap();
+        let mut app = FileRenamePlus::from_settings(Settings::default());
+        app.mode = AppMode::Convention;
+        app.files.push(FileEntry {
+            path: path.clone(),
+            name: Arc::new("My File.txt".to_string()),
+            identity: None,
+            pinned: false,
+            added: 1,
+        });
+
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+        assert_eq!(app.previews.len(), 1);
+
+        // Rewritten in place, the file keeps its key without being read; a rescan or a
+        // rename clears the cache
+        std::fs::write(&path, "longer").unwrap();
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+        app.clear_file_caches();
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 2);
+    }
+
+    #[test]
+    fn numbering_that_reads_the_folder_follows_its_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "a").unwrap();
+        let mut app = FileRenamePlus::from_settings(Settings::default());
+        app.mode = AppMode::Iteration;
+        app.template = "{n}".to_string();
+        app.padding = "3".to_string();
+        app.files.push(FileEntry {
+            path,
+            name: Arc::new("a.txt".to_string()),
+            identity: None,
+            pinned: false,
+            added: 1,
+        });
+
+        let _ = app.generate_preview();
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+        assert_eq!(app.previews[0].new_name, "001.txt");
+
+        // Without skipping taken names a new file in the folder doesn't matter
+        let taken = dir.path().join("001.txt");
+        std::fs::write(&taken, "b").unwrap();
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+
+        app.skip_existing = true;
+        let _ = app.generate_preview();
+        assert_eq!(app.previews[0].new_name, "002.txt");
+        let computed = app.preview_computations;
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, computed);
+
+        std::fs::remove_file(&taken).unwrap();
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, computed + 1);
+        assert_eq!(app.previews[0].new_name, "001.txt");
+    }
+
+    #[test]
+    fn renaming_continues_numbering_from_the_shown_preview() {
+        let mut app = listed(&["a.txt", "b.txt"]);
+        app.mode = AppMode::Iteration;
+        app.template = "{n}".to_string();
+        app.start_number = "4".to_string();
+        app.continue_numbering = true;
+        // Stops at the confirmation, after the continuation is recorded
+        app.confirm_threshold = "1".to_string();
+
+        let _ = app.generate_preview();
+        assert_eq!(app.preview_computations, 1);
+        let _ = app.update(Message::ExecuteRename);
+        assert_eq!(app.large_batch_warning, Some(2));
+        assert_eq!(app.pending_continuation, Some(("{n}".to_string(), 6)));
+        assert_eq!(app.preview_computations, 1);
+    }
+}
@@ -0,0 +1,94 @@
+// Keyboard navigation and scrolling for the file and preview lists
+
+use super::focus::FocusTarget;
+use super::{FileRenamePlus, Message};
+use crate::theme::{FONT_SM, LIST_HEIGHT, SPACING_SM, SPACING_XS};
+use iced::keyboard::key::Named;
+use iced::widget::scrollable::{self, AbsoluteOffset, RelativeOffset};
+use iced::Task;
+
+// Estimated height of one file row: text line, vertical padding and list spacing
+const FILE_ROW_PITCH: f32 = FONT_SM * 1.3 + SPACING_SM as f32 * 2.0 + SPACING_XS as f32;
+
+pub fn file_list_id() -> scrollable::Id {
+    scrollable::Id::new("file_list")
+}
+
+pub fn preview_list_id() -> scrollable::Id {
+    scrollable::Id::new("preview_list")
+}
+
+// Scrolls the preview back to where a mode left it
+pub fn scroll_previews_to<T>(offset: f32) -> Task<T> {
+    scrollable::scroll_to(preview_list_id(), AbsoluteOffset { x: 0.0, y: offset })
+}
+
+// Number of file rows that fit in one page of the list
+fn page_rows() -> usize {
+    ((LIST_HEIGHT / FILE_ROW_PITCH) as usize).max(1)
+}
+
+impl FileRenamePlus {
+    // Handles list navigation keys. Arrows, Page Up/Down and Home/End act on the focused list;
+    // Ctrl+Home/End jump to the ends of the file list from anywhere outside a text input.
+    pub(super) fn navigate_list(&mut self, key: Named, control: bool) -> Option<Task<Message>> {
+        match self.focus {
+            Some(FocusTarget::FileList) => self.navigate_files(key),
+            Some(FocusTarget::PreviewList) => scroll_previews(key),
+            Some(target) if target.is_text_input() => None,
+            _ if control && matches!(key, Named::Home | Named::End) => self.navigate_files(key),
+            _ => None,
+        }
+    }
+
+    // Moves the selection and keeps it visible
+    fn navigate_files(&mut self, key: Named) -> Option<Task<Message>> {
+        let last = self.files.len().checked_sub(1)?;
+        let current = self.selected_index;
+        let index = match key {
+            Named::ArrowUp => current.map_or(0, |i| i.saturating_sub(1)),
+            Named::ArrowDown => current.map_or(0, |i| (i + 1).min(last)),
+            Named::PageUp => current.map_or(0, |i| i.saturating_sub(page_rows())),
+            Named::PageDown => current.map_or(0, |i| (i + page_rows()).min(last)),
+            Named::Home => 0,
+            Named::End => last,
+            _ => return None,
+        };
+        self.selected_index = Some(index);
+        // The keys move a single selection
+        let had_marked = !std::mem::take(&mut self.marked_files).is_empty();
+        let reveal = self.reveal_file(index);
+        if self.selection_only && (had_marked || current != Some(index)) {
+            return Some(Task::batch([reveal, self.generate_preview()]));
+        }
+        Some(reveal)
+    }
+
+    // Scrolls the file list just enough to show the row at `index`
+    pub(super) fn reveal_file(&self, index: usize) -> Task<Message> {
+        let top = index as f32 * FILE_ROW_PITCH;
+        let bottom = top + FILE_ROW_PITCH;
+        let y = if top < self.file_list_offset {
+            top
+        } else if bottom > self.file_list_offset + LIST_HEIGHT {
+            bottom - LIST_HEIGHT
+        } else {
+            return Task::none();
+        };
+        scrollable::scroll_to(file_list_id(), AbsoluteOffset { x: 0.0, y })
+    }
+}
+
+// The preview list has no selection, so keys scroll it directly
+fn scroll_previews(key: Named) -> Option<Task<Message>> {
+    let by = |y: f32| scrollable::scroll_by(preview_list_id(), AbsoluteOffset { x: 0.0, y });
+    Some(match key {
+        Named::ArrowUp => by(-FILE_ROW_PITCH),
+        Named::ArrowDown => by(FILE_ROW_PITCH),
+        Named::PageUp => by(-LIST_HEIGHT),
+        Named::PageDown => by(LIST_HEIGHT),
+        Named::Home => scrollable::snap_to(preview_list_id(), RelativeOffset::START),
+        Named::End => scrollable::snap_to(preview_list_id(), RelativeOffset::END),
+        _ => return None,
+    })
+}
@@ -0,0 +1,162 @@
+// Keyboard shortcut dispatch: which action a key press triggers, given whether a text
+// input has caret focus
+
+use iced::advanced::widget::operate;
+use iced::advanced::widget::operation::focusable::find_focused;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use iced::Task;
+
+/// Action bound to a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    ExecuteRename,
+    AddFolder,
+    RemoveFile,
+    ClearFiles,
+    CycleFocus { forward: bool },
+    // Escape closes the open row menu
+    Dismiss,
+    // Enter / Space on the focused control
+    Activate,
+    // Arrows, paging and Home/End on the focused list
+    Navigate { key: Named, control: bool },
+}
+
+// Maps a key press to its shortcut. Ctrl+Enter, Ctrl+O, Ctrl+Shift+X, Tab and Escape work
+// everywhere, none of them types anything; list and control keys are left to a text input while typing.
+pub fn shortcut(key: &Key, modifiers: Modifiers, typing: bool) -> Option<Shortcut> {
+    match key.as_ref() {
+        Key::Named(Named::Enter) if modifiers.control() => Some(Shortcut::ExecuteRename),
+        Key::Character("o") if modifiers.control() => Some(Shortcut::AddFolder),
+        // Shift makes the character upper case on most layouts
+        Key::Character(c)
+            if modifiers.control() && modifiers.shift() && c.eq_ignore_ascii_case("x") =>
+        {
+            Some(Shortcut::ClearFiles)
+        }
+        Key::Named(Named::Tab) => Some(Shortcut::CycleFocus {
+            forward: !modifiers.shift(),
+        }),
+        Key::Named(Named::Escape) => Some(Shortcut::Dismiss),
+        _ if typing => None,
+        Key::Named(Named::Delete) => Some(Shortcut::RemoveFile),
+        Key::Named(Named::Enter | Named::Space) => Some(Shortcut::Activate),
+        Key::Named(key) => Some(Shortcut::Navigate {
+            key,
+            control: modifiers.control(),
+        }),
+        _ => None,
+    }
+}
+
+// Reports whether any text input has caret focus, however it got there (Tab or a click).
+// Text inputs are the only focusable widgets, so any focused widget means typing.
+pub fn text_input_focused() -> Task<bool> {
+    operate(find_focused())
+        .collect()
+        .map(|focused| !focused.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(key: Named) -> Key {
+        Key::Named(key)
+    }
+
+    fn character(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    #[test]
+    fn global_bindings_fire_while_typing() {
+        for typing in [false, true] {
+            assert_eq!(
+                shortcut(&named(Named::Enter), Modifiers::CTRL, typing),
+                Some(Shortcut::ExecuteRename)
+            );
+            assert_eq!(
+                shortcut(&character("o"), Modifiers::CTRL, typing),
+                Some(Shortcut::AddFolder)
+            );
+            assert_eq!(
+                shortcut(&character("X"), Modifiers::CTRL | Modifiers::SHIFT, typing),
+                Some(Shortcut::ClearFiles)
+            );
+            assert_eq!(
+                shortcut(&named(Named::Tab), Modifiers::empty(), typing),
+                Some(Shortcut::CycleFocus { forward: true })
+            );
+            assert_eq!(
+                shortcut(&named(Named::Tab), Modifiers::SHIFT, typing),
+                Some(Shortcut::CycleFocus { forward: false })
+            );
+            assert_eq!(
+                shortcut(&named(Named::Escape), Modifiers::empty(), typing),
+                Some(Shortcut::Dismiss)
+            );
+        }
+    }
+
+    #[test]
+    fn clear_accepts_either_case_but_needs_both_modifiers() {
+        let both = Modifiers::CTRL | Modifiers::SHIFT;
+        assert_eq!(
+            shortcut(&character("x"), both, false),
+            Some(Shortcut::ClearFiles)
+        );
+        assert_eq!(shortcut(&character("x"), Modifiers::CTRL, false), None);
+        assert_eq!(shortcut(&character("X"), Modifiers::SHIFT, false), None);
+    }
+
+    #[test]
+    fn plain_keys_need_their_modifier() {
+        assert_eq!(shortcut(&character("o"), Modifiers::empty(), false), None);
+        assert_eq!(
+            shortcut(&named(Named::Enter), Modifiers::empty(), false),
+            Some(Shortcut::Activate)
+        );
+    }
+
+    #[test]
+    fn list_keys_act_on_the_focused_control() {
+        assert_eq!(
+            shortcut(&named(Named::Delete), Modifiers::empty(), false),
+            Some(Shortcut::RemoveFile)
+        );
+        assert_eq!(
+            shortcut(&named(Named::Space), Modifiers::empty(), false),
+            Some(Shortcut::Activate)
+        );
+        assert_eq!(
+            shortcut(&named(Named::ArrowDown), Modifiers::empty(), false),
+            Some(Shortcut::Navigate {
+                key: Named::ArrowDown,
+                control: false
+            })
+        );
+        assert_eq!(
+            shortcut(&named(Named::Home), Modifiers::CTRL, false),
+            Some(Shortcut::Navigate {
+                key: Named::Home,
+                control: true
+            })
+        );
+    }
+
+    #[test]
+    fn typing_leaves_list_keys_to_the_text_input() {
+        for key in [
+            Named::Delete,
+            Named::Enter,
+            Named::Space,
+            Named::ArrowDown,
+            Named::Home,
+        ] {
+            assert_eq!(shortcut(&named(key), Modifiers::empty(), true), None);
+        }
+        assert_eq!(shortcut(&character("a"), Modifiers::empty(), false), None);
+    }
+}
@@ -1,110 +1,992 @@
-// File operations: directory scanning and atomic renaming
+// File operations: directory scanning, atomic renaming and script export
 
-use crate::types::{FileEntry, RenamePreview};
+use crate::theme::MAX_FILES;
+use crate::types::{
+    ConflictPolicy, ConflictReason, ErrorKind, ExtensionGroup, ExtensionTemplate, FileEntry,
+    FileIdentity, RenamePreview, ScriptFormat, SortOrder, DEFAULT_EXTENSION_GROUPS,
+};
 use anyhow::{Context, Result};
-use std::collections::HashSet;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-// Scans directory and returns files sorted naturally (like File Explorer)
-pub fn scan_directory(path: &str) -> Result<Vec<FileEntry>> {
+// Prefix for intermediate names used by the two-phase rename. A process id and random
+// tag follow it at runtime; generated names may not start with it at all.
+const TEMP_PREFIX: &str = ".rename_temp_";
+
+// Per-folder record of "new name<TAB>original name" lines, for renamed files whose
+// filesystem can't hold the extended attribute. Never listed by a scan.
+pub const ORIGIN_SIDECAR: &str = ".frp-origin";
+
+// Extended attribute holding a renamed file's original name
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const ORIGIN_ATTRIBUTE: &[u8] = b"user.frp.origin\0";
+
+// Win32 MAX_PATH; longer paths need the extended-length prefix
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+/// Files found by a folder scan, plus how many were left out by the limits
+#[derive(Debug, Clone)]
+pub struct FolderScan {
+    pub files: Vec<FileEntry>,
+    pub skipped: usize,
+    // The walk stopped at MAX_FILES without reading the rest of the folder, so `skipped`
+    // counts only files it saw
+    pub stopped_early: bool,
+}
+
+/// Why a folder couldn't be scanned
+#[derive(Debug, thiserror::Error)]
+pub enum ScanError {
+    #[error("Path does not exist: {}", .0.display())]
+    NotFound(PathBuf),
+    #[error("Failed to read: {}", path.display())]
+    Unreadable {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Why a batch was refused before any file was renamed
+#[derive(Debug, thiserror::Error)]
+pub enum RenameError {
+    #[error("{reason}: {}", path.display())]
+    InvalidName { path: PathBuf, reason: String },
+    #[error("Reserved temporary name: {}", .0.display())]
+    ReservedName(PathBuf),
+    #[error("Target exists: {}", .0.display())]
+    TargetExists(PathBuf),
+    #[error("Duplicate target: {}", .0.display())]
+    DuplicateTarget(PathBuf),
+}
+
+impl RenameError {
+    // File the error is about: the file being renamed, or the target it would take
+    pub fn path(&self) -> &Path {
+        match self {
+            RenameError::InvalidName { path, .. }
+            | RenameError::ReservedName(path)
+            | RenameError::TargetExists(path)
+            | RenameError::DuplicateTarget(path) => path,
+        }
+    }
+}
+
+// Kind of the first error in the chain that says what went wrong: a rename refusal, a
+// missing folder or an I/O error the system classified. Anything else is Other.
+pub fn error_kind(error: &anyhow::Error) -> ErrorKind {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<RenameError>() {
+            return match e {
+                RenameError::InvalidName { .. } | RenameError::ReservedName(_) => {
+                    ErrorKind::InvalidName
+                }
+                RenameError::TargetExists(_) | RenameError::DuplicateTarget(_) => {
+                    ErrorKind::Conflict
+                }
+            };
+        }
+        if let Some(ScanError::NotFound(_)) = cause.downcast_ref::<ScanError>() {
+            return ErrorKind::NotFound;
+        }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            match e.kind() {
+                io::ErrorKind::PermissionDenied => return ErrorKind::PermissionDenied,
+                io::ErrorKind::NotFound => return ErrorKind::NotFound,
+                io::ErrorKind::AlreadyExists => return ErrorKind::Conflict,
+                _ => {}
+            }
+        }
+    }
+    ErrorKind::Other
+}
+
+// Scans directory and returns files in the given order; "as added" keeps the order the
+// directory listed them in. Only the first `per_dir_limit` files in that order are kept,
+// and never more than MAX_FILES. The walk itself stops after MAX_FILES files, so a huge
+// folder can't fill memory; the order then covers the files read before stopping.
+pub fn scan_directory(
+    path: &str,
+    per_dir_limit: Option<usize>,
+    order: SortOrder,
+) -> Result<FolderScan, ScanError> {
     let path = Path::new(path);
 
     if !path.exists() {
-        anyhow::bail!("Path does not exist: {}", path.display());
+        return Err(ScanError::NotFound(path.to_path_buf()));
     }
 
     if !path.is_dir() {
-        let name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default();
-        return Ok(vec![FileEntry {
-            path: path.to_path_buf(),
-            name: Arc::new(name),
-        }]);
+        return Ok(FolderScan {
+            files: vec![file_entry(path.to_path_buf())],
+            skipped: 0,
+            stopped_early: false,
+        });
     }
 
-    let entries =
-        fs::read_dir(path).with_context(|| format!("Failed to read: {}", path.display()))?;
+    let unreadable = |source| ScanError::Unreadable {
+        path: path.to_path_buf(),
+        source,
+    };
+    let entries = fs::read_dir(path).map_err(unreadable)?;
     let mut files = Vec::new();
+    let mut stopped_early = false;
 
     for entry in entries {
-        let entry = entry.with_context(|| "Failed to read entry")?;
+        let entry = entry.map_err(unreadable)?;
         let file_path = entry.path();
 
-        if file_path.is_dir() {
+        if file_path.is_dir() || entry.file_name() == ORIGIN_SIDECAR {
             continue;
         }
+        if files.len() == MAX_FILES {
+            stopped_early = true;
+            break;
+        }
 
-        let name = file_path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default();
-        files.push(FileEntry {
-            path: file_path,
-            name: Arc::new(name),
-        });
+        files.push(file_entry(file_path));
     }
 
-    files.sort_by(|a, b| natural_cmp(&a.name, &b.name));
-    Ok(files)
+    sort_files(&mut files, order);
+    let limit = per_dir_limit.map_or(MAX_FILES, |l| l.min(MAX_FILES));
+    let skipped = files.len().saturating_sub(limit);
+    files.truncate(limit);
+    Ok(FolderScan {
+        files,
+        skipped,
+        stopped_early,
+    })
 }
 
-// Natural sort: compares numbers numerically within strings
-fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
-    let mut a_chars = a.chars().peekable();
-    let mut b_chars = b.chars().peekable();
+/// Files read from a path list, plus the lines that couldn't be used
+#[derive(Debug, Clone)]
+pub struct PathListImport {
+    pub files: Vec<FileEntry>,
+    pub invalid_lines: Vec<String>,
+}
 
-    loop {
-        match (a_chars.peek(), b_chars.peek()) {
-            (None, None) => return std::cmp::Ordering::Equal,
-            (None, Some(_)) => return std::cmp::Ordering::Less,
-            (Some(_), None) => return std::cmp::Ordering::Greater,
-            (Some(&ac), Some(&bc)) => {
-                if ac.is_ascii_digit() && bc.is_ascii_digit() {
-                    let a_num = extract_number(&mut a_chars);
-                    let b_num = extract_number(&mut b_chars);
-                    match a_num.cmp(&b_num) {
-                        std::cmp::Ordering::Equal => continue,
+// Reads one path per line; relative paths are resolved against the list's folder.
+// Duplicates are dropped and the result is sorted in `order`; "as added" keeps the lines' order.
+pub fn import_path_list(list: &Path, order: SortOrder) -> Result<PathListImport> {
+    let content =
+        fs::read_to_string(list).with_context(|| format!("Failed to read: {}", list.display()))?;
+    let base = list.parent().unwrap_or(Path::new(""));
+
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    let mut invalid_lines = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if files.len() >= MAX_FILES {
+            invalid_lines.push(format!(
+                "line {}: over the {} file limit",
+                number + 1,
+                MAX_FILES
+            ));
+            break;
+        }
+        let path = base.join(line);
+        if !path.exists() {
+            invalid_lines.push(format!("line {}: not found: {}", number + 1, line));
+        } else if !path.is_file() {
+            invalid_lines.push(format!("line {}: not a file: {}", number + 1, line));
+        } else if seen.insert(path.clone()) {
+            files.push(file_entry(path));
+        }
+    }
+
+    sort_files(&mut files, order);
+    Ok(PathListImport {
+        files,
+        invalid_lines,
+    })
+}
+
+// Splits a filter like "jpg, .PNG gif" into lowercase extensions without dots
+pub fn parse_extension_list(list: &str) -> Vec<String> {
+    list.split(|c: char| c == ',' || c.is_whitespace())
+        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
+// Parses groups written as "Images: jpg png; Video: mp4 mkv". Entries without a name or
+// extensions are ignored; blank text gives the built-in groups.
+pub fn parse_extension_groups(text: &str) -> Vec<ExtensionGroup> {
+    if text.trim().is_empty() {
+        return DEFAULT_EXTENSION_GROUPS
+            .iter()
+            .map(|(name, extensions)| ExtensionGroup {
+                name: name.to_string(),
+                extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            })
+            .collect();
+    }
+    text.split(';')
+        .filter_map(|entry| {
+            let (name, list) = entry.split_once(':')?;
+            let name = name.trim();
+            let extensions = parse_extension_list(list);
+            (!name.is_empty() && !extensions.is_empty()).then(|| ExtensionGroup {
+                name: name.to_string(),
+                extensions,
+            })
+        })
+        .collect()
+}
+
+// Parses extension templates written as "jpg png: image_{n}; pdf: doc_{n}". Entries
+// without extensions or a template are ignored.
+pub fn parse_extension_templates(text: &str) -> Vec<ExtensionTemplate> {
+    text.split(';')
+        .filter_map(|entry| {
+            let (list, template) = entry.split_once(':')?;
+            let extensions = parse_extension_list(list);
+            let template = template.trim();
+            (!extensions.is_empty() && !template.is_empty()).then(|| ExtensionTemplate {
+                extensions,
+                template: template.to_string(),
+            })
+        })
+        .collect()
+}
+
+// True when the file's extension is in the list; an empty list lets every file through
+pub fn matches_extensions(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| extensions.contains(&ext))
+}
+
+// Builds a list entry, recording its identity when it has other hard links
+fn file_entry(path: PathBuf) -> FileEntry {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let identity = hard_link_identity(&path);
+    FileEntry {
+        path,
+        name: Arc::new(name),
+        identity,
+        pinned: false,
+        added: 0,
+    }
+}
+
+// Entry for a listed file that now lives at `path`, keeping its pin and list position
+pub fn relocated_entry(entry: &FileEntry, path: PathBuf) -> FileEntry {
+    FileEntry {
+        pinned: entry.pinned,
+        added: entry.added,
+        ..file_entry(path)
+    }
+}
+
+// Returns device + inode when the file has more than one link (Unix)
+#[cfg(unix)]
+fn hard_link_identity(path: &Path) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    (metadata.nlink() > 1).then(|| FileIdentity {
+        device: metadata.dev(),
+        index: metadata.ino(),
+    })
+}
+
+// Returns volume serial + file index when the file has more than one link (Windows)
+#[cfg(windows)]
+fn hard_link_identity(path: &Path) -> Option<FileIdentity> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+
+    // Metadata-only access so files without read permission are still inspected
+    let file = fs::OpenOptions::new().access_mode(0).open(path).ok()?;
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    // SAFETY: the handle stays valid while `file` is alive and `info` is a valid out pointer
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info) }.ok()?;
+    (info.nNumberOfLinks > 1).then(|| FileIdentity {
+        device: u64::from(info.dwVolumeSerialNumber),
+        index: (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow),
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn hard_link_identity(_path: &Path) -> Option<FileIdentity> {
+    None
+}
+
+// First folder among the paths that lies on a network share (SMB, NFS and the like).
+// Renames there go through the server, which may apply them late, fold letter case
+// differently, or not make them atomic.
+pub fn network_folder<'a>(paths: impl Iterator<Item = &'a Path>) -> Option<PathBuf> {
+    let folders: BTreeSet<&Path> = paths.filter_map(Path::parent).collect();
+    folders
+        .into_iter()
+        .find(|folder| network_filesystem(folder))
+        .map(Path::to_path_buf)
+}
+
+// UNC paths (\\server\share) and drive letters mapped to a share
+#[cfg(windows)]
+fn network_filesystem(folder: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    const DRIVE_REMOTE: u32 = 4;
+
+    match folder.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::UNC(..) | Prefix::VerbatimUNC(..) => true,
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                let root: Vec<u16> = std::ffi::OsStr::new(&format!("{}:\\", letter as char))
+                    .encode_wide()
+                    .chain([0])
+                    .collect();
+                // SAFETY: `root` is a NUL-terminated wide string that outlives the call
+                unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) == DRIVE_REMOTE }
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// Filesystem type reported by statfs: NFS, SMB/CIFS, AFS, Coda, NCP, Ceph or 9P (which
+// also serves Windows drives to WSL)
+#[cfg(target_os = "linux")]
+fn network_filesystem(folder: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    const NETWORK_MAGIC: [u32; 9] = [
+        0x6969,      // NFS
+        0x517B,      // SMB
+        0xFF53_4D42, // CIFS
+        0xFE53_4D42, // SMB2
+        0x5346_414F, // AFS
+        0x7375_7245, // Coda
+        0x564C,      // NCP
+        0x00C3_6400, // Ceph
+        0x0102_1997, // 9P
+    ];
+    let Ok(path) = std::ffi::CString::new(folder.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` a valid out pointer
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    // f_type is signed on some targets; the magic numbers are its low 32 bits
+    NETWORK_MAGIC.contains(&(stat.f_type as u32))
+}
+
+// Filesystem type name reported by statfs
+#[cfg(target_os = "macos")]
+fn network_filesystem(folder: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    const NETWORK_TYPES: [&str; 5] = ["smbfs", "nfs", "afpfs", "webdav", "cifs"];
+    let Ok(path) = std::ffi::CString::new(folder.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` a valid out pointer
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    // SAFETY: statfs fills f_fstypename with a NUL-terminated name
+    let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+    NETWORK_TYPES.contains(&name.to_string_lossy().as_ref())
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn network_filesystem(_folder: &Path) -> bool {
+    false
+}
+
+// Maps each list index to the other entries sharing its file identity
+pub fn hard_link_peers(files: &[FileEntry]) -> HashMap<usize, Vec<usize>> {
+    let mut groups: HashMap<FileIdentity, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        if let Some(identity) = file.identity {
+            groups.entry(identity).or_default().push(i);
+        }
+    }
+
+    let mut peers = HashMap::new();
+    for members in groups.into_values().filter(|m| m.len() > 1) {
+        for &i in &members {
+            peers.insert(i, members.iter().copied().filter(|&j| j != i).collect());
+        }
+    }
+    peers
+}
+
+// Lists paths already present next to the given files, excluding the files themselves.
+// With `fold_case`, names are lowercased so numbering can skip names that differ only in
+// letter case.
+pub fn existing_paths(files: &[FileEntry], fold_case: bool) -> HashSet<PathBuf> {
+    let batch: HashSet<&Path> = files.iter().map(|f| f.path.as_path()).collect();
+    let dirs: HashSet<&Path> = files.iter().filter_map(|f| f.path.parent()).collect();
+
+    let mut existing = HashSet::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if batch.contains(path.as_path()) {
+                continue;
+            }
+            if fold_case {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                existing.insert(dir.join(name));
+            } else {
+                existing.insert(path);
+            }
+        }
+    }
+    existing
+}
+
+// Finds the digit width most names in `dir` use where the template's {n} goes, e.g. 4 for
+// a folder of photo_0001.jpg... with template "photo_{n}". Ties go to the wider width.
+// Numbered counters such as {n1} match any number.
+pub fn detect_existing_number_width(dir: &Path, template: &str) -> Option<usize> {
+    if !template.contains("{n}") {
+        return None;
+    }
+    // Counters and the group number stand for numbers of any width
+    let counter = regex::Regex::new(r"\\\{(?:n\d+|g(?::\d+)?)\\\}").ok()?;
+    let stem = template
+        .split("{n}")
+        .map(|part| {
+            counter
+                .replace_all(&regex::escape(part), r"-?\d+")
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(r"(\d+)");
+    let pattern = regex::Regex::new(&format!(r"(?i)^{}(?:\.[^.]+)*$", stem)).ok()?;
+
+    let mut widths: HashMap<usize, usize> = HashMap::new();
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let name = entry.file_name();
+        let Some(digits) = name
+            .to_str()
+            .and_then(|name| pattern.captures(name))
+            .and_then(|caps| caps.get(1))
+        else {
+            continue;
+        };
+        *widths.entry(digits.as_str().len()).or_insert(0) += 1;
+    }
+    widths
+        .into_iter()
+        .max_by_key(|&(width, count)| (count, width))
+        .map(|(width, _)| width)
+}
+
+// Applies a reordering to the whole list, then puts pinned entries back at their previous
+// positions; the other entries fill the remaining slots in their new relative order
+pub fn reorder_around_pins(files: &mut Vec<FileEntry>, reorder: impl FnOnce(&mut Vec<FileEntry>)) {
+    let slots: Vec<(usize, PathBuf)> = files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.pinned)
+        .map(|(i, f)| (i, f.path.clone()))
+        .collect();
+    reorder(files);
+    if slots.is_empty() {
+        return;
+    }
+    let mut pinned: HashMap<PathBuf, FileEntry> = HashMap::with_capacity(slots.len());
+    files.retain(|f| {
+        if f.pinned {
+            pinned.insert(f.path.clone(), f.clone());
+        }
+        !f.pinned
+    });
+    for (index, path) in slots {
+        if let Some(entry) = pinned.remove(&path) {
+            files.insert(index.min(files.len()), entry);
+        }
+    }
+}
+
+// Sorts entries in the given order. Entries not yet listed all count as added together,
+// so "as added" leaves them as they are.
+pub fn sort_files(files: &mut [FileEntry], order: SortOrder) {
+    match order {
+        SortOrder::Natural => sort_naturally(files),
+        SortOrder::Lexicographic => files.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortOrder::AsAdded => files.sort_by_key(|f| f.added),
+    }
+}
+
+// Sorts entries naturally (like File Explorer), computing each name's key once
+fn sort_naturally(files: &mut [FileEntry]) {
+    files.sort_by_cached_key(|f| NaturalKey::new(&f.name));
+}
+
+// Precomputed natural sort key: alternating runs of lowercased text and numbers.
+// Orders exactly like walking both names character by character, comparing digit
+// runs numerically and everything else by ASCII-lowercased code point.
+#[derive(PartialEq, Eq)]
+struct NaturalKey(Vec<Segment>);
+
+#[derive(PartialEq, Eq)]
+enum Segment {
+    Text(Box<str>),
+    Number(u64),
+}
+
+impl NaturalKey {
+    fn new(name: &str) -> Self {
+        let bytes = name.as_bytes();
+        let mut segments = Vec::new();
+        let mut start = 0;
+        while start < bytes.len() {
+            let digits = bytes[start].is_ascii_digit();
+            // Digits are ASCII, so a change in digit-ness is always a char boundary
+            let end = bytes[start..]
+                .iter()
+                .position(|b| b.is_ascii_digit() != digits)
+                .map_or(bytes.len(), |n| start + n);
+            let run = &name[start..end];
+            segments.push(if digits {
+                Segment::Number(run.bytes().fold(0u64, |n, d| {
+                    n.saturating_mul(10).saturating_add(u64::from(d - b'0'))
+                }))
+            } else {
+                Segment::Text(run.to_ascii_lowercase().into_boxed_str())
+            });
+            start = end;
+        }
+        NaturalKey(segments)
+    }
+}
+
+impl Ord for NaturalKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut a = self.0.iter();
+        let mut b = other.0.iter();
+        loop {
+            let ordering = match (a.next(), b.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(Segment::Number(x)), Some(Segment::Number(y))) => x.cmp(y),
+                // A digit against text compares as characters; any digit will do
+                (Some(Segment::Number(_)), Some(Segment::Text(y))) => b'0'.cmp(&y.as_bytes()[0]),
+                (Some(Segment::Text(x)), Some(Segment::Number(_))) => x.as_bytes()[0].cmp(&b'0'),
+                (Some(Segment::Text(x)), Some(Segment::Text(y))) => {
+                    // Byte order of UTF-8 matches code point order
+                    let (x, y) = (x.as_bytes(), y.as_bytes());
+                    let common = x.len().min(y.len());
+                    match x[..common].cmp(&y[..common]) {
+                        Ordering::Equal => {}
                         other => return other,
                     }
-                }
-                let ac_lower = ac.to_ascii_lowercase();
-                let bc_lower = bc.to_ascii_lowercase();
-                match ac_lower.cmp(&bc_lower) {
-                    std::cmp::Ordering::Equal => {
-                        a_chars.next();
-                        b_chars.next();
+                    // When one run is a prefix of the other, the longer run's next
+                    // character meets the other name's following digit or its end
+                    match x.len().cmp(&y.len()) {
+                        Ordering::Equal => Ordering::Equal,
+                        Ordering::Greater => {
+                            return b.next().map_or(Ordering::Greater, |_| x[common].cmp(&b'0'))
+                        }
+                        Ordering::Less => {
+                            return a.next().map_or(Ordering::Less, |_| b'0'.cmp(&y[common]))
+                        }
                     }
-                    other => return other,
                 }
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
             }
         }
     }
 }
 
-// Extracts consecutive digits as a number
-fn extract_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> u64 {
-    let mut num: u64 = 0;
-    while let Some(&c) = chars.peek() {
-        if c.is_ascii_digit() {
-            num = num
-                .saturating_mul(10)
-                .saturating_add(u64::from(c.to_digit(10).unwrap_or(0)));
-            chars.next();
+impl PartialOrd for NaturalKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A folder as it would be listed once the batch is renamed
+#[derive(Debug, Clone)]
+pub struct FolderListing {
+    pub folder: PathBuf,
+    pub entries: Vec<ListedName>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ListedName {
+    pub name: String,
+    // The name it has now, for entries the batch renames
+    pub renamed_from: Option<Arc<String>>,
+}
+
+// SHA-256 of the file's contents as lowercase hex, read in chunks so large files don't
+// have to fit in memory
+pub fn content_hash(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(long_path(path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => hasher.update(&buffer[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Hash of every entry's name, size and modified time in the folders, so a file being
+// written or added changes it. Folders that can't be read count as empty.
+pub fn folder_fingerprint(folders: &[PathBuf]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for folder in folders {
+        let mut entries: Vec<(String, u64, Option<std::time::SystemTime>)> =
+            fs::read_dir(long_path(folder))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|e| {
+                            let metadata = e.metadata().ok();
+                            (
+                                e.file_name().to_string_lossy().into_owned(),
+                                metadata.as_ref().map_or(0, |m| m.len()),
+                                metadata.and_then(|m| m.modified().ok()),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+        entries.sort();
+        (folder, entries).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// Opens the file with the system's default program for its type, or a folder in the file
+// manager
+pub fn open_path(path: &Path) -> Result<()> {
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(path)
+        .spawn()
+        .with_context(|| format!("Could not run {} to open {}", opener, path.display()))?;
+    Ok(())
+}
+
+// Shows the file in the system file manager: selected where the file manager can do
+// that, otherwise by opening the folder holding it
+pub fn reveal_in_file_manager(path: &Path) -> Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = std::process::Command::new("explorer");
+        // Explorer wants the flag and path as one argument
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        command.arg(select);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(path));
+        command
+    };
+    command
+        .spawn()
+        .with_context(|| format!("Could not show {} in the file manager", path.display()))?;
+    Ok(())
+}
+
+// Names currently in each folder holding one of the given files
+pub fn folder_contents<'a>(paths: impl Iterator<Item = &'a Path>) -> HashMap<PathBuf, Vec<String>> {
+    let dirs: HashSet<&Path> = paths.filter_map(Path::parent).collect();
+    dirs.into_iter()
+        .map(|dir| {
+            let names = fs::read_dir(long_path(dir))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|e| e.file_name().to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            (dir.to_path_buf(), names)
+        })
+        .collect()
+}
+
+// Each folder's names from `contents` with the previewed files under their new names, in
+// natural order. Files no longer on disk are left out; a name taken twice shows twice.
+pub fn resulting_listing(
+    previews: &[RenamePreview],
+    contents: &HashMap<PathBuf, Vec<String>>,
+) -> Vec<FolderListing> {
+    let mut renamed: BTreeMap<&Path, HashMap<&str, &RenamePreview>> = BTreeMap::new();
+    for preview in previews {
+        if let Some(dir) = preview.original_path.parent() {
+            renamed
+                .entry(dir)
+                .or_default()
+                .insert(preview.original_name.as_str(), preview);
+        }
+    }
+    renamed
+        .into_iter()
+        .filter_map(|(dir, previews)| {
+            let names = contents.get(dir)?;
+            let mut entries: Vec<ListedName> = names
+                .iter()
+                .map(|name| match previews.get(name.as_str()) {
+                    Some(p) if p.new_name != *p.original_name => ListedName {
+                        name: p.new_name.clone(),
+                        renamed_from: Some(Arc::clone(&p.original_name)),
+                    },
+                    _ => ListedName {
+                        name: name.clone(),
+                        renamed_from: None,
+                    },
+                })
+                .collect();
+            entries.sort_by_cached_key(|e| NaturalKey::new(&e.name));
+            Some(FolderListing {
+                folder: dir.to_path_buf(),
+                entries,
+            })
+        })
+        .collect()
+}
+
+// Whether file names are compared ignoring letter case by default on this OS. Windows and
+// macOS filesystems are case-insensitive unless set up otherwise; Linux ones aren't.
+pub const CASE_INSENSITIVE_OS: bool = cfg!(any(windows, target_os = "macos"));
+
+// Flags previews whose target already exists on disk and isn't renamed away in this batch.
+// With `fold_case`, a file whose name differs only in letter case is flagged as a case
+// collision; a case-only rename of the file itself is not. Earlier on-disk flags are
+// re-checked, so files removed since the last check clear.
+pub fn mark_disk_conflicts(previews: &mut [RenamePreview], fold_case: bool) {
+    let original_paths: HashSet<PathBuf> =
+        previews.iter().map(|p| p.original_path.clone()).collect();
+    // Each folder's names, exact and lowercased, read once for the case check
+    let mut listings: HashMap<PathBuf, Option<FolderNames>> = HashMap::new();
+    for preview in previews {
+        let dir = preview
+            .original_path
+            .parent()
+            .unwrap_or(&preview.original_path);
+        let target_path = dir.join(&preview.new_name);
+        preview
+            .conflicts
+            .retain(|&r| r != ConflictReason::ExistsOnDisk && r != ConflictReason::CaseCollision);
+        if original_paths.contains(&target_path) {
+            continue;
+        }
+        let listing = if fold_case {
+            listings
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| folder_names(dir))
+                .as_ref()
         } else {
+            None
+        };
+        let Some((exact, folded)) = listing else {
+            if long_path(&target_path).exists() {
+                preview.conflicts.push(ConflictReason::ExistsOnDisk);
+            }
+            continue;
+        };
+        if exact.contains(&preview.new_name) {
+            preview.conflicts.push(ConflictReason::ExistsOnDisk);
+        } else if folded
+            .get(&preview.new_name.to_lowercase())
+            .is_some_and(|names| {
+                names
+                    .iter()
+                    .any(|name| !original_paths.contains(&dir.join(name)))
+            })
+        {
+            preview.conflicts.push(ConflictReason::CaseCollision);
+        }
+    }
+}
+
+// A folder's names, and the same grouped by their lowercase form
+type FolderNames = (HashSet<String>, HashMap<String, Vec<String>>);
+
+// None if the folder can't be read
+fn folder_names(dir: &Path) -> Option<FolderNames> {
+    let names: HashSet<String> = fs::read_dir(long_path(dir))
+        .ok()?
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    let mut folded: HashMap<String, Vec<String>> = HashMap::new();
+    for name in &names {
+        folded
+            .entry(name.to_lowercase())
+            .or_default()
+            .push(name.clone());
+    }
+    Some((names, folded))
+}
+
+/// How far a file has got in a running rename, reported as each step happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameStage {
+    // Its first filesystem step is under way
+    Renaming,
+    Renamed,
+    // Abandoned; the report says why and where the file is now
+    Failed,
+}
+
+/// Outcome of a rename run that kept going past individual failures
+#[derive(Debug, Clone, Default)]
+pub struct RenameReport {
+    pub renamed: usize,
+    pub failed: Vec<FailedRename>,
+    // Where every file in the batch ended up: its original path and its path now
+    pub locations: Vec<(PathBuf, PathBuf)>,
+    // Existing files moved to the recycle bin to free their name
+    pub trashed: Vec<PathBuf>,
+    // Existing files replaced by a renamed file
+    pub overwritten: Vec<PathBuf>,
+    // Files left untouched because their new name was taken
+    pub skipped: Vec<PathBuf>,
+    // Renames that went through but didn't check out on disk afterwards
+    pub verify_failed: Vec<VerifyFailure>,
+    // Renamed files whose original name was asked for but couldn't be recorded
+    pub unrecorded: Vec<PathBuf>,
+}
+
+/// A completed rename whose result doesn't match what was asked for
+#[derive(Debug, Clone)]
+pub struct VerifyFailure {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A file that didn't reach its new name. `preview` starts from wherever the file is now
+/// (normally its original path), so it can be retried as-is.
+#[derive(Debug, Clone)]
+pub struct FailedRename {
+    pub preview: RenamePreview,
+    pub error: String,
+}
+
+// Splits off the previews that can't run this time: the held files themselves, failed with
+// the given reason, and any preview whose target is a held file's current name (repeatedly,
+// so chains waiting on a held file are held too). Returns the runnable rest and the held ones.
+pub fn hold_back(
+    previews: &[RenamePreview],
+    held: &HashMap<PathBuf, String>,
+) -> (Vec<RenamePreview>, Vec<FailedRename>) {
+    let mut reasons: HashMap<&Path, String> = held
+        .iter()
+        .map(|(path, reason)| (path.as_path(), reason.clone()))
+        .collect();
+    loop {
+        let blocked: Vec<(&Path, String)> = previews
+            .iter()
+            .filter(|p| !reasons.contains_key(p.original_path.as_path()))
+            .filter_map(|p| {
+                let target = p.original_path.parent()?.join(&p.new_name);
+                reasons.contains_key(target.as_path()).then(|| {
+                    (
+                        p.original_path.as_path(),
+                        format!(
+                            "Target name is still taken by {}, which could not be renamed",
+                            p.new_name
+                        ),
+                    )
+                })
+            })
+            .collect();
+        if blocked.is_empty() {
             break;
         }
+        reasons.extend(blocked);
     }
-    num
+
+    let (failed, runnable): (Vec<&RenamePreview>, Vec<&RenamePreview>) = previews
+        .iter()
+        .partition(|p| reasons.contains_key(p.original_path.as_path()));
+    let failed = failed
+        .into_iter()
+        .map(|p| FailedRename {
+            preview: p.clone(),
+            error: reasons[p.original_path.as_path()].clone(),
+        })
+        .collect();
+    (runnable.into_iter().cloned().collect(), failed)
 }
 
-// Executes renames atomically using two-phase temporary rename
-pub fn validate_and_rename(previews: &[RenamePreview]) -> Result<usize> {
+// Existing files that a preview's new name would collide with, other than files in the batch
+// itself (a case-only rename doesn't collide with its own file)
+pub fn occupied_targets(previews: &[RenamePreview]) -> Vec<PathBuf> {
+    let sources: HashSet<&Path> = previews.iter().map(|p| p.original_path.as_path()).collect();
+    previews
+        .iter()
+        .filter_map(|p| {
+            let target = p.original_path.parent()?.join(&p.new_name);
+            (!sources.contains(target.as_path()) && is_occupied(&p.original_path, &target))
+                .then_some(target)
+        })
+        .collect()
+}
+
+// Executes renames using two-phase temporary rename, or in dependency order with temporary
+// names only for cycles when `ordered` is set. A file whose rename fails is skipped and moved
+// back to its original name where possible; the rest of the batch still goes through.
+// An existing target outside the batch aborts everything unless `resolutions` says to skip
+// that file, move the existing one to the recycle bin first, or replace it. `progress` hears
+// each file's original path as it starts, finishes or fails.
+pub fn validate_and_rename(
+    previews: &[RenamePreview],
+    ordered: bool,
+    resolutions: &HashMap<PathBuf, ConflictPolicy>,
+    mut progress: impl FnMut(&Path, RenameStage),
+) -> Result<RenameReport, RenameError> {
     if previews.is_empty() {
-        return Ok(0);
+        return Ok(RenameReport::default());
     }
 
     let mut target_names: HashSet<PathBuf> = HashSet::new();
@@ -112,46 +994,1269 @@ pub fn validate_and_rename(previews: &[RenamePreview]) -> Result<usize> {
         previews.iter().map(|p| p.original_path.clone()).collect();
 
     for preview in previews {
+        if let Some(reason) = &preview.invalid_reason {
+            return Err(RenameError::InvalidName {
+                path: preview.original_path.clone(),
+                reason: reason.clone(),
+            });
+        }
         let target_path = preview
             .original_path
             .parent()
             .unwrap_or(&preview.original_path)
             .join(&preview.new_name);
+        if is_reserved_name(&preview.new_name) {
+            return Err(RenameError::ReservedName(target_path));
+        }
 
-        if target_path.exists() && !original_paths.contains(&target_path) {
-            anyhow::bail!("Target exists: {}", target_path.display());
+        let resolution = resolutions.get(&target_path).copied().unwrap_or_default();
+        if long_path(&target_path).exists()
+            && !original_paths.contains(&target_path)
+            && resolution == ConflictPolicy::Abort
+        {
+            return Err(RenameError::TargetExists(target_path));
         }
         if target_names.contains(&target_path) {
-            anyhow::bail!("Duplicate target: {}", preview.new_name);
+            return Err(RenameError::DuplicateTarget(target_path));
         }
         target_names.insert(target_path);
     }
 
-    let temp_prefix = format!(".rename_temp_{}_", std::process::id());
-    let mut temp_renames: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let temp_prefix = format!(
+        "{}{}_{:016x}_",
+        TEMP_PREFIX,
+        std::process::id(),
+        random_tag()
+    );
+    let renamed: Vec<&RenamePreview> = previews
+        .iter()
+        .filter(|p| p.original_name.as_str() != p.new_name)
+        .collect();
+    let moves: Vec<(PathBuf, PathBuf)> = renamed
+        .iter()
+        .map(|p| {
+            let parent = p.original_path.parent().unwrap_or(&p.original_path);
+            (p.original_path.clone(), parent.join(&p.new_name))
+        })
+        .collect();
+
+    let steps = if ordered {
+        plan_ordered(&moves, &temp_prefix)
+    } else {
+        plan_two_phase(&moves, &temp_prefix)
+    };
+
+    // Where each file currently is, and why its move was abandoned
+    let mut current: Vec<PathBuf> = moves.iter().map(|(source, _)| source.clone()).collect();
+    let mut errors: Vec<Option<String>> = vec![None; moves.len()];
+    let mut skipped = vec![false; moves.len()];
+    let mut replaces = vec![false; moves.len()];
+    let mut report = RenameReport::default();
+    for (i, (source, target)) in moves.iter().enumerate() {
+        let Some(&policy) = resolutions.get(target) else {
+            continue;
+        };
+        if !is_occupied(source, target) {
+            continue;
+        }
+        match policy {
+            ConflictPolicy::Abort => {}
+            ConflictPolicy::Skip => {
+                skipped[i] = true;
+                report.skipped.push(source.clone());
+            }
+            ConflictPolicy::Trash => match trash::delete(target) {
+                Ok(()) => report.trashed.push(target.clone()),
+                Err(e) => {
+                    progress(source, RenameStage::Failed);
+                    errors[i] = Some(format!(
+                        "Could not move existing {} to the recycle bin: {}",
+                        target.display(),
+                        e
+                    ))
+                }
+            },
+            ConflictPolicy::Overwrite => replaces[i] = true,
+        }
+    }
+    // Files on network shares are looked for after each step instead of trusting the server
+    let mut shares: HashMap<&Path, bool> = HashMap::new();
+    let cautious: Vec<bool> = moves
+        .iter()
+        .map(|(source, _)| {
+            let folder = source.parent().unwrap_or(source);
+            *shares
+                .entry(folder)
+                .or_insert_with(|| network_filesystem(folder))
+        })
+        .collect();
+    let mut started = vec![false; moves.len()];
+    for step in steps {
+        if errors[step.index].is_some() || skipped[step.index] {
+            continue;
+        }
+        let source = &moves[step.index].0;
+        if !std::mem::replace(&mut started[step.index], true) {
+            progress(source, RenameStage::Renaming);
+        }
+        let replace = step.completes && replaces[step.index];
+        match run_step(&step, replace, cautious[step.index]) {
+            Ok(()) => {
+                if replace {
+                    report.overwritten.push(step.to.clone());
+                }
+                current[step.index] = step.to;
+                if step.completes {
+                    report.renamed += 1;
+                    progress(source, RenameStage::Renamed);
+                }
+            }
+            Err(e) => {
+                errors[step.index] = Some(format!("{:#}", e));
+                progress(source, RenameStage::Failed);
+            }
+        }
+    }
+
+    for (i, error) in errors.into_iter().enumerate() {
+        let Some(mut error) = error else { continue };
+        let source = &moves[i].0;
+        // Files parked on a temp name go back to their original name if it's still free
+        if current[i] != *source {
+            if !long_path(source).exists()
+                && fs::rename(long_path(&current[i]), long_path(source)).is_ok()
+            {
+                current[i] = source.clone();
+            } else {
+                error.push_str(&format!(" (left as {})", current[i].display()));
+            }
+        }
+        let original = renamed[i];
+        let original_name = if current[i] == *source {
+            Arc::clone(&original.original_name)
+        } else {
+            let name = current[i].file_name().unwrap_or_default();
+            Arc::new(name.to_string_lossy().to_string())
+        };
+        report.failed.push(FailedRename {
+            preview: RenamePreview {
+                original_path: current[i].clone(),
+                original_name,
+                new_name: original.new_name.clone(),
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count: original.match_count,
+                edited: original.edited,
+                warnings: Vec::new(),
+            },
+            error,
+        });
+    }
+    report.locations = moves
+        .into_iter()
+        .map(|(source, _)| source)
+        .zip(current)
+        .collect();
+
+    Ok(report)
+}
 
+// Records each renamed file's original name where it can be found later without the app:
+// an extended attribute on the file, or a line in its folder's sidecar where the filesystem
+// has none. A name recorded by an earlier rename is kept, so it stays the first one.
+// Returns the files whose name couldn't be recorded either way.
+pub fn record_original_names(report: &RenameReport, previews: &[RenamePreview]) -> Vec<PathBuf> {
+    let current: HashMap<&Path, &Path> = report
+        .locations
+        .iter()
+        .map(|(source, now)| (source.as_path(), now.as_path()))
+        .collect();
+    let mut sidecars: BTreeMap<&Path, Vec<(PathBuf, String)>> = BTreeMap::new();
     for preview in previews {
-        if preview.original_name.as_str() == preview.new_name {
+        let source = preview.original_path.as_path();
+        let Some(&now) = current.get(source) else {
+            continue;
+        };
+        let folder = source.parent().unwrap_or(source);
+        if now != folder.join(&preview.new_name) {
             continue;
         }
+        if set_origin_attribute(now, &preview.original_name).is_err() {
+            sidecars.entry(folder).or_default().push((
+                now.to_path_buf(),
+                format!("{}\t{}\n", preview.new_name, preview.original_name),
+            ));
+        }
+    }
+
+    let mut unrecorded = Vec::new();
+    for (folder, entries) in sidecars {
+        let lines: String = entries.iter().map(|(_, line)| line.as_str()).collect();
+        let written = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(long_path(&folder.join(ORIGIN_SIDECAR)))
+            .and_then(|mut file| io::Write::write_all(&mut file, lines.as_bytes()));
+        if written.is_err() {
+            unrecorded.extend(entries.into_iter().map(|(path, _)| path));
+        }
+    }
+    unrecorded
+}
+
+// Sets the origin attribute without following symlinks; one already there is left as it is
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn set_origin_attribute(path: &Path, original: &str) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let name = ORIGIN_ATTRIBUTE.as_ptr().cast();
+    let value = original.as_bytes();
+    // SAFETY: `path` and `name` are NUL-terminated, `value` is valid for its length
+    #[cfg(target_os = "linux")]
+    let result = unsafe {
+        libc::lsetxattr(
+            path.as_ptr(),
+            name,
+            value.as_ptr().cast(),
+            value.len(),
+            libc::XATTR_CREATE,
+        )
+    };
+    // SAFETY: as above
+    #[cfg(target_os = "macos")]
+    let result = unsafe {
+        libc::setxattr(
+            path.as_ptr(),
+            name,
+            value.as_ptr().cast(),
+            value.len(),
+            0,
+            libc::XATTR_CREATE | libc::XATTR_NOFOLLOW,
+        )
+    };
+    if result == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::EEXIST) {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn set_origin_attribute(_path: &Path, _original: &str) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// Sizes of the files about to be renamed, taken before the batch runs so the verification
+// pass can compare them. Files that can't be read are left out and their size isn't checked.
+pub fn file_sizes(previews: &[RenamePreview]) -> HashMap<PathBuf, u64> {
+    previews
+        .iter()
+        .filter(|p| p.original_name.as_str() != p.new_name)
+        .filter_map(|p| {
+            let metadata = fs::metadata(long_path(&p.original_path)).ok()?;
+            metadata
+                .is_file()
+                .then(|| (p.original_path.clone(), metadata.len()))
+        })
+        .collect()
+}
+
+// Checks every rename the report counts as done: the new name must exist, the old one must
+// be gone and the size must be what it was before. A discrepancy moves the file from the
+// renamed count to `verify_failed`; failed and skipped files were already reported.
+pub fn verify_renames(
+    report: &mut RenameReport,
+    previews: &[RenamePreview],
+    sizes: &HashMap<PathBuf, u64>,
+) {
+    let current: HashMap<&Path, &Path> = report
+        .locations
+        .iter()
+        .map(|(source, now)| (source.as_path(), now.as_path()))
+        .collect();
+    let mut moves = Vec::new();
+    for preview in previews {
+        let source = preview.original_path.as_path();
+        let target = source.parent().unwrap_or(source).join(&preview.new_name);
+        if current.get(source).is_some_and(|&now| now == target) {
+            moves.push((source, target));
+        }
+    }
+    // A source name may have been taken over by another file in the batch (swaps, chains)
+    let targets: HashSet<&Path> = moves.iter().map(|(_, target)| target.as_path()).collect();
+
+    let mut failures = Vec::new();
+    for (source, target) in &moves {
+        let reason = match fs::metadata(long_path(target)) {
+            Err(e) => Some(format!("new name not found ({})", e)),
+            // On a case-insensitive filesystem a case-only rename leaves the old name
+            // resolving to the same file
+            Ok(_)
+                if !targets.contains(source)
+                    && !same_ignoring_case(source, target)
+                    && long_path(source).exists() =>
+            {
+                Some("the old name still exists".to_string())
+            }
+            Ok(metadata) => sizes
+                .get(*source)
+                .filter(|&&before| metadata.is_file() && metadata.len() != before)
+                .map(|before| format!("size changed from {} to {} bytes", before, metadata.len())),
+        };
+        if let Some(reason) = reason {
+            failures.push(VerifyFailure {
+                path: target.clone(),
+                reason,
+            });
+        }
+    }
+    report.renamed = report.renamed.saturating_sub(failures.len());
+    report.verify_failed = failures;
+}
+
+fn same_ignoring_case(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+// One filesystem rename for move `index`; `completes` marks the step that gives a file its
+// final name
+struct RenameStep {
+    index: usize,
+    from: PathBuf,
+    to: PathBuf,
+    completes: bool,
+}
+
+// Renames without replacing a file: after an earlier failure the target may still be
+// occupied by a file that didn't move away. With `replace`, an existing file is replaced by
+// the rename call itself, which is atomic on Unix and uses MoveFileEx's replace-existing
+// mode on Windows. With `check`, the file must be found under its new name afterwards, as
+// a network share can report success for a rename it hasn't carried out.
+fn run_step(step: &RenameStep, replace: bool, check: bool) -> Result<()> {
+    if !replace && is_occupied(&step.from, &step.to) {
+        anyhow::bail!("Target still occupied: {}", step.to.display());
+    }
+    fs::rename(long_path(&step.from), long_path(&step.to)).with_context(|| {
+        if step.completes {
+            format!("Failed to finalize: {}", step.to.display())
+        } else {
+            format!("Failed to rename: {}", step.from.display())
+        }
+    })?;
+    if check {
+        // A case-only rename leaves the old spelling resolving to the same file
+        let moved = long_path(&step.to).exists()
+            && (same_ignoring_case(&step.from, &step.to) || !long_path(&step.from).exists());
+        if !moved {
+            anyhow::bail!(
+                "The network share reported {} as renamed, but the file hasn't moved",
+                step.to.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+// Whether `to` exists as a file other than `from`. For case-only renames a
+// case-insensitive filesystem resolves `to` to `from` itself, so the folder is checked for
+// an entry with exactly the target name.
+fn is_occupied(from: &Path, to: &Path) -> bool {
+    if !long_path(to).exists() {
+        return false;
+    }
+    if !same_ignoring_case(from, to) {
+        return true;
+    }
+    let (Some(dir), Some(name)) = (to.parent(), to.file_name()) else {
+        return true;
+    };
+    fs::read_dir(long_path(dir)).map_or(true, |entries| {
+        entries.flatten().any(|entry| entry.file_name() == name)
+    })
+}
+
+// Checks whether a name falls in the namespace reserved for intermediate temp names, or
+// would take the place of the original-name sidecar
+pub fn is_reserved_name(name: &str) -> bool {
+    name.to_ascii_lowercase().starts_with(TEMP_PREFIX) || name.eq_ignore_ascii_case(ORIGIN_SIDECAR)
+}
+
+// Random per-run component of temp names, so they can't collide with real files
+fn random_tag() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+// Temporary name next to the file, derived from `name`
+fn temp_path_for(path: &Path, temp_prefix: &str, name: &str) -> PathBuf {
+    path.parent()
+        .unwrap_or(path)
+        .join(format!("{}{}", temp_prefix, name))
+}
+
+// Moves every file to a temporary name first, then to its final name
+fn plan_two_phase(moves: &[(PathBuf, PathBuf)], temp_prefix: &str) -> Vec<RenameStep> {
+    let mut steps = Vec::with_capacity(moves.len() * 2);
+    let mut finals = Vec::with_capacity(moves.len());
+    for (index, (source, target)) in moves.iter().enumerate() {
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let temp_path = temp_path_for(target, temp_prefix, &name);
+        steps.push(RenameStep {
+            index,
+            from: source.clone(),
+            to: temp_path.clone(),
+            completes: false,
+        });
+        finals.push(RenameStep {
+            index,
+            from: temp_path,
+            to: target.clone(),
+            completes: true,
+        });
+    }
+    steps.extend(finals);
+    steps
+}
+
+// Orders direct renames so no target is written while its current occupant is still waiting to
+// move (e.g. 3→4, then 2→3, then 1→2). Only moves on a cycle (a→b, b→a) go through a temporary
+// name. Paths are compared case-insensitively so case-insensitive filesystems stay safe.
+fn plan_ordered(moves: &[(PathBuf, PathBuf)], temp_prefix: &str) -> Vec<RenameStep> {
+    let key = |path: &Path| path.to_string_lossy().to_lowercase();
+    let by_source: HashMap<String, usize> = moves
+        .iter()
+        .enumerate()
+        .map(|(i, (source, _))| (key(source), i))
+        .collect();
+
+    // dependents[j] lists the moves waiting for move j to vacate their target
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); moves.len()];
+    let mut queued = vec![false; moves.len()];
+    let mut ready = std::collections::VecDeque::new();
+    for (i, (_, target)) in moves.iter().enumerate() {
+        match by_source.get(&key(target)).copied().filter(|&j| j != i) {
+            Some(j) => dependents[j].push(i),
+            None => {
+                queued[i] = true;
+                ready.push_back(i);
+            }
+        }
+    }
+
+    let mut sources: Vec<PathBuf> = moves.iter().map(|(source, _)| source.clone()).collect();
+    let mut done = vec![false; moves.len()];
+    let mut steps = Vec::with_capacity(moves.len());
+
+    loop {
+        while let Some(i) = ready.pop_front() {
+            steps.push(RenameStep {
+                index: i,
+                from: sources[i].clone(),
+                to: moves[i].1.clone(),
+                completes: true,
+            });
+            done[i] = true;
+            for &d in &dependents[i] {
+                if !queued[d] {
+                    queued[d] = true;
+                    ready.push_back(d);
+                }
+            }
+        }
+
+        // Whatever remains forms cycles; park one member on a temp name to break each
+        let Some(i) = (0..moves.len()).find(|&i| !done[i] && !queued[i]) else {
+            break;
+        };
+        let name = sources[i]
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let temp_path = temp_path_for(&sources[i], temp_prefix, &name);
+        steps.push(RenameStep {
+            index: i,
+            from: sources[i].clone(),
+            to: temp_path.clone(),
+            completes: false,
+        });
+        sources[i] = temp_path;
+        for &d in &dependents[i] {
+            if !queued[d] {
+                queued[d] = true;
+                ready.push_back(d);
+            }
+        }
+    }
+
+    steps
+}
+
+// Converts to extended-length form (\\?\C:\… or \\?\UNC\…) when the path exceeds MAX_PATH
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    use std::path::{Component, Prefix};
+
+    if path.as_os_str().len() < MAX_PATH {
+        return Cow::Borrowed(path);
+    }
+    // Extended-length paths skip Win32 normalization, so resolve `.`, `..` and `/` first
+    let Ok(absolute) = std::path::absolute(path) else {
+        return Cow::Borrowed(path);
+    };
+    let (is_disk, is_unc) = match absolute.components().next() {
+        Some(Component::Prefix(prefix)) => (
+            matches!(prefix.kind(), Prefix::Disk(_)),
+            matches!(prefix.kind(), Prefix::UNC(..)),
+        ),
+        _ => (false, false),
+    };
+    // Already verbatim (\\?\…) or device paths are used as-is
+    if !is_disk && !is_unc {
+        return Cow::Owned(absolute);
+    }
+    let Some(text) = absolute.to_str() else {
+        return Cow::Borrowed(path);
+    };
+    let text = text.trim_end_matches('\\');
+
+    let extended = if is_unc {
+        format!(r"\\?\UNC\{}", text.trim_start_matches('\\'))
+    } else {
+        format!(r"\\?\{}", text)
+    };
+    Cow::Owned(PathBuf::from(extended))
+}
+
+// Non-Windows platforms have no MAX_PATH limit
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+// Writes a script that performs the previewed renames with the same two-phase approach
+pub fn export_script(previews: &[RenamePreview], format: ScriptFormat, path: &Path) -> Result<()> {
+    if let Some(preview) = previews.iter().find(|p| p.invalid_reason.is_some()) {
+        anyhow::bail!(
+            "{}: {}",
+            preview.invalid_reason.as_deref().unwrap_or_default(),
+            preview.original_path.display()
+        );
+    }
+    if let Some(preview) = previews.iter().find(|p| is_reserved_name(&p.new_name)) {
+        anyhow::bail!("Reserved temporary name: {}", preview.new_name);
+    }
+    // The script runs unattended, so a clash would overwrite a file rather than stop
+    if let Some(preview) = previews.iter().find(|p| !p.conflicts.is_empty()) {
+        anyhow::bail!(
+            "{}: {}",
+            preview.conflicts[0],
+            preview.original_path.display()
+        );
+    }
+    let mut targets = HashSet::new();
+    for preview in previews {
         let parent = preview
             .original_path
             .parent()
             .unwrap_or(&preview.original_path);
-        let temp_path = parent.join(format!("{}{}", temp_prefix, preview.new_name));
-        let final_path = parent.join(&preview.new_name);
+        if !targets.insert((parent, preview.new_name.to_lowercase())) {
+            anyhow::bail!(
+                "Another file in the batch gets the same name: {}",
+                parent.join(&preview.new_name).display()
+            );
+        }
+    }
+
+    let temp_prefix = format!("{}script_{:016x}_", TEMP_PREFIX, random_tag());
+    let original_paths: HashSet<&Path> =
+        previews.iter().map(|p| p.original_path.as_path()).collect();
+
+    // (source, temp, final) for each entry that actually changes
+    let steps: Vec<(&Path, PathBuf, PathBuf)> = previews
+        .iter()
+        .filter(|p| p.original_name.as_str() != p.new_name)
+        .map(|p| {
+            let parent = p.original_path.parent().unwrap_or(&p.original_path);
+            (
+                p.original_path.as_path(),
+                parent.join(format!("{}{}", temp_prefix, p.new_name)),
+                parent.join(&p.new_name),
+            )
+        })
+        .collect();
 
-        fs::rename(&preview.original_path, &temp_path)
-            .with_context(|| format!("Failed to rename: {}", preview.original_path.display()))?;
-        temp_renames.push((temp_path, final_path));
+    let script = match format {
+        ScriptFormat::Shell => shell_script(&steps, &original_paths),
+        ScriptFormat::Batch => batch_script(&steps, &original_paths),
+    };
+
+    fs::write(path, script).with_context(|| format!("Failed to write: {}", path.display()))?;
+
+    #[cfg(unix)]
+    if format == ScriptFormat::Shell {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o755));
     }
 
-    let mut renamed_count = 0;
-    for (temp_path, final_path) in temp_renames {
-        fs::rename(&temp_path, &final_path)
-            .with_context(|| format!("Failed to finalize: {}", final_path.display()))?;
-        renamed_count += 1;
+    Ok(())
+}
+
+fn shell_script(steps: &[(&Path, PathBuf, PathBuf)], sources: &HashSet<&Path>) -> String {
+    let mut out = String::from("#!/bin/sh\n# Generated by File Rename Plus\nset -e\n");
+    for (_, _, final_path) in steps {
+        if !sources.contains(final_path.as_path()) {
+            let target = shell_quote(final_path);
+            out.push_str(&format!(
+                "if [ -e {target} ]; then echo \"Target exists: \"{target} >&2; exit 1; fi\n"
+            ));
+        }
+    }
+    out.push_str("\n# Phase 1: move to temporary names\n");
+    for (source, temp_path, _) in steps {
+        out.push_str(&format!(
+            "mv -- {} {}\n",
+            shell_quote(source),
+            shell_quote(temp_path)
+        ));
     }
+    out.push_str("\n# Phase 2: move to final names\n");
+    for (_, temp_path, final_path) in steps {
+        // mv replaces an existing target without asking, so each move checks first
+        let target = shell_quote(final_path);
+        out.push_str(&format!(
+            "if [ -e {target} ]; then echo \"Target exists: \"{target} >&2; exit 1; fi\n"
+        ));
+        out.push_str(&format!("mv -- {} {}\n", shell_quote(temp_path), target));
+    }
+    out
+}
+
+fn batch_script(steps: &[(&Path, PathBuf, PathBuf)], sources: &HashSet<&Path>) -> String {
+    let mut lines = vec![
+        "@echo off".to_string(),
+        "rem Generated by File Rename Plus".to_string(),
+        "setlocal DisableDelayedExpansion".to_string(),
+        "chcp 65001 >nul".to_string(),
+        String::new(),
+    ];
+    for (_, _, final_path) in steps {
+        if !sources.contains(final_path.as_path()) {
+            let target = batch_quote(final_path);
+            lines.push(format!(
+                "if exist {target} (echo Target exists: {target} & exit /b 1)"
+            ));
+        }
+    }
+    lines.push(String::new());
+    lines.push("rem Phase 1: move to temporary names".to_string());
+    for (source, temp_path, _) in steps {
+        lines.push(format!(
+            "ren {} {} || exit /b 1",
+            batch_quote(source),
+            batch_quote_name(temp_path)
+        ));
+    }
+    lines.push(String::new());
+    lines.push("rem Phase 2: move to final names".to_string());
+    for (_, temp_path, final_path) in steps {
+        // `ren` refuses an existing target too; the check names it before stopping
+        let target = batch_quote(final_path);
+        lines.push(format!(
+            "if exist {target} (echo Target exists: {target} & exit /b 1)"
+        ));
+        lines.push(format!(
+            "ren {} {} || exit /b 1",
+            batch_quote(temp_path),
+            batch_quote_name(final_path)
+        ));
+    }
+    lines.push(String::new());
+    lines.join("\r\n")
+}
+
+// Single-quotes a path for POSIX sh, escaping embedded single quotes
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+// Double-quotes a path for cmd.exe; percent signs must be doubled inside batch files
+fn batch_quote(path: &Path) -> String {
+    format!("\"{}\"", path.to_string_lossy().replace('%', "%%"))
+}
+
+// `ren` takes a bare file name as its target
+fn batch_quote_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("\"{}\"", name.replace('%', "%%"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rename::{
+        apply_find_replace, apply_iteration_numbering, lint_template, FindOptions, NumberingOptions,
+    };
+    use crate::types::NumberGrouping;
+    use proptest::prelude::*;
+
+    fn listed(folder: &Path, name: &str) -> FileEntry {
+        FileEntry {
+            path: folder.join(name),
+            name: Arc::new(name.to_string()),
+            identity: None,
+            pinned: false,
+            added: 0,
+        }
+    }
+
+    fn preview(folder: &Path, from: &str, to: &str) -> RenamePreview {
+        RenamePreview {
+            original_path: folder.join(from),
+            original_name: Arc::new(from.to_string()),
+            new_name: to.to_string(),
+            conflicts: Vec::new(),
+            invalid_reason: None,
+            match_count: 0,
+            edited: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn rename(previews: &[RenamePreview], ordered: bool) -> Result<RenameReport, RenameError> {
+        validate_and_rename(previews, ordered, &HashMap::new(), |_, _| {})
+    }
+
+    // Each file in the folder with its contents, sorted by path
+    fn listing(folder: &Path) -> Vec<(PathBuf, String)> {
+        let mut files: Vec<(PathBuf, String)> = fs::read_dir(folder)
+            .expect("listing")
+            .flatten()
+            .map(|e| {
+                let contents = fs::read_to_string(e.path()).unwrap_or_default();
+                (e.path(), contents)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn invalid_names_never_reach_the_disk() {
+        let mut preview = preview(Path::new("/props"), "photo.jpg", ".jpg");
+        preview.invalid_reason =
+            Some("Resulting name \".jpg\" has no name before the extension".into());
+        let error = rename(&[preview], false).expect_err("invalid name");
+        assert!(error.to_string().contains("no name before the extension"));
+    }
+
+    #[test]
+    fn existing_paths_leave_out_the_batch() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        for name in ["a.txt", "b.txt", "002.txt", "004.txt"] {
+            fs::write(folder.join(name), name).expect("test file");
+        }
+        let files = [listed(folder, "a.txt"), listed(folder, "b.txt")];
+        let taken = existing_paths(&files, false);
+        let expected: HashSet<PathBuf> = [folder.join("002.txt"), folder.join("004.txt")].into();
+        assert_eq!(taken, expected);
+    }
+
+    #[test]
+    fn folded_numbering_skips_names_taken_in_another_case() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        for name in ["a.txt", "b.txt", "002.TXT"] {
+            fs::write(folder.join(name), name).expect("test file");
+        }
+        let files = [listed(folder, "a.txt"), listed(folder, "b.txt")];
+        let numbered = |fold_case: bool| {
+            let options = NumberingOptions {
+                start_number: 1,
+                padding: 3,
+                fold_case,
+                ..NumberingOptions::default()
+            };
+            let taken = existing_paths(&files, fold_case);
+            apply_iteration_numbering(&files, "{n}", &options, Some(&taken))
+                .expect("numbering")
+                .into_iter()
+                .map(|p| p.new_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(numbered(true), ["001.txt", "003.txt"]);
+        assert_eq!(numbered(false), ["001.txt", "002.txt"]);
+    }
+
+    #[test]
+    fn short_paths_are_used_as_they_are() {
+        let path = Path::new("short/name.txt");
+        assert!(matches!(long_path(path), Cow::Borrowed(p) if p == path));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_paths_take_the_extended_length_form() {
+        let deep = "d".repeat(120);
+        let disk = PathBuf::from(format!(r"C:\{0}\x\..\{0}\{0}\file.txt", deep));
+        assert_eq!(
+            long_path(&disk).as_os_str(),
+            format!(r"\\?\C:\{0}\{0}\{0}\file.txt", deep).as_str()
+        );
+        let unc = PathBuf::from(format!(r"\\server\share\{0}\{0}\{0}\", deep));
+        assert_eq!(
+            long_path(&unc).as_os_str(),
+            format!(r"\\?\UNC\server\share\{0}\{0}\{0}", deep).as_str()
+        );
+        // Already extended paths stay as they are
+        let verbatim = PathBuf::from(format!(r"\\?\C:\{0}\{0}\{0}", deep));
+        assert_eq!(long_path(&verbatim).as_os_str(), verbatim.as_os_str());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn files_beyond_max_path_can_be_renamed() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path().join("f".repeat(100)).join("g".repeat(100));
+        fs::create_dir_all(long_path(&folder)).expect("deep folder");
+        let from = "a".repeat(80) + ".txt";
+        let to = "b".repeat(80) + ".txt";
+        assert!(folder.join(&to).as_os_str().len() > 260);
+        fs::write(long_path(&folder.join(&from)), "long").expect("test file");
+
+        let report = rename(&[preview(&folder, &from, &to)], false).expect("rename");
+        assert_eq!(report.renamed, 1);
+        assert!(long_path(&folder.join(&to)).exists());
+        assert!(!long_path(&folder.join(&from)).exists());
+    }
+
+    #[test]
+    fn ordered_renames_shift_a_series_without_temporary_names() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        for n in 1..=3 {
+            fs::write(folder.join(format!("{}.txt", n)), n.to_string()).expect("test file");
+        }
+        let shift = [
+            preview(folder, "1.txt", "2.txt"),
+            preview(folder, "2.txt", "3.txt"),
+            preview(folder, "3.txt", "4.txt"),
+        ];
+        let mut started = Vec::new();
+        let report = validate_and_rename(&shift, true, &HashMap::new(), |path, stage| {
+            if stage == RenameStage::Renaming {
+                started.push(path.file_name().unwrap().to_string_lossy().into_owned());
+            }
+        })
+        .expect("shift");
+        assert_eq!(report.renamed, 3);
+        // Each target is vacated before it's written
+        assert_eq!(started, ["3.txt", "2.txt", "1.txt"]);
+        assert_eq!(
+            listing(folder),
+            [
+                (folder.join("2.txt"), "1".to_string()),
+                (folder.join("3.txt"), "2".to_string()),
+                (folder.join("4.txt"), "3".to_string()),
+            ]
+        );
 
-    Ok(renamed_count)
+        // A swap is a cycle, so one of the pair is parked and nothing is left behind
+        let swap = [
+            preview(folder, "2.txt", "3.txt"),
+            preview(folder, "3.txt", "2.txt"),
+        ];
+        assert_eq!(rename(&swap, true).expect("swap").renamed, 2);
+        assert_eq!(
+            listing(folder),
+            [
+                (folder.join("2.txt"), "2".to_string()),
+                (folder.join("3.txt"), "1".to_string()),
+                (folder.join("4.txt"), "3".to_string()),
+            ]
+        );
+    }
+
+    // The comparator the keys replaced, walking both names character by character
+    fn natural_cmp(a: &str, b: &str) -> Ordering {
+        let mut a_chars = a.chars().peekable();
+        let mut b_chars = b.chars().peekable();
+        loop {
+            match (a_chars.peek(), b_chars.peek()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(&ac), Some(&bc)) => {
+                    if ac.is_ascii_digit() && bc.is_ascii_digit() {
+                        match number(&mut a_chars).cmp(&number(&mut b_chars)) {
+                            Ordering::Equal => continue,
+                            other => return other,
+                        }
+                    }
+                    match ac.to_ascii_lowercase().cmp(&bc.to_ascii_lowercase()) {
+                        Ordering::Equal => {
+                            a_chars.next();
+                            b_chars.next();
+                        }
+                        other => return other,
+                    }
+                }
+            }
+        }
+    }
+
+    fn number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> u64 {
+        let mut num: u64 = 0;
+        while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+            num = num.saturating_mul(10).saturating_add(u64::from(d));
+            chars.next();
+        }
+        num
+    }
+
+    proptest! {
+        // Few distinct characters, so names share prefixes and digit runs meet text often
+        #[test]
+        fn keys_order_like_the_character_walk(
+            a in "[aAb0-9 ._é/]{0,12}",
+            b in "[aAb0-9 ._é/]{0,12}",
+        ) {
+            prop_assert_eq!(NaturalKey::new(&a).cmp(&NaturalKey::new(&b)), natural_cmp(&a, &b));
+        }
+    }
+
+    #[test]
+    fn keys_order_edge_cases_like_the_character_walk() {
+        let names = [
+            "",
+            "a",
+            "a0",
+            "a1",
+            "a01",
+            "a 1",
+            "a.1",
+            "a10",
+            "a2",
+            "A2",
+            "ab",
+            "a_b",
+            "a/b",
+            "1",
+            "01",
+            "1a",
+            "10",
+            "é",
+            "z",
+            "99999999999999999999999",
+            "photo (2).jpg",
+            "photo (10).jpg",
+            "photo.jpg",
+            "photo1.jpg",
+        ];
+        for a in names {
+            for b in names {
+                assert_eq!(
+                    NaturalKey::new(a).cmp(&NaturalKey::new(b)),
+                    natural_cmp(a, b),
+                    "{:?} vs {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn natural_sort_orders_numbers_by_value() {
+        let mut files: Vec<FileEntry> = ["img10.jpg", "IMG2.jpg", "img1.jpg", "img02b.jpg"]
+            .into_iter()
+            .map(|name| file_entry(PathBuf::from(name)))
+            .collect();
+        sort_naturally(&mut files);
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["img1.jpg", "IMG2.jpg", "img02b.jpg", "img10.jpg"]);
+    }
+
+    #[test]
+    fn existing_targets_follow_the_chosen_policy() {
+        let run = |policy: Option<ConflictPolicy>| {
+            let dir = tempfile::tempdir().expect("temp dir");
+            let folder = dir.path();
+            fs::write(folder.join("new.txt"), "new").expect("source");
+            fs::write(folder.join("old.txt"), "old").expect("occupant");
+            let resolutions: HashMap<PathBuf, ConflictPolicy> = policy
+                .map(|policy| (folder.join("old.txt"), policy))
+                .into_iter()
+                .collect();
+            let rename = [preview(folder, "new.txt", "old.txt")];
+            let result = validate_and_rename(&rename, false, &resolutions, |_, _| {});
+            (result, listing(folder), dir)
+        };
+
+        // By default an existing target aborts the whole run
+        let (result, files, _dir) = run(None);
+        let error = result.expect_err("target exists");
+        assert!(matches!(error, RenameError::TargetExists(_)));
+        assert_eq!(files.len(), 2);
+
+        let (result, files, dir_skip) = run(Some(ConflictPolicy::Skip));
+        let report = result.expect("skip");
+        assert_eq!(report.renamed, 0);
+        assert_eq!(report.skipped, [dir_skip.path().join("new.txt")]);
+        assert_eq!(
+            files,
+            [
+                (dir_skip.path().join("new.txt"), "new".to_string()),
+                (dir_skip.path().join("old.txt"), "old".to_string()),
+            ]
+        );
+
+        let (result, files, dir_over) = run(Some(ConflictPolicy::Overwrite));
+        let report = result.expect("overwrite");
+        assert_eq!(report.renamed, 1);
+        assert_eq!(report.overwritten, [dir_over.path().join("old.txt")]);
+        assert_eq!(
+            files,
+            [(dir_over.path().join("old.txt"), "new".to_string())]
+        );
+
+        let (result, files, dir_trash) = run(Some(ConflictPolicy::Trash));
+        let report = result.expect("trash");
+        assert_eq!(report.trashed, [dir_trash.path().join("old.txt")]);
+        assert_eq!(
+            files,
+            [(dir_trash.path().join("old.txt"), "new".to_string())]
+        );
+    }
+
+    #[test]
+    fn core_operations_report_what_went_wrong_as_variants() {
+        let missing = Path::new("/props/no-such-folder");
+        let scan = scan_directory(&missing.to_string_lossy(), None, SortOrder::default());
+        assert!(matches!(scan, Err(ScanError::NotFound(path)) if path == missing));
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(folder.join(name), name).expect("test file");
+        }
+        let refused = |previews: &[RenamePreview]| rename(previews, false).expect_err("refused");
+        let error = refused(&[preview(folder, "a.txt", "c.txt")]);
+        assert!(matches!(&error, RenameError::TargetExists(path) if path == &folder.join("c.txt")));
+        let error = refused(&[
+            preview(folder, "a.txt", "d.txt"),
+            preview(folder, "b.txt", "d.txt"),
+        ]);
+        assert!(
+            matches!(&error, RenameError::DuplicateTarget(path) if path == &folder.join("d.txt"))
+        );
+        let error = refused(&[preview(folder, "a.txt", ".rename_temp_a.txt")]);
+        assert!(matches!(error, RenameError::ReservedName(_)));
+        // Nothing was touched by the refused batches
+        assert_eq!(listing(folder).len(), 3);
+    }
+
+    // Digests of small fixed contents, checked against sha256sum. 1000 bytes span several
+    // blocks, so the chunking and length padding are covered too.
+    const KNOWN_HASHES: [(&str, &str, &str); 3] = [
+        (
+            "empty.bin",
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "abc.txt",
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "long.txt",
+            "x",
+            "44f8354494a5ba03ba1792a8d3e9c534c47a9181980fde7a3f44b06ef2ae7c7f",
+        ),
+    ];
+
+    fn known_hash_folder() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("temp dir");
+        for (name, contents, _) in KNOWN_HASHES {
+            let contents = if name == "long.txt" {
+                contents.repeat(1000)
+            } else {
+                contents.to_string()
+            };
+            fs::write(dir.path().join(name), contents).expect("test file");
+        }
+        dir
+    }
+
+    #[test]
+    fn content_hashes_match_known_digests() {
+        let dir = known_hash_folder();
+        for (name, _, expected) in KNOWN_HASHES {
+            let hash = content_hash(&dir.path().join(name)).expect("hash");
+            assert_eq!(hash, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn hash_tokens_fill_in_the_content_hash() {
+        let dir = known_hash_folder();
+        let files: Vec<FileEntry> = KNOWN_HASHES
+            .iter()
+            .map(|(name, _, _)| listed(dir.path(), name))
+            .collect();
+        let hashes: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|f| (f.path.clone(), content_hash(&f.path).expect("hash")))
+            .collect();
+        let options = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(&files, "^", "{hash8}_", "", options, &hashes, None)
+            .expect("preview");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "e3b0c442_empty.bin",
+                "ba7816bf_abc.txt",
+                "44f83544_long.txt"
+            ]
+        );
+
+        let previews = apply_find_replace(&files, "abc", "{hash}", "", options, &hashes, None)
+            .expect("preview");
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].new_name, format!("{}.txt", KNOWN_HASHES[1].2));
+
+        // Files without a hash are left out rather than named with an empty one
+        let previews =
+            apply_find_replace(&files, "^", "{hash8}_", "", options, &HashMap::new(), None)
+                .expect("preview");
+        assert!(previews.is_empty());
+
+        for token in ["{hash0}", "{hash65}"] {
+            assert!(apply_find_replace(&files, "^", token, "", options, &hashes, None).is_err());
+        }
+    }
+
+    #[test]
+    fn iteration_templates_fill_in_the_content_hash() {
+        let dir = known_hash_folder();
+        let files: Vec<FileEntry> = KNOWN_HASHES
+            .iter()
+            .map(|(name, _, _)| listed(dir.path(), name))
+            .collect();
+        let hashes: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|f| (f.path.clone(), content_hash(&f.path).expect("hash")))
+            .collect();
+        let options = NumberingOptions {
+            start_number: 1,
+            padding: 2,
+            hashes: Arc::new(hashes),
+            ..NumberingOptions::default()
+        };
+        let previews =
+            apply_iteration_numbering(&files, "{n}_{hash8}", &options, None).expect("numbering");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["01_e3b0c442.bin", "02_ba7816bf.txt", "03_44f83544.txt"]
+        );
+
+        // Files without a hash are left out without using up a number
+        let mut partial = (*options.hashes).clone();
+        partial.remove(&files[0].path);
+        let options = NumberingOptions {
+            hashes: Arc::new(partial),
+            ..options
+        };
+        let previews =
+            apply_iteration_numbering(&files, "{n}_{hash}", &options, None).expect("numbering");
+        assert_eq!(previews.len(), 2);
+        assert_eq!(
+            previews[0].new_name,
+            format!("01_{}.txt", KNOWN_HASHES[1].2)
+        );
+
+        assert!(apply_iteration_numbering(&files, "{n}_{hash65}", &options, None).is_err());
+        let lint = lint_template("{n}_{hash0}", 0, NumberGrouping::None);
+        assert_eq!(lint.problems.len(), 1);
+        assert_eq!(lint.problems[0].column, Some(5));
+        assert!(lint_template("{n}_{hash8}", 0, NumberGrouping::None)
+            .problems
+            .is_empty());
+    }
+
+    #[test]
+    fn errors_are_classified_through_their_context() {
+        let missing = Path::new("/props/no-such-folder");
+        let scan = scan_directory(&missing.to_string_lossy(), None, SortOrder::default())
+            .expect_err("missing folder");
+        assert_eq!(error_kind(&scan.into()), ErrorKind::NotFound);
+
+        let hash = content_hash(&missing.join("file.txt")).expect_err("missing file");
+        let hash = anyhow::Error::from(hash).context("Hashing file.txt");
+        assert_eq!(error_kind(&hash), ErrorKind::NotFound);
+
+        let denied =
+            anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+                .context("Renaming a.txt");
+        assert_eq!(error_kind(&denied), ErrorKind::PermissionDenied);
+
+        let taken = anyhow::Error::from(RenameError::TargetExists(missing.join("b.txt")));
+        assert_eq!(error_kind(&taken), ErrorKind::Conflict);
+        let reserved = anyhow::Error::from(RenameError::ReservedName(missing.join("CON")));
+        assert_eq!(error_kind(&reserved), ErrorKind::InvalidName);
+
+        assert_eq!(
+            error_kind(&anyhow::anyhow!("Settings database is locked")),
+            ErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn scripts_refuse_batches_that_would_overwrite_a_file() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        let script = folder.join("rename.sh");
+        let duplicates = [
+            preview(folder, "a.txt", "same.txt"),
+            preview(folder, "b.txt", "Same.txt"),
+        ];
+        for format in [ScriptFormat::Shell, ScriptFormat::Batch] {
+            let error = export_script(&duplicates, format, &script).expect_err("duplicate targets");
+            assert!(error.to_string().contains("same name"), "{}", error);
+            assert!(!script.exists());
+        }
+
+        let mut flagged = preview(folder, "a.txt", "taken.txt");
+        flagged.conflicts.push(ConflictReason::ExistsOnDisk);
+        assert!(export_script(&[flagged], ScriptFormat::Shell, &script).is_err());
+
+        // Each final move checks its target first, since mv would replace it
+        export_script(
+            &[preview(folder, "a.txt", "b.txt")],
+            ScriptFormat::Shell,
+            &script,
+        )
+        .expect("export");
+        let text = fs::read_to_string(&script).expect("script");
+        let target = format!("'{}'", folder.join("b.txt").display());
+        let guard = text.find(&format!("if [ -e {} ]", target)).expect("guard");
+        let phase_two = text.rfind("mv -- ").expect("final move");
+        assert!(guard < phase_two);
+    }
 }
@@ -0,0 +1,244 @@
+// Post-rename hook: a user-configured command run after each batch that renamed something,
+// given the path of a JSON file describing the results
+
+use crate::file_ops::RenameReport;
+use crate::settings::data_dir;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// The command is stopped if it runs longer than this
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Results files kept in the data folder; older ones are removed
+const KEPT_RESULTS: usize = 20;
+const RESULTS_PREFIX: &str = "renamed-";
+
+// Writes the results file and runs the command on it, waiting at most HOOK_TIMEOUT. Blocks
+// while the command runs, so the app calls it from a thread of its own and carries on; its
+// output is discarded.
+pub fn run_post_rename_hook(command: &str, report: &RenameReport) -> Result<()> {
+    let results = write_results(report)?;
+    let mut child = shell(command, &results)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Could not start the post-rename hook \"{}\"", command))?;
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                bail!("The post-rename hook exited with {}", status);
+            }
+            return Ok(());
+        }
+        if started.elapsed() >= HOOK_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!(
+                "The post-rename hook was stopped after {} seconds",
+                HOOK_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// The results path goes in as "$1", so it's never parsed as shell syntax
+#[cfg(not(windows))]
+fn shell(command: &str, results: &Path) -> Command {
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(format!("{} \"$1\"", command))
+        .arg("sh")
+        .arg(results);
+    shell
+}
+
+// cmd parses its own command line, so it's passed through as written; Windows paths can't
+// contain quotes
+#[cfg(windows)]
+fn shell(command: &str, results: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut shell = Command::new("cmd");
+    shell
+        .arg("/S")
+        .arg("/C")
+        .raw_arg(command_line(command, results))
+        .creation_flags(CREATE_NO_WINDOW);
+    shell
+}
+
+// With /S, cmd drops the first and last quote of the line and runs the rest as typed, so a
+// command starting with a quoted program path keeps its own quotes
+#[cfg(windows)]
+fn command_line(command: &str, results: &Path) -> String {
+    format!("\"{} \"{}\"\"", command, results.display())
+}
+
+// Saves the batch as JSON in the data folder's "hooks" subfolder, pruning old files
+fn write_results(report: &RenameReport) -> Result<PathBuf> {
+    let dir = data_dir()
+        .context("No data folder on this system")?
+        .join("hooks");
+    fs::create_dir_all(&dir).with_context(|| format!("Could not create {}", dir.display()))?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = dir.join(format!("{}{}.json", RESULTS_PREFIX, stamp));
+    fs::write(&path, results_json(report)?)
+        .with_context(|| format!("Could not write {}", path.display()))?;
+
+    // Millisecond stamps of the same width sort by age
+    let mut old: Vec<PathBuf> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with(RESULTS_PREFIX))
+                .map(|e| e.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    old.sort();
+    old.truncate(old.len().saturating_sub(KEPT_RESULTS));
+    for file in old {
+        let _ = fs::remove_file(file);
+    }
+    Ok(path)
+}
+
+/// Contents of the results file: full paths, lossily converted to text
+#[derive(Serialize)]
+struct Results<'a> {
+    renamed: Vec<Moved<'a>>,
+    failed: Vec<Failed<'a>>,
+    verify_failed: Vec<VerifyFailed<'a>>,
+}
+
+#[derive(Serialize)]
+struct Moved<'a> {
+    from: Cow<'a, str>,
+    to: Cow<'a, str>,
+}
+
+#[derive(Serialize)]
+struct Failed<'a> {
+    path: Cow<'a, str>,
+    error: &'a str,
+}
+
+#[derive(Serialize)]
+struct VerifyFailed<'a> {
+    path: Cow<'a, str>,
+    reason: &'a str,
+}
+
+// {"renamed": [{"from", "to"}], "failed": [{"path", "error"}], "verify_failed": [{"path",
+// "reason"}]}
+fn results_json(report: &RenameReport) -> Result<String> {
+    let results = Results {
+        renamed: report
+            .locations
+            .iter()
+            .filter(|(from, to)| from != to)
+            .map(|(from, to)| Moved {
+                from: from.to_string_lossy(),
+                to: to.to_string_lossy(),
+            })
+            .collect(),
+        failed: report
+            .failed
+            .iter()
+            .map(|f| Failed {
+                path: f.preview.original_path.to_string_lossy(),
+                error: &f.error,
+            })
+            .collect(),
+        verify_failed: report
+            .verify_failed
+            .iter()
+            .map(|v| VerifyFailed {
+                path: v.path.to_string_lossy(),
+                reason: &v.reason,
+            })
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&results)?;
+    json.push('\n');
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_ops::{FailedRename, VerifyFailure};
+    use crate::types::RenamePreview;
+    use std::sync::Arc;
+
+    #[test]
+    fn results_list_moves_failures_and_verify_failures() {
+        let preview = RenamePreview {
+            original_path: PathBuf::from("/x/say \"hi\".txt"),
+            original_name: Arc::new("say \"hi\".txt".to_string()),
+            new_name: "hi.txt".to_string(),
+            conflicts: Vec::new(),
+            invalid_reason: None,
+            match_count: 1,
+            edited: false,
+            warnings: Vec::new(),
+        };
+        let report = RenameReport {
+            renamed: 1,
+            locations: vec![
+                (PathBuf::from("/x/a.txt"), PathBuf::from("/x/b.txt")),
+                // Files that stayed put aren't listed as renamed
+                (preview.original_path.clone(), preview.original_path.clone()),
+            ],
+            failed: vec![FailedRename {
+                preview,
+                error: "Permission denied\n(left as is)".to_string(),
+            }],
+            verify_failed: vec![VerifyFailure {
+                path: PathBuf::from(r"C:\x\b.txt"),
+                reason: "missing".to_string(),
+            }],
+            ..RenameReport::default()
+        };
+        let json = results_json(&report).expect("results");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "renamed": [{"from": "/x/a.txt", "to": "/x/b.txt"}],
+                "failed": [{"path": "/x/say \"hi\".txt", "error": "Permission denied\n(left as is)"}],
+                "verify_failed": [{"path": r"C:\x\b.txt", "reason": "missing"}],
+            })
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn quoted_program_paths_keep_their_quotes() {
+        let line = command_line(
+            r#""C:\Program Files\Tool\tool.exe" --sync"#,
+            Path::new(r"C:\Users\me\hooks\renamed-1.json"),
+        );
+        assert_eq!(
+            line,
+            r#"""C:\Program Files\Tool\tool.exe" --sync "C:\Users\me\hooks\renamed-1.json"""#
+        );
+
+        // cmd /S strips just the outer pair, leaving the line as the user wrote it
+        let inner = &line[1..line.len() - 1];
+        assert!(inner.starts_with(r#""C:\Program Files\Tool\tool.exe""#));
+    }
+}
@@ -0,0 +1,8 @@
+// Rename engine shared by the application and the benchmarks: scanning, sorting, renaming
+// and the naming strategies, with no GUI state
+
+pub mod file_ops;
+pub mod rename;
+pub mod security;
+pub mod theme;
+pub mod types;
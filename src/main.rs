@@ -4,20 +4,121 @@
 #![windows_subsystem = "windows"]
 
 mod app;
-mod file_ops;
-mod rename;
-mod security;
+mod hook;
+mod notify;
 mod settings;
-mod theme;
-mod types;
 
 use app::FileRenamePlus;
+use file_rename_plus::{file_ops, rename, security, theme, types};
 use iced::{application, Font, Settings, Size};
+use std::process::ExitCode;
 use theme::{WINDOW_HEIGHT, WINDOW_WIDTH};
 
-fn main() -> iced::Result {
+const HEADLESS_HINT: &str = "File Rename Plus is a graphical application and needs a desktop \
+session. Over SSH, enable X11 forwarding (ssh -X) or run it on the machine's own display.";
+
+fn main() -> ExitCode {
+    if let Err(reason) = find_display() {
+        eprintln!("{}", reason);
+        eprintln!("{}", HEADLESS_HINT);
+        return ExitCode::FAILURE;
+    }
+    match run_gui() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Could not start the window: {}", e);
+            eprintln!("{}", HEADLESS_HINT);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// X11 and Wayland advertise their display through the environment. iced panics when it
+// can't reach it (e.g. a stale DISPLAY over SSH), so the display is connected to first;
+// other platforms always have one when the user is logged in
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_display() -> Result<(), String> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY");
+    let x11 = std::env::var("DISPLAY").ok();
+    if wayland.is_none() && x11.is_none() {
+        return Err("No display found (DISPLAY and WAYLAND_DISPLAY are unset).".to_string());
+    }
+    if wayland.is_some_and(|name| wayland_reachable(name.as_ref())) {
+        return Ok(());
+    }
+    if x11.as_deref().is_some_and(x11_reachable) {
+        return Ok(());
+    }
+    Err(format!(
+        "Could not connect to the display (DISPLAY={}, WAYLAND_DISPLAY={}).",
+        x11.unwrap_or_default(),
+        std::env::var("WAYLAND_DISPLAY").unwrap_or_default()
+    ))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn find_display() -> Result<(), String> {
+    Ok(())
+}
+
+// A relative name is a socket in the runtime directory
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wayland_reachable(name: &std::path::Path) -> bool {
+    let path = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => std::path::Path::new(&dir).join(name),
+        None => name.to_path_buf(),
+    };
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+// Local displays listen on a socket (on Linux, possibly only the abstract one), remote ones
+// on TCP port 6000 + display number
+#[cfg(all(unix, not(target_os = "macos")))]
+fn x11_reachable(display: &str) -> bool {
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let Some((host, number)) = x11_display_address(display) else {
+        // Not a form we know how to reach; let the windowing library try it
+        return true;
+    };
+    if host.is_empty() || host == "unix" {
+        let socket = format!("/tmp/.X11-unix/X{}", number);
+        if UnixStream::connect(&socket).is_ok() {
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            if let Ok(addr) = std::os::unix::net::SocketAddr::from_abstract_name(&socket) {
+                return UnixStream::connect_addr(&addr).is_ok();
+            }
+        }
+        return false;
+    }
+    let Some(port) = u16::try_from(number).ok().and_then(|n| n.checked_add(6000)) else {
+        return false;
+    };
+    (host, port).to_socket_addrs().is_ok_and(|mut addrs| {
+        addrs.any(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(2)).is_ok())
+    })
+}
+
+// Splits "host:display.screen" into the host and display number
+#[cfg(all(unix, not(target_os = "macos")))]
+fn x11_display_address(display: &str) -> Option<(&str, u32)> {
+    if display.starts_with('/') {
+        return None;
+    }
+    let (host, rest) = display.rsplit_once(':')?;
+    let number = rest.split('.').next()?.parse().ok()?;
+    Some((host, number))
+}
+
+fn run_gui() -> iced::Result {
     application(
-        "File Rename Plus",
+        FileRenamePlus::title,
         FileRenamePlus::update,
         FileRenamePlus::view,
     )
@@ -32,3 +133,22 @@ fn main() -> iced::Result {
     .window_size(Size::new(WINDOW_WIDTH, WINDOW_HEIGHT))
     .run_with(FileRenamePlus::new)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn x11_display_names_give_host_and_number() {
+        assert_eq!(x11_display_address(":0"), Some(("", 0)));
+        assert_eq!(x11_display_address(":1.0"), Some(("", 1)));
+        assert_eq!(
+            x11_display_address("localhost:10.0"),
+            Some(("localhost", 10))
+        );
+        assert_eq!(x11_display_address("unix:2"), Some(("unix", 2)));
+        assert_eq!(x11_display_address("/tmp/launch-x/org.xquartz:0"), None);
+        assert_eq!(x11_display_address("nonsense"), None);
+    }
+}
@@ -0,0 +1,12 @@
+// Desktop notifications for operations that finish while the window is in the background
+
+use notify_rust::Notification;
+
+// Shows a desktop notification; platforms without a notification service are ignored
+pub fn notify(summary: &str, body: &str) {
+    let _ = Notification::new()
+        .appname("File Rename Plus")
+        .summary(summary)
+        .body(body)
+        .show();
+}
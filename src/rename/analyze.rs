@@ -0,0 +1,472 @@
+// Name analysis: what the listed names have in common, and renames that follow from it
+//
+// Only stems are compared; extensions (compound ones included) are left out, so removing
+// a shared prefix or suffix never touches them.
+
+use super::convention::convert;
+use super::split_extension;
+use crate::types::{FileEntry, NameConvention, DEFAULT_EXTENSION_GROUPS};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+// Separators counted in stems, in the order they are reported
+const SEPARATORS: [char; 4] = ['_', '-', ' ', '.'];
+
+// Shared prefixes or suffixes shorter than this are not worth suggesting to remove
+const MIN_AFFIX_CHARS: usize = 2;
+
+// Iteration template suggested when most listed files belong to a built-in extension group
+const GROUP_TEMPLATES: [(&str, &str); 4] = [
+    ("Images", "IMG_{n}"),
+    ("Documents", "doc_{n}"),
+    ("Video", "VID_{n}"),
+    ("Audio", "track_{n}"),
+];
+
+/// Observations about the listed names, with suggested renames
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Analysis {
+    pub file_count: usize,
+    // Start and end shared by every stem, never cut through a number
+    pub common_prefix: String,
+    pub common_suffix: String,
+    // Names containing a number, and the digit width of the last number in each
+    pub numbered: usize,
+    pub number_widths: BTreeSet<usize>,
+    // Stems already written in each convention; a stem such as `photo` counts for several
+    pub conventions: Vec<(NameConvention, usize)>,
+    // Some stems are lowercase, others uppercase or capitalized
+    pub mixed_case: bool,
+    // Stems using each separator
+    pub separators: Vec<(char, usize)>,
+    // Names equal to another in the same folder apart from letter case
+    pub case_duplicates: usize,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Suggested rename, worded for the user, with the options that carry it out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub action: SuggestedAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuggestedAction {
+    // Regex find/replace over the whole name
+    FindReplace { find: String, replace: String },
+    // Iteration numbering in list order
+    Renumber { template: String, padding: usize },
+    Convert(NameConvention),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LetterCase {
+    Lower,
+    Upper,
+    Mixed,
+}
+
+// Inspects the names of `files` and suggests renames for what they share
+pub fn analyze(files: &[FileEntry]) -> Analysis {
+    let stems: Vec<&str> = files
+        .iter()
+        .map(|f| split_extension(&f.name, true).0)
+        .collect();
+    let mut analysis = Analysis {
+        file_count: files.len(),
+        ..Analysis::default()
+    };
+    if stems.is_empty() {
+        return analysis;
+    }
+
+    // A single name shares everything with itself
+    if stems.len() > 1 {
+        analysis.common_prefix = common_prefix(&stems);
+        analysis.common_suffix = common_suffix(&stems);
+    }
+
+    for width in stems.iter().filter_map(|stem| last_number_width(stem)) {
+        analysis.numbered += 1;
+        analysis.number_widths.insert(width);
+    }
+
+    let lettered: Vec<&str> = stems
+        .iter()
+        .copied()
+        .filter(|stem| stem.chars().any(char::is_alphabetic))
+        .collect();
+    analysis.conventions = NameConvention::ALL
+        .into_iter()
+        .map(|c| (c, lettered.iter().filter(|s| convert(s, c) == **s).count()))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    let cases: BTreeSet<LetterCase> = lettered.iter().map(|s| letter_case(s)).collect();
+    analysis.mixed_case = cases.len() > 1;
+
+    analysis.separators = SEPARATORS
+        .into_iter()
+        .map(|sep| (sep, stems.iter().filter(|s| s.contains(sep)).count()))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+
+    let mut folded: HashMap<(Option<&Path>, String), usize> = HashMap::new();
+    for file in files {
+        *folded
+            .entry((file.path.parent(), file.name.to_lowercase()))
+            .or_default() += 1;
+    }
+    analysis.case_duplicates = folded.values().filter(|&&n| n > 1).sum();
+
+    analysis.suggestions = suggest(&analysis, &stems);
+    analysis
+}
+
+impl Analysis {
+    // One line per observation, for display
+    pub fn observations(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.common_prefix.is_empty() {
+            lines.push(format!("All names start with '{}'", self.common_prefix));
+        }
+        if !self.common_suffix.is_empty() {
+            lines.push(format!(
+                "All names end with '{}' before the extension",
+                self.common_suffix
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.number_widths.first(), self.number_widths.last()) {
+            let widths = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+            lines.push(format!(
+                "{} of {} name(s) contain a number, {} digit(s) wide",
+                self.numbered, self.file_count, widths
+            ));
+        }
+        if self.mixed_case {
+            lines.push("Letter case varies between names".to_string());
+        }
+        if let Some((convention, count)) = self.most_followed_convention() {
+            lines.push(format!(
+                "{} of {} name(s) already follow {}",
+                count, self.file_count, convention
+            ));
+        }
+        if !self.separators.is_empty() {
+            let used: Vec<String> = self
+                .separators
+                .iter()
+                .map(|(sep, count)| format!("{} in {}", separator_label(*sep), count))
+                .collect();
+            lines.push(format!("Separators: {}", used.join(", ")));
+        }
+        if self.case_duplicates > 0 {
+            lines.push(format!(
+                "{} name(s) differ only in letter case from another in the same folder; \
+                 they clash on Windows and macOS",
+                self.case_duplicates
+            ));
+        }
+        if lines.is_empty() {
+            lines.push("Nothing in common between the names".to_string());
+        }
+        lines
+    }
+
+    // Convention followed by the most stems; the first in ALL order wins a tie
+    fn most_followed_convention(&self) -> Option<(NameConvention, usize)> {
+        self.conventions
+            .iter()
+            .copied()
+            .rev()
+            .max_by_key(|&(_, count)| count)
+    }
+}
+
+fn suggest(analysis: &Analysis, stems: &[&str]) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    let prefix = analysis.common_prefix.as_str();
+    let suffix = analysis.common_suffix.as_str();
+    // Removing an affix must leave something of every stem
+    let removable = |affix: &str| {
+        affix.trim().chars().count() >= MIN_AFFIX_CHARS
+            && stems.iter().all(|s| s.len() > affix.len())
+    };
+
+    if removable(prefix) {
+        suggestions.push(Suggestion {
+            message: format!("All files share the prefix '{}'. Remove it?", prefix),
+            action: SuggestedAction::FindReplace {
+                // Separators left at the start go too
+                find: format!("^{}[-_. ]*", regex::escape(prefix)),
+                replace: String::new(),
+            },
+        });
+    }
+    if removable(suffix) {
+        suggestions.push(Suggestion {
+            message: format!("All files share the suffix '{}'. Remove it?", suffix),
+            action: SuggestedAction::FindReplace {
+                // The suffix sits before the extension, which is put back; separators
+                // left at the end go too
+                find: format!(r"[-_. ]*{}(\.[^.]*)?$", regex::escape(suffix)),
+                replace: "$1".to_string(),
+            },
+        });
+    }
+
+    // `IMG_1`, `IMG_10`, `IMG_2`: same prefix, then numbers of uneven width
+    let max_width = analysis.number_widths.last().copied().unwrap_or(0);
+    let numbers_only = stems.iter().all(|s| {
+        s.strip_prefix(prefix)
+            .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+    });
+    if analysis.number_widths.len() > 1 && numbers_only {
+        let template = format!("{}{{n}}", prefix);
+        suggestions.push(Suggestion {
+            message: format!(
+                "Numbers have uneven widths. Renumber as '{}' padded to {} digits so they sort evenly?",
+                template, max_width
+            ),
+            action: SuggestedAction::Renumber {
+                template,
+                padding: max_width,
+            },
+        });
+    }
+
+    if analysis.mixed_case {
+        let convention = analysis
+            .most_followed_convention()
+            .map_or(NameConvention::default(), |(c, _)| c);
+        suggestions.push(Suggestion {
+            message: format!(
+                "Letter case varies between names. Convert them all to {}?",
+                convention
+            ),
+            action: SuggestedAction::Convert(convention),
+        });
+    }
+
+    // Spaces alongside another separator: switch the spaces to the one used most
+    let count_of = |sep: char| {
+        analysis
+            .separators
+            .iter()
+            .find(|(s, _)| *s == sep)
+            .map_or(0, |(_, n)| *n)
+    };
+    let other = ['_', '-'].into_iter().max_by_key(|&sep| count_of(sep));
+    if let Some(other) = other.filter(|&sep| count_of(sep) > 0 && count_of(' ') > 0) {
+        suggestions.push(Suggestion {
+            message: format!(
+                "Names mix spaces and '{}'. Replace spaces with '{}'?",
+                other, other
+            ),
+            action: SuggestedAction::FindReplace {
+                find: " +".to_string(),
+                replace: other.to_string(),
+            },
+        });
+    }
+
+    suggestions
+}
+
+// Template suited to the kind of file making up more than half of the list, judged by
+// extension against the built-in groups
+pub fn suggested_template(files: &[FileEntry]) -> Option<&'static str> {
+    let mut histogram: HashMap<String, usize> = HashMap::new();
+    for file in files {
+        if let Some(ext) = file.path.extension() {
+            *histogram
+                .entry(ext.to_string_lossy().to_lowercase())
+                .or_default() += 1;
+        }
+    }
+    let (name, count) = DEFAULT_EXTENSION_GROUPS
+        .iter()
+        .map(|(name, extensions)| {
+            let count: usize = extensions
+                .iter()
+                .filter_map(|ext| histogram.get(*ext))
+                .sum();
+            (*name, count)
+        })
+        .max_by_key(|&(_, count)| count)?;
+    if count * 2 <= files.len() {
+        return None;
+    }
+    GROUP_TEMPLATES
+        .iter()
+        .find(|(group, _)| *group == name)
+        .map(|(_, template)| *template)
+}
+
+// Longest start shared by every stem, backed off so it doesn't end inside a number
+fn common_prefix(stems: &[&str]) -> String {
+    let first = stems[0];
+    let mut len = stems[1..].iter().fold(first.len(), |len, stem| {
+        first[..len]
+            .char_indices()
+            .zip(stem.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(stem.len()), |((i, _), _)| i)
+    });
+    let splits_number = |len: usize| {
+        first[..len].ends_with(|c: char| c.is_ascii_digit())
+            && stems
+                .iter()
+                .any(|s| s[len..].starts_with(|c: char| c.is_ascii_digit()))
+    };
+    while len > 0 && splits_number(len) {
+        len -= 1;
+    }
+    first[..len].to_string()
+}
+
+// Longest end shared by every stem, moved forward so it doesn't start inside a number
+fn common_suffix(stems: &[&str]) -> String {
+    let first = stems[0];
+    let mut start = stems[1..].iter().fold(0, |start, stem| {
+        let shared: usize = first[start..]
+            .chars()
+            .rev()
+            .zip(stem.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        first.len() - shared
+    });
+    let splits_number = |start: usize| {
+        let suffix = &first[start..];
+        suffix.starts_with(|c: char| c.is_ascii_digit())
+            && stems
+                .iter()
+                .any(|s| s[..s.len() - suffix.len()].ends_with(|c: char| c.is_ascii_digit()))
+    };
+    while start < first.len() && splits_number(start) {
+        start += 1;
+    }
+    first[start..].to_string()
+}
+
+// Digit count of the last run of ASCII digits in the stem
+fn last_number_width(stem: &str) -> Option<usize> {
+    let trimmed = stem.trim_end_matches(|c: char| !c.is_ascii_digit());
+    let width = trimmed.len() - trimmed.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    (width > 0).then_some(width)
+}
+
+fn letter_case(stem: &str) -> LetterCase {
+    let letters = stem.chars().filter(|c| c.is_alphabetic());
+    let (mut lower, mut upper) = (false, false);
+    for c in letters {
+        lower |= c.is_lowercase();
+        upper |= c.is_uppercase();
+    }
+    match (lower, upper) {
+        (true, false) => LetterCase::Lower,
+        (false, true) => LetterCase::Upper,
+        _ => LetterCase::Mixed,
+    }
+}
+
+fn separator_label(sep: char) -> String {
+    match sep {
+        ' ' => "space".to_string(),
+        _ => format!("'{}'", sep),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rename::{apply_find_replace, FindOptions};
+    use std::sync::Arc;
+
+    fn entry(folder: &Path, name: &str) -> FileEntry {
+        FileEntry {
+            path: folder.join(name),
+            name: Arc::new(name.to_string()),
+            identity: None,
+            pinned: false,
+            added: 0,
+        }
+    }
+
+    #[test]
+    fn analysis_suggests_removing_the_shared_prefix_and_evening_out_numbers() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "IMG_1.jpg"),
+            entry(folder, "IMG_10.jpg"),
+            entry(folder, "IMG_2.jpg"),
+        ];
+        let analysis = analyze(&files);
+        assert_eq!(analysis.file_count, 3);
+        assert_eq!(analysis.common_prefix, "IMG_");
+        assert_eq!(analysis.common_suffix, "");
+        assert_eq!(analysis.numbered, 3);
+        assert_eq!(analysis.number_widths, BTreeSet::from([1, 2]));
+        assert_eq!(analysis.separators, [('_', 3)]);
+        assert!(!analysis.mixed_case);
+
+        let actions: Vec<&SuggestedAction> =
+            analysis.suggestions.iter().map(|s| &s.action).collect();
+        assert_eq!(
+            actions,
+            [
+                &SuggestedAction::FindReplace {
+                    find: "^IMG_[-_. ]*".to_string(),
+                    replace: String::new(),
+                },
+                &SuggestedAction::Renumber {
+                    template: "IMG_{n}".to_string(),
+                    padding: 2,
+                },
+            ]
+        );
+
+        // The suggested find/replace carries out what it says
+        let SuggestedAction::FindReplace { find, replace } = actions[0] else {
+            unreachable!()
+        };
+        let options = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let previews =
+            apply_find_replace(&files, find, replace, "", options, &HashMap::new(), None)
+                .expect("preview");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["1.jpg", "10.jpg", "2.jpg"]);
+    }
+
+    #[test]
+    fn analysis_reports_mixed_case_and_case_only_duplicates() {
+        let files = [
+            entry(Path::new("/props/a"), "Report Final.doc"),
+            entry(Path::new("/props/a"), "report final.doc"),
+            entry(Path::new("/props/b"), "REPORT_FINAL.doc"),
+        ];
+        let analysis = analyze(&files);
+        assert!(analysis.mixed_case);
+        // Only the pair in the same folder clashes
+        assert_eq!(analysis.case_duplicates, 2);
+        assert_eq!(analysis.separators, [('_', 1), (' ', 2)]);
+        assert!(analysis
+            .suggestions
+            .iter()
+            .any(|s| matches!(s.action, SuggestedAction::Convert(_))));
+        assert!(analysis.suggestions.iter().any(|s| s.action
+            == SuggestedAction::FindReplace {
+                find: " +".to_string(),
+                replace: "_".to_string(),
+            }));
+    }
+}
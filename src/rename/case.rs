@@ -0,0 +1,94 @@
+// Locale-aware case handling for case-insensitive matching and lowercasing
+//
+// Unicode rules use simple case folding (as the regex crate does): `i`/`I` match each other and
+// the dotted `İ` / dotless `ı` only match themselves. Turkic rules pair `i` with `İ` and `ı`
+// with `I` instead, so `FILE_I` matches `fıle_ı` but not `file_i`.
+
+use crate::types::CaseLocale;
+use regex::{Regex, RegexBuilder};
+
+// Builds a case-insensitive matcher for a literal pattern under the given locale
+pub fn literal_matcher(pattern: &str, locale: CaseLocale) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&literal_source(pattern, locale))
+        .case_insensitive(true)
+        .build()
+}
+
+// Regex source matching the literal when compiled case-insensitively under the locale
+pub fn literal_source(pattern: &str, locale: CaseLocale) -> String {
+    match locale {
+        CaseLocale::Unicode => regex::escape(pattern),
+        CaseLocale::Turkic => pattern.chars().map(turkic_class).collect(),
+    }
+}
+
+// Lowercases text under the locale; Turkic rules map `I` to `ı` and `İ` to `i`
+pub fn to_lowercase(text: &str, locale: CaseLocale) -> String {
+    match locale {
+        CaseLocale::Unicode => text.to_lowercase(),
+        CaseLocale::Turkic => text
+            .chars()
+            .map(|c| match c {
+                'I' => 'ı'.to_string(),
+                'İ' => 'i'.to_string(),
+                _ => c.to_lowercase().collect(),
+            })
+            .collect(),
+    }
+}
+
+// Dotted and dotless i pairs are matched case-sensitively as explicit classes
+fn turkic_class(c: char) -> String {
+    match c {
+        'i' | 'İ' => "(?-i:[iİ])".to_string(),
+        'ı' | 'I' => "(?-i:[ıI])".to_string(),
+        _ => regex::escape(c.encode_utf8(&mut [0; 4])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turkic_lowercasing_keeps_the_dot() {
+        assert_eq!(to_lowercase("FILE_I.TXT", CaseLocale::Turkic), "fıle_ı.txt");
+        assert_eq!(to_lowercase("İSTANBUL", CaseLocale::Turkic), "istanbul");
+        assert_eq!(
+            to_lowercase("FILE_I.TXT", CaseLocale::Unicode),
+            "file_i.txt"
+        );
+    }
+
+    #[test]
+    fn dotted_and_dotless_i_match_their_own_pair() {
+        let turkic = literal_matcher("FILE_I", CaseLocale::Turkic).expect("matcher");
+        assert!(turkic.is_match("fıle_ı"));
+        assert!(turkic.is_match("FILE_I"));
+        assert!(!turkic.is_match("file_i"));
+        let dotted = literal_matcher("istanbul", CaseLocale::Turkic).expect("matcher");
+        assert!(dotted.is_match("İSTANBUL"));
+        assert!(!dotted.is_match("ISTANBUL"));
+
+        let unicode = literal_matcher("FILE_I", CaseLocale::Unicode).expect("matcher");
+        assert!(unicode.is_match("file_i"));
+        assert!(!unicode.is_match("fıle_ı"));
+    }
+
+    #[test]
+    fn other_characters_are_escaped() {
+        let matcher = literal_matcher("a.b (1)", CaseLocale::Turkic).expect("matcher");
+        assert!(matcher.is_match("A.B (1)"));
+        assert!(!matcher.is_match("axb (1)"));
+    }
+
+    #[test]
+    fn turkic_round_trips_through_matching() {
+        // Lowercasing under the locale gives text its own matcher still finds
+        for text in ["İZMİR", "IĞDIR", "Kırşehir"] {
+            let lowered = to_lowercase(text, CaseLocale::Turkic);
+            let matcher = literal_matcher(text, CaseLocale::Turkic).expect("matcher");
+            assert!(matcher.is_match(&lowered), "{} -> {}", text, lowered);
+        }
+    }
+}
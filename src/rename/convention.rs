@@ -0,0 +1,114 @@
+// Naming convention conversion: split a stem into words, then rejoin them
+//
+// Any character that isn't a letter or digit separates words and is dropped. Camel humps
+// split too, keeping acronyms whole: `HTTPServer` is `HTTP` + `Server`. Digits stay with
+// the word before them, so `file2Name` is `file2` + `Name`.
+
+use crate::types::NameConvention;
+
+// Splits a stem into words at separators and camel-case humps
+pub fn split_words(stem: &str) -> Vec<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // `myName`, `file2Name`, and the last capital of an acronym in `HTTPServer`
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+// Rewrites a stem in the given convention; stems without any words are kept as-is
+pub fn convert(stem: &str, convention: NameConvention) -> String {
+    let words = split_words(stem);
+    if words.is_empty() {
+        return stem.to_string();
+    }
+    match convention {
+        NameConvention::Snake => lowercase_joined(&words, "_"),
+        NameConvention::Kebab | NameConvention::WebSafe => lowercase_joined(&words, "-"),
+        NameConvention::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 {
+                    w.to_lowercase()
+                } else {
+                    capitalize(w)
+                }
+            })
+            .collect(),
+        NameConvention::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+    }
+}
+
+fn lowercase_joined(words: &[String], separator: &str) -> String {
+    words
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_split_at_separators_and_humps() {
+        assert_eq!(split_words("myVariableName"), ["my", "Variable", "Name"]);
+        assert_eq!(split_words("my-file_name"), ["my", "file", "name"]);
+        assert_eq!(split_words("HTTPServer"), ["HTTP", "Server"]);
+        assert_eq!(
+            split_words("parseHTTPResponse"),
+            ["parse", "HTTP", "Response"]
+        );
+        assert_eq!(split_words("file2Name"), ["file2", "Name"]);
+        assert_eq!(split_words("  --  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn each_convention_rejoins_the_words() {
+        let cases = [
+            (NameConvention::Snake, "my_variable_name", "http_server"),
+            (NameConvention::Kebab, "my-variable-name", "http-server"),
+            (NameConvention::Camel, "myVariableName", "httpServer"),
+            (NameConvention::Pascal, "MyVariableName", "HttpServer"),
+        ];
+        for (convention, plain, acronym) in cases {
+            assert_eq!(convert("myVariableName", convention), plain);
+            assert_eq!(convert("my-variable_name", convention), plain);
+            assert_eq!(convert("HTTPServer", convention), acronym);
+        }
+        // Nothing to split, nothing to rewrite
+        assert_eq!(convert("___", NameConvention::Camel), "___");
+    }
+}
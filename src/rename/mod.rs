@@ -1,132 +1,2154 @@
-// Rename strategies: find/replace and iteration numbering
+// Rename strategies: find/replace, iteration numbering and naming conventions
 
+mod analyze;
+mod case;
+mod convention;
+
+pub use analyze::{analyze, suggested_template, Analysis, SuggestedAction};
+
+use crate::file_ops::{is_reserved_name, matches_extensions};
 use crate::theme::MAX_PATTERN_LENGTH;
-use crate::types::{FileEntry, RenamePreview};
-use anyhow::Result;
-use regex::RegexBuilder;
-use std::collections::HashMap;
+use crate::types::{
+    CaseLocale, ConflictReason, Counter, ExtensionTemplate, FileEntry, NameConvention, NameWarning,
+    NumberGrouping, RenamePreview, RenameRule,
+};
+use anyhow::{Context, Result};
+use case::{literal_matcher, literal_source, to_lowercase};
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, Local};
+use regex::{Captures, Regex, RegexBuilder};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+// Multi-part extensions kept whole when compound extension handling is on
+const COMPOUND_EXTENSIONS: [&str; 3] = [".tar.gz", ".tar.bz2", ".tar.xz"];
+
+// Duplicate markers added by file managers when copying, matched before the extension:
+// Windows " - Copy", " - Copy (2)" and its translations, macOS " copy", " copy 2", and
+// download-style " (1)". The stem is captured in $1 and the extension in $2.
+pub const COPY_MARKER_PATTERN: &str = concat!(
+    r"^(.+?)(?:",
+    r"\s+(?:-\s+)?(?i:copy|kopie|copie|copia|kopia|kopya|копия|コピー|副本|복사본)",
+    r"(?:\s*\(\d+\)|\s+\d+)?",
+    r"|\s*\(\d+\)",
+    r")+(\.[^.\s]*)?$"
+);
+pub const COPY_MARKER_REPLACEMENT: &str = "$1$2";
+
+// Replacement token standing for the name of the folder holding each file
+pub const FOLDER_TOKEN: &str = "{folder}";
+
+// Replacement token for each file's creation date in local time; `{created:%Y%m%d_%H%M}`
+// picks its own strftime-style format
+const CREATED_TOKEN_PATTERN: &str = r"\{created(?::([^}]*))?\}";
+const CREATED_FORMAT: &str = "%Y-%m-%d";
 
-// Applies find/replace pattern to filenames
+// Replacement token for the SHA-256 of each file's contents in hex; `{hash8}` keeps the
+// first 8 characters
+const HASH_TOKEN_PATTERN: &str = r"\{hash(\d*)\}";
+const HASH_HEX_LEN: usize = 64;
+
+// Longest name, in bytes, that ext4, APFS and most other filesystems accept
+const MAX_NAME_BYTES: usize = 255;
+
+/// Preview generation exceeded its time budget
+#[derive(Debug, thiserror::Error)]
+#[error("Pattern too slow — preview aborted")]
+pub struct PreviewTimeout;
+
+/// Find pattern or replacement that can't be used; returned inside `anyhow::Error` like
+/// `PreviewTimeout`
+#[derive(Debug, thiserror::Error)]
+pub enum PatternError {
+    #[error("Pattern too long (max {max} chars)")]
+    TooLong { max: usize },
+    #[error("Invalid regex: {error}")]
+    InvalidRegex {
+        pattern: String,
+        error: regex::Error,
+    },
+    #[error("Invalid date format in {{created:{0}}}")]
+    InvalidDateFormat(String),
+    #[error("Hash length in {{hash{0}}} must be from 1 to 64")]
+    InvalidHashLength(String),
+    #[error("Invalid pattern: {error}")]
+    InvalidLiteral {
+        pattern: String,
+        error: regex::Error,
+    },
+}
+
+/// Iteration template that can't number the files
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("Template must contain {{n}} placeholder or a counter like {{n1}}")]
+    MissingNumber,
+    #[error("Counter {{n{0}}} isn't defined")]
+    UndefinedCounter(String),
+    #[error("{{g}} needs the numbering to restart per folder or per extension")]
+    GroupNeedsGrouping,
+    #[error("Keeping numbered names only works with {{n}}, not counters or {{g}}")]
+    KeepNumberedUnsupported,
+}
+
+/// Placeholders and problems found in an iteration template while it's typed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateLint {
+    // Placeholders that will be filled in, each once, in template order
+    pub placeholders: Vec<String>,
+    pub problems: Vec<TemplateProblem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateProblem {
+    // 1-based character column the problem starts at; None for the template as a whole
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+// Checks a template without numbering anything: which placeholders it uses, and those that
+// are unknown, malformed or can't be filled in, plus characters no file name can hold.
+// Anything flagged here would otherwise stop the preview or end up in the names as text.
+pub fn lint_template(template: &str, counters: usize, grouping: NumberGrouping) -> TemplateLint {
+    let chars: Vec<char> = template.chars().collect();
+    let mut lint = TemplateLint::default();
+    let mut problem = |column: Option<usize>, message: String| {
+        lint.problems.push(TemplateProblem { column, message })
+    };
+    let mut placeholders: Vec<String> = Vec::new();
+    let mut has_number = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let column = Some(i + 1);
+        match chars[i] {
+            '{' => {
+                let end = chars[i + 1..].iter().position(|&c| c == '{' || c == '}');
+                let Some(len) = end.filter(|&len| chars[i + 1 + len] == '}') else {
+                    let hint = match chars.get(i + 1) {
+                        Some('n' | 'N') => "; did you mean {n}?",
+                        Some('g' | 'G') => "; did you mean {g}?",
+                        _ => "",
+                    };
+                    problem(column, format!("\"{{\" is never closed{}", hint));
+                    i += 1;
+                    continue;
+                };
+                let inner: String = chars[i + 1..i + 1 + len].iter().collect();
+                let token = format!("{{{}}}", inner);
+                match placeholder_kind(&inner) {
+                    Some(Placeholder::Number(index)) => {
+                        has_number = true;
+                        let defined = index.is_empty()
+                            || index
+                                .parse()
+                                .is_ok_and(|k: usize| (1..=counters).contains(&k));
+                        if !defined {
+                            problem(column, format!("Counter {} isn't defined", token));
+                        }
+                    }
+                    Some(Placeholder::Group) if grouping == NumberGrouping::None => problem(
+                        column,
+                        format!("{} needs counting per folder or per extension", token),
+                    ),
+                    Some(Placeholder::Group) => {}
+                    Some(Placeholder::Hash(len))
+                        if !len.is_empty()
+                            && !len
+                                .parse()
+                                .is_ok_and(|k: usize| (1..=HASH_HEX_LEN).contains(&k)) =>
+                    {
+                        problem(
+                            column,
+                            PatternError::InvalidHashLength(len.to_string()).to_string(),
+                        )
+                    }
+                    Some(Placeholder::Hash(_)) => {}
+                    None => match placeholder_kind(&inner.to_lowercase()) {
+                        Some(_) => problem(
+                            column,
+                            format!(
+                                "{} isn't a placeholder; did you mean {}?",
+                                token,
+                                token.to_lowercase()
+                            ),
+                        ),
+                        None => problem(
+                            column,
+                            format!(
+                                "Unknown placeholder {} would stay in the names as text",
+                                token
+                            ),
+                        ),
+                    },
+                }
+                if placeholder_kind(&inner).is_some() && !placeholders.contains(&token) {
+                    placeholders.push(token);
+                }
+                i += len + 2;
+                continue;
+            }
+            '}' => problem(column, "\"}\" has no opening \"{\"".to_string()),
+            c if std::path::is_separator(c) => problem(
+                column,
+                format!("\"{}\" separates folders and can't be in a name", c),
+            ),
+            _ => {}
+        }
+        i += 1;
+    }
+    if !has_number {
+        problem(None, TemplateError::MissingNumber.to_string());
+    }
+    if template.starts_with('.') {
+        problem(
+            Some(1),
+            "Names would start with a dot, leaving no name before the extension".to_string(),
+        );
+    }
+    if is_reserved_name(template) {
+        problem(
+            Some(1),
+            "Names would use the prefix reserved for temporary files".to_string(),
+        );
+    }
+    lint.placeholders = placeholders;
+    lint
+}
+
+enum Placeholder<'a> {
+    // {n}, or a counter such as {n1} with its index
+    Number(&'a str),
+    // {g}, or {g:2} with the group number's padding
+    Group,
+    // {hash}, or {hash8} with the number of characters kept
+    Hash(&'a str),
+}
+
+// What the text between a pair of braces stands for, None if it isn't a placeholder
+fn placeholder_kind(inner: &str) -> Option<Placeholder<'_>> {
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if let Some(len) = inner.strip_prefix("hash") {
+        return all_digits(len).then_some(Placeholder::Hash(len));
+    }
+    if let Some(index) = inner.strip_prefix('n') {
+        return all_digits(index).then_some(Placeholder::Number(index));
+    }
+    match inner.strip_prefix('g')? {
+        "" => Some(Placeholder::Group),
+        padding => padding
+            .strip_prefix(':')
+            .filter(|p| (1..=2).contains(&p.len()) && all_digits(p))
+            .map(|_| Placeholder::Group),
+    }
+}
+
+// Fails once the optional deadline has passed
+fn check_deadline(deadline: Option<Instant>) -> Result<()> {
+    match deadline {
+        Some(d) if Instant::now() >= d => Err(PreviewTimeout.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Matching and output options for find/replace
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FindOptions {
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub case_locale: CaseLocale,
+    // Only matches standing alone between word boundaries count
+    pub whole_word: bool,
+    // Lowercases the stem of every resulting name after replacing; the extension (compound
+    // ones such as `.TAR.GZ` included) keeps its case
+    pub lowercase: bool,
+}
+
+// Applies find/replace pattern to filenames, giving up after `deadline`. Names that already
+// contain `unless_contains` (when set) are left alone, so adding a tag such as "[DRAFT]"
+// can be run again without doubling it. `hashes` holds the content hashes for {hash},
+// keyed by path; files without one are left out when the replacement uses it.
 pub fn apply_find_replace(
     files: &[FileEntry],
     pattern: &str,
     replacement: &str,
-    use_regex: bool,
-    case_sensitive: bool,
+    unless_contains: &str,
+    options: FindOptions,
+    hashes: &HashMap<PathBuf, String>,
+    deadline: Option<Instant>,
 ) -> Result<Vec<RenamePreview>> {
     if pattern.is_empty() {
         return Ok(Vec::new());
     }
 
+    let matcher = find_matcher(pattern, options)?;
+    let dates = created_matcher(replacement)?;
+    let hash_tokens = hash_matcher(replacement)?.map(|tokens| (tokens, hashes));
+
+    let unless_contains = if options.case_sensitive {
+        unless_contains.to_string()
+    } else {
+        to_lowercase(unless_contains, options.case_locale)
+    };
+    let mut previews = Vec::new();
+    for file in files {
+        check_deadline(deadline)?;
+        if !unless_contains.is_empty() && contains_token(&file.name, &unless_contains, options) {
+            continue;
+        }
+        let Some((replacement, date_fallback)) = replacement_for(
+            replacement,
+            &file.path,
+            options.use_regex,
+            dates.as_ref(),
+            hash_tokens
+                .as_ref()
+                .map(|(tokens, hashes)| (tokens, *hashes)),
+        ) else {
+            continue;
+        };
+        let replacement = replacement.as_ref();
+        let (mut new_name, match_count) = match &matcher {
+            Some(regex) => replace_counting(regex, &file.name, replacement, options.use_regex),
+            None => (
+                file.name.replace(pattern, replacement),
+                file.name.matches(pattern).count(),
+            ),
+        };
+        if options.lowercase {
+            let (stem, extension) = split_extension(&new_name, true);
+            new_name = to_lowercase(stem, options.case_locale) + extension;
+        }
+        if new_name != file.name.as_str() {
+            previews.push(RenamePreview {
+                original_path: file.path.clone(),
+                original_name: Arc::clone(&file.name),
+                new_name,
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count,
+                edited: false,
+                warnings: if date_fallback {
+                    vec![NameWarning::ModifiedDateUsed]
+                } else {
+                    Vec::new()
+                },
+            });
+        }
+    }
+
+    detect_conflicts(&mut previews);
+    validate_names(&mut previews);
+    Ok(previews)
+}
+
+// Regex for the find pattern; None for plain case-sensitive search, which needs none
+fn find_matcher(pattern: &str, options: FindOptions) -> Result<Option<Regex>> {
     if pattern.len() > MAX_PATTERN_LENGTH {
-        anyhow::bail!("Pattern too long (max {} chars)", MAX_PATTERN_LENGTH);
+        return Err(PatternError::TooLong {
+            max: MAX_PATTERN_LENGTH,
+        }
+        .into());
     }
 
-    let mut previews = Vec::new();
+    // Plain case-sensitive search needs no regex at all
+    let matcher = if options.use_regex || options.whole_word {
+        let source = match (options.use_regex, options.case_sensitive) {
+            (true, _) if options.whole_word => format!(r"\b(?:{})\b", pattern),
+            (true, _) => pattern.to_string(),
+            (false, true) => format!(r"\b{}\b", regex::escape(pattern)),
+            (false, false) => {
+                format!(r"\b(?:{})\b", literal_source(pattern, options.case_locale))
+            }
+        };
+        Some(
+            RegexBuilder::new(&source)
+                .case_insensitive(!options.case_sensitive)
+                .size_limit(1024 * 1024)
+                .build()
+                .map_err(|error| PatternError::InvalidRegex {
+                    pattern: pattern.to_string(),
+                    error,
+                })?,
+        )
+    } else if !options.case_sensitive {
+        Some(
+            literal_matcher(pattern, options.case_locale).map_err(|error| {
+                PatternError::InvalidLiteral {
+                    pattern: pattern.to_string(),
+                    error,
+                }
+            })?,
+        )
+    } else {
+        None
+    };
+    Ok(matcher)
+}
+
+/// Name split into the parts each regex group captured, for checking a pattern's groups
+#[derive(Debug, Clone)]
+pub struct CaptureView {
+    // Consecutive parts of the name: `Some(0)` for matched text outside any group,
+    // `Some(i)` for text captured by group i (the innermost group wins), None elsewhere
+    pub parts: Vec<(String, Option<usize>)>,
+    // What `$1`, `$2`... captured in the first match; None for groups that took no part
+    pub groups: Vec<Option<String>>,
+}
 
-    if use_regex {
-        let regex = RegexBuilder::new(pattern)
-            .case_insensitive(!case_sensitive)
-            .size_limit(1024 * 1024)
-            .build()
-            .map_err(|e| anyhow::anyhow!("Invalid regex: {}", e))?;
-
-        for file in files {
-            let new_name = regex.replace_all(&file.name, replacement).to_string();
-            if new_name != file.name.as_str() {
-                previews.push(RenamePreview {
-                    original_path: file.path.clone(),
-                    original_name: Arc::clone(&file.name),
-                    new_name,
-                    has_conflict: false,
-                });
+// Shows what each group of the regex captures in the first `limit` names it matches
+pub fn capture_views(
+    files: &[FileEntry],
+    pattern: &str,
+    options: FindOptions,
+    limit: usize,
+) -> Result<Vec<CaptureView>> {
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(regex) = find_matcher(pattern, options)? else {
+        return Ok(Vec::new());
+    };
+    let mut views = Vec::new();
+    for file in files {
+        if views.len() == limit {
+            break;
+        }
+        let name = file.name.as_str();
+        let mut owner: Vec<Option<usize>> = vec![None; name.len()];
+        let mut groups = Vec::new();
+        for caps in regex.captures_iter(name) {
+            if groups.is_empty() {
+                groups = (1..caps.len())
+                    .map(|i| caps.get(i).map(|m| m.as_str().to_string()))
+                    .collect();
+            }
+            // A group nested in another always has the higher index, so it paints last
+            for (i, m) in caps.iter().enumerate() {
+                if let Some(m) = m {
+                    owner[m.range()].fill(Some(i));
+                }
             }
         }
-    } else {
-        for file in files {
-            let new_name = if case_sensitive {
-                file.name.replace(pattern, replacement)
-            } else {
-                replace_case_insensitive(&file.name, pattern, replacement)
-            };
-            if new_name != file.name.as_str() {
-                previews.push(RenamePreview {
-                    original_path: file.path.clone(),
-                    original_name: Arc::clone(&file.name),
-                    new_name,
-                    has_conflict: false,
-                });
+        if owner.iter().all(Option::is_none) {
+            continue;
+        }
+        let mut parts: Vec<(String, Option<usize>)> = Vec::new();
+        for (i, c) in name.char_indices() {
+            match parts.last_mut() {
+                Some((text, group)) if *group == owner[i] => text.push(c),
+                _ => parts.push((c.to_string(), owner[i])),
             }
         }
+        views.push(CaptureView { parts, groups });
     }
+    Ok(views)
+}
 
-    detect_conflicts(&mut previews);
-    Ok(previews)
+// Whether the name holds `token`, compared with the find options' case handling; `token`
+// is already lowercased when matching ignores case
+fn contains_token(name: &str, token: &str, options: FindOptions) -> bool {
+    if options.case_sensitive {
+        name.contains(token)
+    } else {
+        to_lowercase(name, options.case_locale).contains(token)
+    }
+}
+
+// Builds a regex matching any of the literals, each escaped. Longer literals come first
+// so overlapping alternatives such as "Copy of" and "Copy" prefer the longer match.
+pub fn alternation_pattern(
+    literals: &[String],
+    case_sensitive: bool,
+    locale: CaseLocale,
+) -> String {
+    let mut sorted: Vec<&String> = literals.iter().filter(|l| !l.is_empty()).collect();
+    sorted.sort_by_key(|l| Reverse(l.chars().count()));
+    sorted
+        .into_iter()
+        .map(|l| {
+            if case_sensitive {
+                regex::escape(l)
+            } else {
+                literal_source(l, locale)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("|")
 }
 
-// Applies sequential numbering using template with {n} placeholder
+/// Numbering options for iteration mode
+#[derive(Debug, Clone, Default)]
+pub struct NumberingOptions {
+    pub start_number: u32,
+    pub padding: usize,
+    // `{n1}` is the first entry, `{n2}` the second, and so on
+    pub counters: Vec<Counter>,
+    pub compound_extensions: bool,
+    // Hands numbers out from the last file to the first
+    pub reverse: bool,
+    // Restarts every counter for each folder or extension
+    pub grouping: NumberGrouping,
+    // Leaves names already following the template as they are, numbers included
+    pub keep_matching: bool,
+    // Widens `padding` to the digits of the largest `{n}` handed out, so every number in
+    // the batch has the same width
+    pub fit_padding: bool,
+    // Content hashes for {hash}, keyed by path; files without one are left out when the
+    // template uses it
+    pub hashes: Arc<HashMap<PathBuf, String>>,
+    // Compares names against `taken` lowercased, as listed by `existing_paths` with
+    // `fold_case`
+    pub fold_case: bool,
+}
+
+// Applies sequential numbering using a template with the {n} placeholder and any number of
+// {n1}, {n2}... counters. All of them advance together, one step per file: with {n1} from 1
+// by 1 and {n2} from 100 by 10, "{n1}_{n2}" gives 1_100, 2_110, 3_120...
+// Positions whose target path is in `taken` are skipped by every counter at once. With
+// `reverse`, numbers are handed out from the last file to the first while the preview
+// keeps list order.
+// With a grouping, counters restart for each folder or extension and `{g}` (or `{g:3}`,
+// padded to 3 digits) is the 1-based group number, in order of each group's first file:
+// "set{g}_img{n}" gives set1_img001, set1_img002, set2_img001...
+// With `keep_matching`, names that already follow the template keep their number and stay
+// unchanged; the other files take the numbers left free, filling gaps first.
+// With `fit_padding`, `{n}` is padded to at least the width of the largest number in the
+// batch, taken or kept numbers included: 1 to 120 with padding 1 gives 001...120, so the
+// names sort in numbering order. `{n1}`, `{n2}`... keep their own padding.
 pub fn apply_iteration_numbering(
     files: &[FileEntry],
     template: &str,
-    start_number: u32,
-    padding: usize,
+    options: &NumberingOptions,
+    taken: Option<&HashSet<PathBuf>>,
 ) -> Result<Vec<RenamePreview>> {
-    if !template.contains("{n}") {
-        anyhow::bail!("Template must contain {{n}} placeholder");
+    number_files(files, template, options, taken).map(|(previews, _)| previews)
+}
+
+// Same as `apply_iteration_numbering`, also returning the start number that carries the
+// sequence on in a later run: one past the last `{n}` handed out, in the longest group
+pub fn apply_iteration_numbering_continued(
+    files: &[FileEntry],
+    template: &str,
+    options: &NumberingOptions,
+    taken: Option<&HashSet<PathBuf>>,
+) -> Result<(Vec<RenamePreview>, u32)> {
+    number_files(files, template, options, taken)
+}
+
+fn number_files(
+    files: &[FileEntry],
+    template: &str,
+    options: &NumberingOptions,
+    taken: Option<&HashSet<PathBuf>>,
+) -> Result<(Vec<RenamePreview>, u32)> {
+    let mut numbered = number_files_padded(files, template, options, taken)?;
+    if !options.fit_padding {
+        return Ok(numbered);
+    }
+    // A wider number can change which names are taken or kept, and so the largest number;
+    // the width only grows, so this settles within a few runs
+    let mut options = options.clone();
+    loop {
+        let largest = numbered.1.saturating_sub(1).max(options.start_number);
+        let width = largest.to_string().len();
+        if width <= options.padding {
+            return Ok(numbered);
+        }
+        options.padding = width;
+        numbered = number_files_padded(files, template, &options, taken)?;
+    }
+}
+
+fn number_files_padded(
+    files: &[FileEntry],
+    template: &str,
+    options: &NumberingOptions,
+    taken: Option<&HashSet<PathBuf>>,
+) -> Result<(Vec<RenamePreview>, u32)> {
+    let tokens = Regex::new(r"\{(?:n(\d*)|g(?::(\d{1,2}))?)\}")?;
+    let mut has_token = false;
+    for caps in tokens.captures_iter(template) {
+        match caps.get(1) {
+            Some(index) => {
+                has_token = true;
+                let index = index.as_str();
+                if !index.is_empty() && counter_at(&options.counters, index).is_none() {
+                    return Err(TemplateError::UndefinedCounter(index.to_string()).into());
+                }
+            }
+            None if options.grouping == NumberGrouping::None => {
+                return Err(TemplateError::GroupNeedsGrouping.into())
+            }
+            None => {}
+        }
+    }
+    if !has_token {
+        return Err(TemplateError::MissingNumber.into());
+    }
+    let hash_tokens = hash_matcher(template)?;
+
+    let mut ordered: Vec<&FileEntry> = files
+        .iter()
+        .filter(|f| hash_tokens.is_none() || options.hashes.contains_key(&f.path))
+        .collect();
+    if options.reverse {
+        ordered.reverse();
+    }
+
+    // Numbers held by names that already follow the template, per group
+    let mut kept: HashMap<&Path, u32> = HashMap::new();
+    let mut used: HashMap<(Option<&Path>, String), HashSet<u32>> = HashMap::new();
+    if options.keep_matching {
+        let matcher = template_matcher(template, &tokens)?;
+        for file in &ordered {
+            if let Some(number) = template_number(&file.name, &matcher, template, &tokens, options)
+            {
+                kept.insert(&file.path, number);
+                used.entry(group_key(file, options))
+                    .or_default()
+                    .insert(number);
+            }
+        }
     }
 
     let mut previews = Vec::new();
+    let last_position = u64::from(u32::MAX - options.start_number);
+    // Group number and next position for each folder or extension
+    let mut groups: HashMap<(Option<&Path>, String), (usize, u64)> = HashMap::new();
 
-    for (index, file) in files.iter().enumerate() {
-        let number = start_number.saturating_add(index as u32);
-        let formatted_number = format!("{:0>width$}", number, width = padding);
-        let extension = file
-            .path
-            .extension()
-            .map(|e| format!(".{}", e.to_string_lossy()))
-            .unwrap_or_default();
-        let new_name = format!(
-            "{}{}",
-            template.replace("{n}", &formatted_number),
-            extension
-        );
+    for file in ordered {
+        let (_, extension) = split_extension(&file.name, options.compound_extensions);
+        let parent = file.path.parent().unwrap_or(&file.path);
+        let key = group_key(file, options);
+        let held = used.get(&key);
+        let next_group = groups.len() + 1;
+        let (group, position) = groups.entry(key).or_insert((next_group, 0));
+        if kept.contains_key(file.path.as_path()) {
+            previews.push(RenamePreview {
+                original_path: file.path.clone(),
+                original_name: Arc::clone(&file.name),
+                new_name: file.name.to_string(),
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count: 0,
+                edited: false,
+                warnings: Vec::new(),
+            });
+            continue;
+        }
+        let new_name = loop {
+            let stem = tokens.replace_all(template, |caps: &Captures| match caps.get(1) {
+                Some(index) => format_token(index.as_str(), *position, options),
+                None => {
+                    let width = caps.get(2).map_or(0, |w| w.as_str().parse().unwrap_or(0));
+                    format!("{:0>width$}", group, width = width)
+                }
+            });
+            let stem = match (&hash_tokens, options.hashes.get(&file.path)) {
+                (Some(hash_tokens), Some(hash)) => fill_hash(hash_tokens, &stem, hash),
+                _ => stem.into_owned(),
+            };
+            let candidate = format!("{}{}", stem, extension);
+            let number = options
+                .start_number
+                .saturating_add(u32::try_from(*position).unwrap_or(u32::MAX));
+            let is_taken = taken.is_some_and(|t| {
+                if options.fold_case {
+                    t.contains(&parent.join(candidate.to_lowercase()))
+                } else {
+                    t.contains(&parent.join(&candidate))
+                }
+            }) || held.is_some_and(|h| h.contains(&number));
+            let exhausted = *position >= last_position;
+            *position += 1;
+            if !is_taken || exhausted {
+                break candidate;
+            }
+        };
 
         previews.push(RenamePreview {
             original_path: file.path.clone(),
             original_name: Arc::clone(&file.name),
             new_name,
-            has_conflict: false,
+            conflicts: Vec::new(),
+            invalid_reason: None,
+            match_count: 0,
+            edited: false,
+            warnings: Vec::new(),
         });
     }
 
+    if options.reverse {
+        previews.reverse();
+    }
     detect_conflicts(&mut previews);
+    validate_names(&mut previews);
+    let handed_out = groups
+        .values()
+        .map(|&(_, position)| position)
+        .max()
+        .unwrap_or(0);
+    let next = u64::from(options.start_number).saturating_add(handed_out);
+    let after_kept = kept.values().map(|&n| u64::from(n) + 1).max().unwrap_or(0);
+    let next = next.max(after_kept);
+    Ok((previews, u32::try_from(next).unwrap_or(u32::MAX)))
+}
+
+// Folder or extension whose files share one sequence, per the grouping
+fn group_key<'a>(file: &'a FileEntry, options: &NumberingOptions) -> (Option<&'a Path>, String) {
+    match options.grouping {
+        NumberGrouping::None => (None, String::new()),
+        NumberGrouping::Folder => (file.path.parent(), String::new()),
+        NumberGrouping::Extension => {
+            let (_, extension) = split_extension(&file.name, options.compound_extensions);
+            (None, extension.to_lowercase())
+        }
+    }
+}
+
+// Regex matching stems written by the template with any number for `{n}`. Templates with
+// counters or `{g}` can't be matched.
+fn template_matcher(template: &str, tokens: &Regex) -> Result<Regex> {
+    if tokens
+        .captures_iter(template)
+        .any(|caps| caps.get(1).is_none_or(|index| !index.as_str().is_empty()))
+    {
+        return Err(TemplateError::KeepNumberedUnsupported.into());
+    }
+    let mut parts = tokens.split(template).map(regex::escape);
+    let mut source = format!("^{}", parts.next().unwrap_or_default());
+    for part in parts {
+        source.push_str(r"(\d+)");
+        source.push_str(&part);
+    }
+    source.push('$');
+    Ok(Regex::new(&source)?)
+}
+
+// The `{n}` number of a name that already follows the template exactly, padding included,
+// at or above the start number
+fn template_number(
+    name: &str,
+    matcher: &Regex,
+    template: &str,
+    tokens: &Regex,
+    options: &NumberingOptions,
+) -> Option<u32> {
+    let (stem, _) = split_extension(name, options.compound_extensions);
+    let number: u32 = matcher.captures(stem)?.get(1)?.as_str().parse().ok()?;
+    // Every {n} must carry the same number with the configured padding
+    let expected = tokens.replace_all(template, |_: &Captures| {
+        format!("{:0>width$}", number, width = options.padding)
+    });
+    (number >= options.start_number && expected == stem).then_some(number)
+}
+
+// Replaces each name with the template mapped to its extension, numbering every mapping
+// on its own: "jpg: image_{n}; pdf: doc_{n}" gives image_1.jpg, image_2.jpg... and
+// doc_1.pdf, doc_2.pdf... Files with an unmapped extension are left out; a file matching
+// several mappings takes the first.
+pub fn apply_extension_templates(
+    files: &[FileEntry],
+    templates: &[ExtensionTemplate],
+    options: &NumberingOptions,
+    taken: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<RenamePreview>> {
+    let mut routed: Vec<Vec<FileEntry>> = vec![Vec::new(); templates.len()];
+    for file in files {
+        if let Some(i) = templates
+            .iter()
+            .position(|t| matches_extensions(&file.path, &t.extensions))
+        {
+            routed[i].push(file.clone());
+        }
+    }
+
+    let mut renamed: HashMap<PathBuf, RenamePreview> = HashMap::new();
+    for (mapping, group) in templates.iter().zip(&routed) {
+        if group.is_empty() {
+            continue;
+        }
+        let previews = apply_iteration_numbering(group, &mapping.template, options, taken)
+            .with_context(|| format!("Template for {}", mapping.extensions.join(", ")))?;
+        renamed.extend(previews.into_iter().map(|p| (p.original_path.clone(), p)));
+    }
+
+    // Back in list order; names from different mappings can still collide
+    let mut previews: Vec<RenamePreview> = files
+        .iter()
+        .filter_map(|f| renamed.remove(&f.path))
+        .collect();
+    detect_conflicts(&mut previews);
+    Ok(previews)
+}
+
+// Gives each file the template of the first rule whose regex matches its name, with the
+// groups that match captured filled in: "^IMG_(\d+)\.jpe?g$ => photo_$1.jpg". Rules
+// without a pattern are skipped and files no rule matches are left out.
+pub fn apply_rules(files: &[FileEntry], rules: &[RenameRule]) -> Result<Vec<RenamePreview>> {
+    let options = FindOptions {
+        use_regex: true,
+        case_sensitive: true,
+        ..FindOptions::default()
+    };
+    let mut matchers = Vec::with_capacity(rules.len());
+    for (i, rule) in rules.iter().enumerate() {
+        if rule.pattern.is_empty() {
+            continue;
+        }
+        if let Some(matcher) =
+            find_matcher(&rule.pattern, options).with_context(|| format!("Rule {}", i + 1))?
+        {
+            matchers.push((matcher, braced_group_refs(&rule.template)));
+        }
+    }
+
+    let mut previews: Vec<RenamePreview> = files
+        .iter()
+        .filter_map(|file| {
+            let (captures, template) = matchers
+                .iter()
+                .find_map(|(matcher, template)| Some((matcher.captures(&file.name)?, template)))?;
+            let mut new_name = String::new();
+            captures.expand(template, &mut new_name);
+            Some(RenamePreview {
+                original_path: file.path.clone(),
+                original_name: Arc::clone(&file.name),
+                new_name,
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count: 0,
+                edited: false,
+                warnings: Vec::new(),
+            })
+        })
+        .collect();
+
+    detect_conflicts(&mut previews);
+    validate_names(&mut previews);
     Ok(previews)
 }
 
-// Case-insensitive string replacement
-fn replace_case_insensitive(text: &str, pattern: &str, replacement: &str) -> String {
-    let regex = RegexBuilder::new(&regex::escape(pattern))
-        .case_insensitive(true)
-        .build()
-        .expect("Escaped pattern should be valid");
-    regex.replace_all(text, replacement).to_string()
+// Counter for a 1-based token index such as "2" in {n2}
+fn counter_at<'a>(counters: &'a [Counter], index: &str) -> Option<&'a Counter> {
+    let index: usize = index.parse().ok()?;
+    counters.get(index.checked_sub(1)?)
+}
+
+// Value of {n} (empty index) or a numbered counter at the given sequence position
+fn format_token(index: &str, position: u64, options: &NumberingOptions) -> String {
+    match counter_at(&options.counters, index) {
+        Some(counter) => {
+            let offset = i64::try_from(position).unwrap_or(i64::MAX);
+            let value = counter
+                .start
+                .saturating_add(counter.step.saturating_mul(offset));
+            format!("{:0width$}", value, width = counter.padding)
+        }
+        None => {
+            let offset = u32::try_from(position).unwrap_or(u32::MAX);
+            let number = options.start_number.saturating_add(offset);
+            format!("{:0>width$}", number, width = options.padding)
+        }
+    }
+}
+
+// For display only: pads each stem with spaces to the longest one, in characters, so the
+// extensions line up in a monospace font. The spaces never become part of a file name.
+pub fn align_stems(names: &[&str], compound: bool) -> Vec<String> {
+    let split: Vec<(&str, &str)> = names
+        .iter()
+        .map(|name| split_extension(name, compound))
+        .collect();
+    let width = split
+        .iter()
+        .map(|(stem, _)| stem.chars().count())
+        .max()
+        .unwrap_or(0);
+    split
+        .into_iter()
+        .map(|(stem, extension)| format!("{:<width$}{}", stem, extension, width = width))
+        .collect()
+}
+
+// Rewrites each stem in the target naming convention, keeping extensions (compound ones
+// such as `.tar.gz` included) untouched; web-safe lowercases them as well. Files whose name
+// doesn't change are left out.
+pub fn apply_convention(files: &[FileEntry], convention: NameConvention) -> Vec<RenamePreview> {
+    let mut previews: Vec<RenamePreview> = files
+        .iter()
+        .filter_map(|file| {
+            let (stem, extension) = split_extension(&file.name, true);
+            let extension = match convention {
+                NameConvention::WebSafe => Cow::Owned(extension.to_lowercase()),
+                _ => Cow::Borrowed(extension),
+            };
+            let new_name = format!("{}{}", convention::convert(stem, convention), extension);
+            (new_name != file.name.as_str()).then(|| RenamePreview {
+                original_path: file.path.clone(),
+                original_name: Arc::clone(&file.name),
+                new_name,
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count: 0,
+                edited: false,
+                warnings: Vec::new(),
+            })
+        })
+        .collect();
+
+    detect_conflicts(&mut previews);
+    validate_names(&mut previews);
+    previews
+}
+
+// Gives every name the new extension, or removes the extension when it's empty (a leading
+// dot is optional). The extension is what follows the last dot, or a compound one such as
+// `.tar.gz` with `compound`; dotfiles such as `.gitignore` have none, so removing leaves
+// them as they are. Files whose name doesn't change are left out.
+pub fn apply_extension_change(
+    files: &[FileEntry],
+    extension: &str,
+    compound: bool,
+) -> Vec<RenamePreview> {
+    let extension = extension.trim().trim_start_matches('.');
+    let mut previews: Vec<RenamePreview> = files
+        .iter()
+        .filter_map(|file| {
+            let (stem, _) = split_extension(&file.name, compound);
+            let new_name = if extension.is_empty() {
+                stem.to_string()
+            } else {
+                format!("{}.{}", stem, extension)
+            };
+            (new_name != file.name.as_str()).then(|| RenamePreview {
+                original_path: file.path.clone(),
+                original_name: Arc::clone(&file.name),
+                new_name,
+                conflicts: Vec::new(),
+                invalid_reason: None,
+                match_count: 0,
+                edited: false,
+                warnings: Vec::new(),
+            })
+        })
+        .collect();
+
+    // "a.txt" and "a.dat" both losing their extension collide here
+    detect_conflicts(&mut previews);
+    validate_names(&mut previews);
+    previews
+}
+
+// Overwrites new names in order with hand-supplied ones, then re-checks conflicts and
+// validity. Returns how many rows received a name.
+pub fn assign_names(previews: &mut [RenamePreview], names: &[&str]) -> usize {
+    for (preview, name) in previews.iter_mut().zip(names) {
+        preview.new_name = name.to_string();
+        preview.edited = true;
+    }
+    for preview in previews.iter_mut() {
+        preview
+            .conflicts
+            .retain(|&r| r != ConflictReason::DuplicateInBatch);
+    }
+    detect_conflicts(previews);
+    validate_names(previews);
+    previews.len().min(names.len())
+}
+
+// Re-applies names supplied by hand, keyed by original path, to a freshly generated preview.
+// Files with an edit but no row get one; rows follow the order of `files`. Conflicts and
+// validity are checked again afterwards.
+pub fn apply_edits(
+    previews: &mut Vec<RenamePreview>,
+    files: &[FileEntry],
+    edits: &HashMap<PathBuf, String>,
+) {
+    if edits.is_empty() {
+        return;
+    }
+    let mut rows: HashMap<PathBuf, RenamePreview> = previews
+        .drain(..)
+        .map(|p| (p.original_path.clone(), p))
+        .collect();
+    for file in files {
+        let row = rows.remove(&file.path);
+        let Some(name) = edits.get(&file.path) else {
+            previews.extend(row);
+            continue;
+        };
+        let mut row = row.unwrap_or_else(|| RenamePreview {
+            original_path: file.path.clone(),
+            original_name: Arc::clone(&file.name),
+            new_name: String::new(),
+            conflicts: Vec::new(),
+            invalid_reason: None,
+            match_count: 0,
+            edited: false,
+            warnings: Vec::new(),
+        });
+        row.new_name = name.clone();
+        row.edited = true;
+        previews.push(row);
+    }
+    for preview in previews.iter_mut() {
+        preview
+            .conflicts
+            .retain(|&r| r != ConflictReason::DuplicateInBatch);
+    }
+    detect_conflicts(previews);
+    validate_names(previews);
+}
+
+// Gives duplicate new names in the same folder a " (1)", " (2)"... suffix before the
+// extension. The first occurrence keeps its name, later ones take the lowest number not
+// used elsewhere in that folder. Rows with an invalid name are left alone.
+pub fn resolve_conflicts_with_suffix(previews: &mut [RenamePreview]) {
+    let mut counts: HashMap<(PathBuf, String), usize> = HashMap::with_capacity(previews.len());
+    for preview in previews.iter() {
+        *counts.entry(target_key(preview)).or_insert(0) += 1;
+    }
+    let mut used: HashSet<(PathBuf, String)> = counts.keys().cloned().collect();
+    let mut seen: HashSet<(PathBuf, String)> = HashSet::with_capacity(counts.len());
+
+    for preview in previews.iter_mut() {
+        let key = target_key(preview);
+        let duplicate = counts.get(&key).is_some_and(|&count| count > 1);
+        if !duplicate || preview.invalid_reason.is_some() {
+            continue;
+        }
+        preview
+            .conflicts
+            .retain(|&r| r != ConflictReason::DuplicateInBatch);
+        if seen.insert(key.clone()) {
+            continue;
+        }
+        let (stem, extension) = split_extension(&preview.new_name, true);
+        let mut number = 1u32;
+        let new_name = loop {
+            let candidate = format!("{} ({}){}", stem, number, extension);
+            if used.insert((key.0.clone(), candidate.to_lowercase())) {
+                break candidate;
+            }
+            number += 1;
+        };
+        preview.new_name = new_name;
+    }
 }
 
-// Marks duplicate target names as conflicts
-fn detect_conflicts(previews: &mut [RenamePreview]) {
-    let mut counts: HashMap<String, usize> = HashMap::with_capacity(previews.len());
+/// Differences between the preview's new names and a list of expected final names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameListCheck {
+    // Distinct names in the list
+    pub listed: usize,
+    // Listed names no row is renamed to
+    pub missing: Vec<String>,
+    // Rows renamed to a name that isn't listed, as (current name, new name)
+    pub unexpected: Vec<(Arc<String>, String)>,
+    // Names the list holds more than once
+    pub repeated: Vec<String>,
+}
+
+impl NameListCheck {
+    pub fn is_match(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.repeated.is_empty()
+    }
+}
+
+// Compares the new names against the expected ones as sets, ignoring order. Names are
+// compared exactly, letter case included, since that's what lands on disk. Missing and
+// repeated names keep the list's order, unexpected ones the preview's.
+pub fn check_against_list(previews: &[RenamePreview], expected: &[&str]) -> NameListCheck {
+    let mut listed: HashSet<&str> = HashSet::with_capacity(expected.len());
+    let mut repeated = Vec::new();
+    for &name in expected {
+        if !listed.insert(name) && !repeated.iter().any(|r| r == name) {
+            repeated.push(name.to_string());
+        }
+    }
+    let produced: HashSet<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+    let mut seen = HashSet::with_capacity(listed.len());
+    let missing = expected
+        .iter()
+        .filter(|name| !produced.contains(*name) && seen.insert(**name))
+        .map(|name| name.to_string())
+        .collect();
+    let unexpected = previews
+        .iter()
+        .filter(|p| !listed.contains(p.new_name.as_str()))
+        .map(|p| (Arc::clone(&p.original_name), p.new_name.clone()))
+        .collect();
+    NameListCheck {
+        listed: listed.len(),
+        missing,
+        unexpected,
+        repeated,
+    }
+}
+
+// Splits a name into stem and extension (including its dot). Leading-dot files such as
+// `.gitignore` have no extension; `.tar.gz` and friends stay whole when `compound` is set.
+fn split_extension(name: &str, compound: bool) -> (&str, &str) {
+    if compound {
+        let lower = name.to_ascii_lowercase();
+        if let Some(ext) = COMPOUND_EXTENSIONS
+            .iter()
+            .find(|ext| lower.len() > ext.len() && lower.ends_with(*ext))
+        {
+            return name.split_at(name.len() - ext.len());
+        }
+    }
+    match name.rfind('.') {
+        Some(i) if i > 0 => name.split_at(i),
+        _ => (name, ""),
+    }
+}
+
+// Matcher for the {created} tokens in a replacement, None when it has none. Each format is
+// checked up front so a typo is reported once instead of formatting into garbage.
+fn created_matcher(replacement: &str) -> Result<Option<Regex>> {
+    if !replacement.contains("{created") {
+        return Ok(None);
+    }
+    let tokens = Regex::new(CREATED_TOKEN_PATTERN)?;
+    for caps in tokens.captures_iter(replacement) {
+        if let Some(format) = caps.get(1) {
+            if StrftimeItems::new(format.as_str()).parse().is_err() {
+                return Err(PatternError::InvalidDateFormat(format.as_str().to_string()).into());
+            }
+        }
+    }
+    Ok(Some(tokens))
+}
+
+// Whether the replacement uses {hash}, so the files' contents must be hashed first
+pub fn uses_content_hash(replacement: &str) -> bool {
+    Regex::new(HASH_TOKEN_PATTERN).is_ok_and(|tokens| tokens.is_match(replacement))
+}
+
+// Matcher for the {hash} tokens in a replacement, None when it has none. Lengths beyond
+// the 64 hex characters of the hash are reported up front.
+fn hash_matcher(replacement: &str) -> Result<Option<Regex>> {
+    if !replacement.contains("{hash") {
+        return Ok(None);
+    }
+    let tokens = Regex::new(HASH_TOKEN_PATTERN)?;
+    for caps in tokens.captures_iter(replacement) {
+        let digits = caps.get(1).map_or("", |m| m.as_str());
+        if !digits.is_empty() && !(1..=HASH_HEX_LEN).contains(&digits.parse().unwrap_or(0)) {
+            return Err(PatternError::InvalidHashLength(digits.to_string()).into());
+        }
+    }
+    Ok(tokens.is_match(replacement).then_some(tokens))
+}
+
+// Creation time where the platform and filesystem record it (some Linux filesystems and
+// older kernels don't), otherwise the modified time; true when it fell back
+fn created_time(path: &Path) -> Option<(SystemTime, bool)> {
+    let metadata = fs::metadata(path).ok()?;
+    match metadata.created() {
+        Ok(created) => Some((created, false)),
+        Err(_) => metadata.modified().ok().map(|modified| (modified, true)),
+    }
+}
+
+// Fills in {created}, {folder} and {hash} for one file, also telling whether {created} fell
+// back to the modified time. Files without a named folder, such as those at a drive root,
+// whose times can't be read or that have no hash give None and are left out.
+fn replacement_for<'a>(
+    replacement: &'a str,
+    path: &Path,
+    expand: bool,
+    dates: Option<&Regex>,
+    hashes: Option<(&Regex, &HashMap<PathBuf, String>)>,
+) -> Option<(Cow<'a, str>, bool)> {
+    // A `$` would start a group reference in an expanded regex replacement
+    let escape = |value: String| {
+        if expand {
+            value.replace('$', "$$")
+        } else {
+            value
+        }
+    };
+    let mut result = Cow::Borrowed(replacement);
+    let mut fallback = false;
+    if let Some(dates) = dates {
+        let (created, fell_back) = created_time(path)?;
+        fallback = fell_back;
+        let created = DateTime::<Local>::from(created);
+        let filled = dates.replace_all(&result, |caps: &Captures| {
+            let format = caps.get(1).map_or(CREATED_FORMAT, |m| m.as_str());
+            escape(created.format(format).to_string())
+        });
+        result = Cow::Owned(filled.into_owned());
+    }
+    if result.contains(FOLDER_TOKEN) {
+        let folder = path.parent()?.file_name()?.to_string_lossy().into_owned();
+        result = Cow::Owned(result.replace(FOLDER_TOKEN, &escape(folder)));
+    }
+    if let Some((tokens, hashes)) = hashes {
+        let hash = hashes.get(path)?;
+        result = Cow::Owned(fill_hash(tokens, &result, hash));
+    }
+    Some((result, fallback))
+}
+
+// Replaces each {hash} token with the hash, or its first characters for {hash8} and the like
+fn fill_hash(tokens: &Regex, text: &str, hash: &str) -> String {
+    tokens
+        .replace_all(text, |caps: &Captures| {
+            let len = caps[1].parse().unwrap_or(HASH_HEX_LEN);
+            hash[..len.min(hash.len())].to_string()
+        })
+        .into_owned()
+}
+
+// Replaces every non-overlapping match like `Regex::replace_all`, also returning the match count.
+// With `expand`, `$1`-style references in the replacement are substituted from the captures.
+fn replace_counting(regex: &Regex, text: &str, replacement: &str, expand: bool) -> (String, usize) {
+    let replacement = if expand {
+        braced_group_refs(replacement)
+    } else {
+        Cow::Borrowed(replacement)
+    };
+    let replacement = replacement.as_ref();
+    let mut result = String::with_capacity(text.len());
+    let mut last_end = 0;
+    let mut count = 0;
+    for caps in regex.captures_iter(text) {
+        let Some(whole) = caps.get(0) else { continue };
+        result.push_str(&text[last_end..whole.start()]);
+        if expand {
+            caps.expand(replacement, &mut result);
+        } else {
+            result.push_str(replacement);
+        }
+        last_end = whole.end();
+        count += 1;
+    }
+    result.push_str(&text[last_end..]);
+    (result, count)
+}
+
+// Braces numbered group references followed by a word character, so `$1_x` means group 1
+// then `_x` rather than a group named `1_x`, which can't exist and expands to nothing.
+// `$$` stays a literal dollar sign.
+fn braced_group_refs(template: &str) -> Cow<'_, str> {
+    if !template.contains('$') {
+        return Cow::Borrowed(template);
+    }
+    let mut result = String::with_capacity(template.len() + 4);
+    let mut rest = template;
+    while let Some(i) = rest.find('$') {
+        result.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            result.push_str("$$");
+            rest = tail;
+            continue;
+        }
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, tail) = after.split_at(digits);
+        if digits > 0 && tail.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            result.push_str(&format!("${{{}}}", number));
+        } else {
+            result.push('$');
+            result.push_str(number);
+        }
+        rest = tail;
+    }
+    result.push_str(rest);
+    Cow::Owned(result)
+}
+
+// Flags names that are empty, only dots, reserved for temp files, or lost their stem
+// (e.g. "photo.jpg" -> ".jpg"), and warns about names that may not turn out as shown. A
+// name typed in by hand no longer depends on the file's dates.
+fn validate_names(previews: &mut [RenamePreview]) {
+    for preview in previews.iter_mut() {
+        preview.invalid_reason = invalid_name_reason(&preview.original_name, &preview.new_name);
+        let edited = preview.edited;
+        preview
+            .warnings
+            .retain(|&w| w == NameWarning::ModifiedDateUsed && !edited);
+        if preview.new_name.len() > MAX_NAME_BYTES {
+            preview.warnings.push(NameWarning::TooLong);
+        }
+        if preview.new_name.ends_with(['.', ' ']) {
+            preview.warnings.push(NameWarning::TrailingDotOrSpace);
+        }
+    }
+}
+
+fn invalid_name_reason(original: &str, new_name: &str) -> Option<String> {
+    if new_name.trim().is_empty() {
+        Some("Resulting name is empty".to_string())
+    } else if new_name.chars().all(|c| c == '.') {
+        Some("Resulting name consists only of dots".to_string())
+    } else if new_name.contains(std::path::is_separator) {
+        // Joined onto the folder, it would move the file somewhere else
+        Some(format!(
+            "Resulting name \"{}\" contains a path separator",
+            new_name
+        ))
+    } else if is_reserved_name(new_name) {
+        Some(format!(
+            "Resulting name \"{}\" uses the prefix reserved for temporary files",
+            new_name
+        ))
+    } else if new_name.starts_with('.') && !original.starts_with('.') {
+        Some(format!(
+            "Resulting name \"{}\" has no name before the extension",
+            new_name
+        ))
+    } else {
+        None
+    }
+}
+
+// Marks duplicate target names in the same folder as conflicts within the batch
+pub fn detect_conflicts(previews: &mut [RenamePreview]) {
+    let mut counts: HashMap<(PathBuf, String), usize> = HashMap::with_capacity(previews.len());
     for preview in previews.iter() {
-        *counts.entry(preview.new_name.to_lowercase()).or_insert(0) += 1;
+        *counts.entry(target_key(preview)).or_insert(0) += 1;
     }
     for preview in previews.iter_mut() {
-        if counts
-            .get(&preview.new_name.to_lowercase())
-            .copied()
-            .unwrap_or(0)
-            > 1
+        if counts.get(&target_key(preview)).copied().unwrap_or(0) > 1
+            && !preview
+                .conflicts
+                .contains(&ConflictReason::DuplicateInBatch)
         {
-            preview.has_conflict = true;
+            preview.conflicts.push(ConflictReason::DuplicateInBatch);
+        }
+    }
+}
+
+// Folder and lowercased new name: two files only collide when they end up in the same
+// folder, so identical names in different folders are fine
+fn target_key(preview: &RenamePreview) -> (PathBuf, String) {
+    let folder = preview.original_path.parent().unwrap_or(Path::new(""));
+    (folder.to_path_buf(), preview.new_name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn entry(folder: &Path, name: &str) -> FileEntry {
+        FileEntry {
+            path: folder.join(name),
+            name: Arc::new(name.to_string()),
+            identity: None,
+            pinned: false,
+            added: 0,
+        }
+    }
+
+    fn preview(folder: &Path, from: &str, to: &str) -> RenamePreview {
+        RenamePreview {
+            original_path: folder.join(from),
+            original_name: Arc::new(from.to_string()),
+            new_name: to.to_string(),
+            conflicts: Vec::new(),
+            invalid_reason: None,
+            match_count: 1,
+            edited: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn find(
+        files: &[FileEntry],
+        pattern: &str,
+        replacement: &str,
+        use_regex: bool,
+        case_locale: CaseLocale,
+    ) -> Vec<RenamePreview> {
+        let options = FindOptions {
+            use_regex,
+            case_locale,
+            ..FindOptions::default()
+        };
+        apply_find_replace(
+            files,
+            pattern,
+            replacement,
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect("preview")
+    }
+
+    #[test]
+    fn names_left_without_a_stem_are_invalid() {
+        let folder = Path::new("/props");
+        let files = [entry(folder, "photo.jpg"), entry(folder, "notes")];
+
+        // Replacing the whole name leaves nothing
+        let previews = find(&files, "^.*$", "", true, CaseLocale::Unicode);
+        let reasons: Vec<Option<&str>> = previews
+            .iter()
+            .map(|p| p.invalid_reason.as_deref())
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                Some("Resulting name is empty"),
+                Some("Resulting name is empty")
+            ]
+        );
+
+        // Removing the stem leaves only the extension
+        let previews = find(&files, "^photo", "", true, CaseLocale::Unicode);
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].new_name, ".jpg");
+        assert!(previews[0]
+            .invalid_reason
+            .as_deref()
+            .is_some_and(|r| r.contains("no name before the extension")));
+
+        // Dotfiles keep their leading dot
+        let files = [entry(folder, ".bashrc")];
+        let previews = find(&files, "bash", "zsh", false, CaseLocale::Unicode);
+        assert_eq!(previews[0].new_name, ".zshrc");
+        assert_eq!(previews[0].invalid_reason, None);
+    }
+
+    #[test]
+    fn turkic_locale_applies_to_matching() {
+        let folder = Path::new("/props");
+        let files = [entry(folder, "FILE_I.TXT"), entry(folder, "file_i.txt")];
+        let previews = find(&files, "fıle", "doc", false, CaseLocale::Turkic);
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["doc_I.TXT"]);
+
+        let previews = find(&files, "fıle", "doc", false, CaseLocale::Unicode);
+        assert!(previews.is_empty());
+    }
+
+    #[test]
+    fn numbering_skips_names_already_taken() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "a.txt"),
+            entry(folder, "b.txt"),
+            entry(folder, "c.txt"),
+        ];
+        let taken: HashSet<PathBuf> = ["002.txt", "004.txt"]
+            .iter()
+            .map(|name| folder.join(name))
+            .collect();
+        let options = NumberingOptions {
+            start_number: 1,
+            padding: 3,
+            ..NumberingOptions::default()
+        };
+        let previews =
+            apply_iteration_numbering(&files, "{n}", &options, Some(&taken)).expect("numbering");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["001.txt", "003.txt", "005.txt"]);
+
+        // Without the listing nothing is skipped
+        let previews = apply_iteration_numbering(&files, "{n}", &options, None).expect("numbering");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["001.txt", "002.txt", "003.txt"]);
+    }
+
+    #[test]
+    fn numbering_keeps_dotfiles_whole_and_compound_extensions_together() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, ".gitignore"),
+            entry(folder, "archive.tar.gz"),
+            entry(folder, "backup.TAR.XZ"),
+            entry(folder, "photo.jpg"),
+        ];
+        let numbered = |compound_extensions: bool| {
+            let options = NumberingOptions {
+                start_number: 1,
+                padding: 1,
+                compound_extensions,
+                ..NumberingOptions::default()
+            };
+            apply_iteration_numbering(&files, "file_{n}", &options, None)
+                .expect("numbering")
+                .into_iter()
+                .map(|p| p.new_name)
+                .collect::<Vec<_>>()
+        };
+
+        // A leading dot starts the name, not an extension
+        assert_eq!(
+            numbered(false),
+            ["file_1", "file_2.gz", "file_3.XZ", "file_4.jpg"]
+        );
+        assert_eq!(
+            numbered(true),
+            ["file_1", "file_2.tar.gz", "file_3.TAR.XZ", "file_4.jpg"]
+        );
+    }
+
+    #[test]
+    fn reverse_numbering_hands_the_start_number_to_the_last_file() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "a.jpg"),
+            entry(folder, "b.jpg"),
+            entry(folder, "c.jpg"),
+        ];
+        let numbered = |template: &str, options: &NumberingOptions| {
+            apply_iteration_numbering(&files, template, options, None)
+                .expect("numbering")
+                .into_iter()
+                .map(|p| (p.original_name.to_string(), p.new_name))
+                .collect::<Vec<_>>()
+        };
+        let reverse = NumberingOptions {
+            start_number: 10,
+            padding: 1,
+            reverse: true,
+            ..NumberingOptions::default()
+        };
+        // Numbers still ascend from the start; the preview keeps list order
+        assert_eq!(
+            numbered("{n}", &reverse),
+            [
+                ("a.jpg".to_string(), "12.jpg".to_string()),
+                ("b.jpg".to_string(), "11.jpg".to_string()),
+                ("c.jpg".to_string(), "10.jpg".to_string()),
+            ]
+        );
+
+        // A descending counter instead counts down from the start in list order
+        let descending = NumberingOptions {
+            counters: vec![Counter {
+                start: 10,
+                step: -1,
+                padding: 1,
+            }],
+            ..NumberingOptions::default()
+        };
+        assert_eq!(
+            numbered("{n1}", &descending),
+            [
+                ("a.jpg".to_string(), "10.jpg".to_string()),
+                ("b.jpg".to_string(), "9.jpg".to_string()),
+                ("c.jpg".to_string(), "8.jpg".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbered_counters_advance_in_lockstep() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "a.txt"),
+            entry(folder, "b.txt"),
+            entry(folder, "c.txt"),
+        ];
+        let options = NumberingOptions {
+            start_number: 1,
+            padding: 2,
+            counters: vec![
+                Counter {
+                    start: 1,
+                    step: 1,
+                    padding: 1,
+                },
+                Counter {
+                    start: 100,
+                    step: 10,
+                    padding: 4,
+                },
+            ],
+            ..NumberingOptions::default()
+        };
+        let numbered = |template: &str| {
+            apply_iteration_numbering(&files, template, &options, None)
+                .map(|previews| previews.into_iter().map(|p| p.new_name).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            numbered("{n1}_{n2}").expect("counters"),
+            ["1_0100.txt", "2_0110.txt", "3_0120.txt"]
+        );
+        // {n} keeps its own start and padding alongside them
+        assert_eq!(
+            numbered("{n}-{n2}").expect("counters"),
+            ["01-0100.txt", "02-0110.txt", "03-0120.txt"]
+        );
+        let error = numbered("{n1}_{n3}").expect_err("undefined counter");
+        assert!(matches!(
+            error.downcast_ref::<TemplateError>(),
+            Some(TemplateError::UndefinedCounter(index)) if index == "3"
+        ));
+    }
+
+    #[test]
+    fn conventions_keep_the_extension() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "myVariableName.rs"),
+            entry(folder, "my-file_name.tar.gz"),
+            entry(folder, "already_snake.txt"),
+        ];
+        let previews = apply_convention(&files, NameConvention::Snake);
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["my_variable_name.rs", "my_file_name.tar.gz"]);
+    }
+
+    #[test]
+    fn lowercase_result_applies_to_the_stem_after_replacing() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "Photo 1.JPG"),
+            entry(folder, "BACKUP.TAR.GZ"),
+            entry(folder, "notes.txt"),
+        ];
+        let options = FindOptions {
+            lowercase: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(&files, " ", "_", "", options, &HashMap::new(), None)
+            .expect("preview");
+        let renamed: Vec<(&str, &str, usize)> = previews
+            .iter()
+            .map(|p| (p.original_name.as_str(), p.new_name.as_str(), p.match_count))
+            .collect();
+        // Names without a match are lowercased too, extensions keep their case, and names
+        // already lowercase are left out
+        assert_eq!(
+            renamed,
+            [
+                ("Photo 1.JPG", "photo_1.JPG", 1),
+                ("BACKUP.TAR.GZ", "backup.TAR.GZ", 0),
+            ]
+        );
+
+        // The replacement is lowercased along with the rest
+        let previews = apply_find_replace(
+            &files,
+            "notes",
+            "README",
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect("preview");
+        assert_eq!(
+            previews.last().map(|p| p.new_name.as_str()),
+            Some("readme.txt")
+        );
+    }
+
+    #[test]
+    fn turkic_locale_applies_to_lowercasing() {
+        let folder = Path::new("/props");
+        let files = [entry(folder, "FILE_I.TXT"), entry(folder, "file_i.txt")];
+        let options = FindOptions {
+            case_locale: CaseLocale::Turkic,
+            lowercase: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(&files, "_", "-", "", options, &HashMap::new(), None)
+            .expect("preview");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["fıle-ı.TXT", "file-i.txt"]);
+    }
+
+    #[test]
+    fn whole_word_matches_only_standalone_tokens() {
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "cat.jpg"),
+            entry(folder, "a cat b.jpg"),
+            entry(folder, "category.jpg"),
+            entry(folder, "Cat.jpg"),
+        ];
+        let matched = |pattern: &str, options: FindOptions| {
+            let previews =
+                apply_find_replace(&files, pattern, "dog", "", options, &HashMap::new(), None)
+                    .expect("preview");
+            previews.into_iter().map(|p| p.new_name).collect::<Vec<_>>()
+        };
+
+        let literal = FindOptions {
+            case_sensitive: true,
+            whole_word: true,
+            ..FindOptions::default()
+        };
+        assert_eq!(matched("cat", literal), ["dog.jpg", "a dog b.jpg"]);
+        let any_case = FindOptions {
+            case_sensitive: false,
+            ..literal
+        };
+        assert_eq!(
+            matched("cat", any_case),
+            ["dog.jpg", "a dog b.jpg", "dog.jpg"]
+        );
+        // A regex is wrapped as a whole, alternatives included
+        let regex = FindOptions {
+            use_regex: true,
+            ..literal
+        };
+        assert_eq!(matched("cat|categ", regex), ["dog.jpg", "a dog b.jpg"]);
+
+        // Off, the pattern matches inside words too
+        let anywhere = FindOptions {
+            whole_word: false,
+            ..literal
+        };
+        assert_eq!(
+            matched("cat", anywhere),
+            ["dog.jpg", "a dog b.jpg", "dogegory.jpg"]
+        );
+    }
+
+    #[test]
+    fn each_extension_mapping_numbers_its_own_files() {
+        let templates =
+            crate::file_ops::parse_extension_templates("jpg jpeg: image_{n}; pdf: doc_{n}");
+        assert_eq!(
+            templates,
+            [
+                ExtensionTemplate {
+                    extensions: vec!["jpg".to_string(), "jpeg".to_string()],
+                    template: "image_{n}".to_string(),
+                },
+                ExtensionTemplate {
+                    extensions: vec!["pdf".to_string()],
+                    template: "doc_{n}".to_string(),
+                },
+            ]
+        );
+
+        let folder = Path::new("/props");
+        let files = [
+            entry(folder, "a.jpg"),
+            entry(folder, "b.pdf"),
+            entry(folder, "c.txt"),
+            entry(folder, "d.JPEG"),
+            entry(folder, "e.pdf"),
+        ];
+        let options = NumberingOptions {
+            start_number: 1,
+            padding: 1,
+            ..NumberingOptions::default()
+        };
+        let previews =
+            apply_extension_templates(&files, &templates, &options, None).expect("templates");
+        let renamed: Vec<(&str, &str)> = previews
+            .iter()
+            .map(|p| (p.original_name.as_str(), p.new_name.as_str()))
+            .collect();
+        // The unmapped .txt file is left out; the others keep list order
+        assert_eq!(
+            renamed,
+            [
+                ("a.jpg", "image_1.jpg"),
+                ("b.pdf", "doc_1.pdf"),
+                ("d.JPEG", "image_2.JPEG"),
+                ("e.pdf", "doc_2.pdf"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tagging_again_leaves_tagged_names_alone() {
+        let folder = Path::new("/props");
+        let options = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let tag = |files: &[FileEntry]| {
+            apply_find_replace(
+                files,
+                "^",
+                "[DRAFT] ",
+                "[draft]",
+                options,
+                &HashMap::new(),
+                None,
+            )
+            .expect("preview")
+        };
+
+        let files = [
+            entry(folder, "report.doc"),
+            entry(folder, "[DRAFT] plan.doc"),
+        ];
+        let previews = tag(&files);
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["[DRAFT] report.doc"]);
+
+        // A second run over the result is a no-op
+        let renamed: Vec<FileEntry> = files
+            .iter()
+            .map(|f| {
+                let name = previews
+                    .iter()
+                    .find(|p| p.original_path == f.path)
+                    .map_or(f.name.as_str(), |p| p.new_name.as_str());
+                entry(folder, name)
+            })
+            .collect();
+        assert!(tag(&renamed).is_empty());
+    }
+
+    #[test]
+    fn patterns_and_templates_report_what_went_wrong_as_variants() {
+        let files = [entry(Path::new("/props"), "a.txt")];
+        let regex = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let error = apply_find_replace(&files, "(a", "b", "", regex, &HashMap::new(), None)
+            .expect_err("invalid regex");
+        assert!(matches!(
+            error.downcast_ref::<PatternError>(),
+            Some(PatternError::InvalidRegex { pattern, .. }) if pattern == "(a"
+        ));
+        let error = apply_iteration_numbering(&files, "photo", &NumberingOptions::default(), None)
+            .expect_err("no number");
+        assert!(matches!(
+            error.downcast_ref::<TemplateError>(),
+            Some(TemplateError::MissingNumber)
+        ));
+    }
+
+    #[test]
+    fn created_dates_fall_back_to_the_modified_time() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        fs::write(folder.join("scan.pdf"), "scan").expect("test file");
+        let files = [entry(folder, "scan.pdf"), entry(folder, "gone.pdf")];
+        let options = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(
+            &files,
+            "^",
+            "{created:%Y%m%d}_",
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect("preview");
+
+        // Files whose times can't be read are left out
+        assert_eq!(previews.len(), 1);
+        let metadata = fs::metadata(folder.join("scan.pdf")).expect("metadata");
+        let (time, fell_back) = match metadata.created() {
+            Ok(created) => (created, false),
+            Err(_) => (metadata.modified().expect("modified"), true),
+        };
+        let date = DateTime::<Local>::from(time).format("%Y%m%d");
+        assert_eq!(previews[0].new_name, format!("{}_scan.pdf", date));
+        assert_eq!(
+            previews[0]
+                .warnings
+                .contains(&NameWarning::ModifiedDateUsed),
+            fell_back
+        );
+
+        // Without a format the date reads year-month-day
+        let previews = apply_find_replace(
+            &files,
+            "^",
+            "{created} ",
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect("preview");
+        let date = DateTime::<Local>::from(time).format("%Y-%m-%d");
+        assert_eq!(previews[0].new_name, format!("{} scan.pdf", date));
+
+        let error = apply_find_replace(
+            &files,
+            "^",
+            "{created:%Q}",
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect_err("bad format");
+        assert!(matches!(
+            error.downcast_ref::<PatternError>(),
+            Some(PatternError::InvalidDateFormat(format)) if format == "%Q"
+        ));
+    }
+
+    #[test]
+    fn per_folder_numbering_restarts_without_false_conflicts() {
+        let files = [
+            entry(Path::new("/props/a"), "x.jpg"),
+            entry(Path::new("/props/a"), "y.jpg"),
+            entry(Path::new("/props/b"), "z.jpg"),
+        ];
+        let options = NumberingOptions {
+            start_number: 1,
+            padding: 3,
+            grouping: NumberGrouping::Folder,
+            ..NumberingOptions::default()
+        };
+        let previews =
+            apply_iteration_numbering(&files, "img{n}", &options, None).expect("numbering");
+        let renamed: Vec<(PathBuf, &str)> = previews
+            .iter()
+            .map(|p| (p.original_path.clone(), p.new_name.as_str()))
+            .collect();
+        assert_eq!(
+            renamed,
+            [
+                (PathBuf::from("/props/a/x.jpg"), "img001.jpg"),
+                (PathBuf::from("/props/a/y.jpg"), "img002.jpg"),
+                (PathBuf::from("/props/b/z.jpg"), "img001.jpg"),
+            ]
+        );
+        // img001.jpg lands in two different folders, which is no clash
+        assert!(previews.iter().all(|p| p.conflicts.is_empty()));
+
+        // The same name twice in one folder still is
+        let mut previews = previews;
+        previews[1].new_name = "IMG001.jpg".to_string();
+        detect_conflicts(&mut previews);
+        let flagged: Vec<bool> = previews.iter().map(|p| !p.conflicts.is_empty()).collect();
+        assert_eq!(flagged, [true, true, false]);
+    }
+
+    #[test]
+    fn only_true_collisions_get_a_suffix() {
+        let folder = Path::new("/props");
+        let mut previews = vec![
+            preview(folder, "a.jpg", "photo.jpg"),
+            preview(folder, "b.jpg", "unique.jpg"),
+            preview(folder, "c.jpg", "photo.jpg"),
+            preview(folder, "d.jpg", "photo.jpg"),
+            preview(folder, "e.jpg", "notes"),
+        ];
+        detect_conflicts(&mut previews);
+        resolve_conflicts_with_suffix(&mut previews);
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "photo.jpg",
+                "unique.jpg",
+                "photo (1).jpg",
+                "photo (2).jpg",
+                "notes"
+            ]
+        );
+        assert!(previews.iter().all(|p| p.conflicts.is_empty()));
+
+        // Resolving again is stable
+        let mut again = previews.clone();
+        detect_conflicts(&mut again);
+        resolve_conflicts_with_suffix(&mut again);
+        let renamed: Vec<&str> = again.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(renamed, names);
+    }
+
+    #[test]
+    fn suffixes_number_duplicates_within_each_folder() {
+        let a = Path::new("/props/a");
+        let b = Path::new("/props/b");
+        let mut previews = vec![
+            preview(a, "1.jpg", "img.jpg"),
+            preview(a, "2.jpg", "img.jpg"),
+            preview(a, "3.jpg", "IMG.jpg"),
+            // Already holds the first suffix, so the duplicates skip it
+            preview(a, "4.jpg", "img (1).jpg"),
+            preview(b, "5.jpg", "img.jpg"),
+            preview(b, "6.jpg", "backup.tar.gz"),
+            preview(b, "7.jpg", "backup.tar.gz"),
+        ];
+        detect_conflicts(&mut previews);
+        resolve_conflicts_with_suffix(&mut previews);
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "img.jpg",
+                "img (2).jpg",
+                "IMG (3).jpg",
+                "img (1).jpg",
+                "img.jpg",
+                "backup.tar.gz",
+                "backup (1).tar.gz",
+            ]
+        );
+        assert!(previews
+            .iter()
+            .all(|p| !p.conflicts.contains(&ConflictReason::DuplicateInBatch)));
+
+        // Invalid rows keep their name
+        let mut previews = vec![preview(a, "1.jpg", "x.jpg"), preview(a, "2.jpg", "x.jpg")];
+        previews[1].invalid_reason = Some("invalid".to_string());
+        resolve_conflicts_with_suffix(&mut previews);
+        assert_eq!(previews[1].new_name, "x.jpg");
+    }
+
+    #[test]
+    fn template_lint_points_at_each_problem() {
+        let problems = |template: &str| -> Vec<(Option<usize>, String)> {
+            lint_template(template, 1, NumberGrouping::None)
+                .problems
+                .into_iter()
+                .map(|p| (p.column, p.message))
+                .collect()
+        };
+        let columns = |template: &str| -> Vec<Option<usize>> {
+            problems(template).into_iter().map(|(c, _)| c).collect()
+        };
+
+        assert!(problems("photo_{n}_{n1}").is_empty());
+        let upper = problems("photo_{N}");
+        assert_eq!(upper[0].0, Some(7));
+        assert!(upper[0].1.contains("did you mean {n}"));
+        // The misspelled placeholder leaves no number at all
+        assert_eq!(upper[1].0, None);
+
+        let unclosed = problems("img_{n)");
+        assert_eq!(unclosed[0].0, Some(5));
+        assert!(unclosed[0].1.contains("did you mean {n}"));
+
+        assert_eq!(columns("{n}_{date}"), [Some(5)]);
+        assert_eq!(columns("{n}_{n2}"), [Some(5)]);
+        assert_eq!(columns("{n}_{g}"), [Some(5)]);
+        assert_eq!(columns("a}{n}"), [Some(2)]);
+        assert_eq!(columns("a/{n}"), [Some(2)]);
+        assert_eq!(columns(".{n}"), [Some(1)]);
+        // Columns count characters, not bytes
+        assert_eq!(columns("фото_{x}{n}"), [Some(6)]);
+    }
+
+    #[test]
+    fn web_safe_names_slugify_the_stem_and_lowercase_the_extension() {
+        let folder = Path::new("/props");
+        let names = [
+            "My File.JPG",
+            "Backup Set.TAR.GZ",
+            "already-fine.png",
+            "notes.TXT",
+            "Report_v2.PDF",
+        ];
+        let files: Vec<FileEntry> = names.iter().map(|n| entry(folder, n)).collect();
+        let previews = apply_convention(&files, NameConvention::WebSafe);
+        let renamed: Vec<(&str, &str)> = previews
+            .iter()
+            .map(|p| (p.original_name.as_str(), p.new_name.as_str()))
+            .collect();
+        assert_eq!(
+            renamed,
+            [
+                ("My File.JPG", "my-file.jpg"),
+                ("Backup Set.TAR.GZ", "backup-set.tar.gz"),
+                ("notes.TXT", "notes.txt"),
+                ("Report_v2.PDF", "report-v2.pdf"),
+            ]
+        );
+        assert!(previews.iter().all(|p| p.conflicts.is_empty()));
+
+        // Names that only differed in spacing or case now collide
+        let files = [entry(folder, "My File.JPG"), entry(folder, "my_file.jpg")];
+        let previews = apply_convention(&files, NameConvention::WebSafe);
+        assert_eq!(previews.len(), 2);
+        assert!(previews.iter().all(|p| p.new_name == "my-file.jpg"
+            && p.conflicts.contains(&ConflictReason::DuplicateInBatch)));
+    }
+
+    fn rule(pattern: &str, template: &str) -> RenameRule {
+        RenameRule {
+            pattern: pattern.to_string(),
+            template: template.to_string(),
         }
     }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let folder = Path::new("/props");
+        let names = ["IMG_0042.jpg", "IMG_7.png", "scan 3.pdf", "notes.txt"];
+        let files: Vec<FileEntry> = names.iter().map(|n| entry(folder, n)).collect();
+        let rules = [
+            rule("", "ignored"),
+            rule(r"^IMG_(\d+)\.jpg$", "photo_$1.jpg"),
+            // Also matches IMG_0042.jpg, but the rule above comes first
+            rule(
+                r"^IMG_(?P<number>\d+)\.(?P<ext>\w+)$",
+                "image_${number}.${ext}",
+            ),
+            rule(r"^scan (\d+)", "document_$1.pdf"),
+        ];
+        let previews = apply_rules(&files, &rules).expect("valid rules");
+        let renamed: Vec<(&str, &str)> = previews
+            .iter()
+            .map(|p| (p.original_name.as_str(), p.new_name.as_str()))
+            .collect();
+        assert_eq!(
+            renamed,
+            [
+                ("IMG_0042.jpg", "photo_0042.jpg"),
+                ("IMG_7.png", "image_7.png"),
+                ("scan 3.pdf", "document_3.pdf"),
+            ]
+        );
+
+        // Swapping the two IMG rules changes which one renames the jpg
+        let swapped = [rules[2].clone(), rules[1].clone()];
+        let previews = apply_rules(&files[..1], &swapped).expect("valid rules");
+        assert_eq!(previews[0].new_name, "image_0042.jpg");
+
+        let error = apply_rules(&files, &[rule("ok", "x"), rule("(unclosed", "x")])
+            .expect_err("invalid regex");
+        assert!(error.to_string().starts_with("Rule 2"));
+    }
+
+    #[test]
+    fn numbered_groups_can_be_followed_by_word_characters() {
+        let folder = Path::new("/props");
+        let files = [entry(folder, "IMG_0042.jpg")];
+        let renamed = |template: &str| {
+            apply_rules(&files, &[rule(r"^IMG_(\d+)\.jpg$", template)]).expect("valid rule")[0]
+                .new_name
+                .clone()
+        };
+        assert_eq!(renamed("photo_$1_x.jpg"), "photo_0042_x.jpg");
+        assert_eq!(renamed("photo_$1x.jpg"), "photo_0042x.jpg");
+        assert_eq!(renamed("photo_${1}_x.jpg"), "photo_0042_x.jpg");
+        assert_eq!(renamed("photo_$1.jpg"), "photo_0042.jpg");
+        // An escaped dollar sign stays literal, digits and all
+        assert_eq!(renamed("$$1_$1.jpg"), "$1_0042.jpg");
+
+        let options = FindOptions {
+            use_regex: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(
+            &files,
+            r"IMG_(\d+)",
+            "$1_photo",
+            "",
+            options,
+            &HashMap::new(),
+            None,
+        )
+        .expect("preview");
+        assert_eq!(previews[0].new_name, "0042_photo.jpg");
+    }
 }
@@ -1,30 +1,263 @@
 // Security: privilege detection and file access validation
 
+use crate::file_ops::long_path;
+use std::collections::HashMap;
+use std::env;
 use std::fs::{self, OpenOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-// Checks if file can be modified (considers permissions and admin status)
-pub fn can_modify_file(path: &Path) -> bool {
-    if !path.exists() {
-        return path.parent().map(can_write_to_directory).unwrap_or(false);
+// Returns the paths that can't be modified (considers permissions and admin status).
+// The directory check and the admin check run once per batch, not once per file. With
+// `probe_fallback`, directories the OS can't answer for are tested with a temporary file.
+pub fn unmodifiable_files<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+    probe_fallback: bool,
+) -> Vec<PathBuf> {
+    let mut checker = AccessChecker {
+        probe_fallback,
+        ..AccessChecker::default()
+    };
+    paths
+        .into_iter()
+        .filter(|path| !checker.can_modify(path))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+// The files that can't be renamed as things stand, each with the reason: no permission or
+// read-only, or open in another program. The same checks a rename starts with, for
+// showing in the list ahead of time.
+pub fn blocked_files(paths: &[PathBuf], probe_fallback: bool) -> HashMap<PathBuf, String> {
+    let mut blocked: HashMap<PathBuf, String> =
+        unmodifiable_files(paths.iter().map(PathBuf::as_path), probe_fallback)
+            .into_iter()
+            .map(|path| (path, "Read-only, or no permission to change it".to_string()))
+            .collect();
+    for file in locked_files(paths.iter().map(PathBuf::as_path)) {
+        let reason = file.reason();
+        blocked.entry(file.path).or_insert(reason);
+    }
+    blocked
+}
+
+/// A file another program has open in a way that blocks renaming it
+#[derive(Debug, Clone)]
+pub struct LockedFile {
+    pub path: PathBuf,
+    // Programs holding the file, when the OS can name them
+    pub holders: Vec<String>,
+}
+
+impl LockedFile {
+    pub fn reason(&self) -> String {
+        if self.holders.is_empty() {
+            "In use by another program".to_string()
+        } else {
+            format!("In use by {}", self.holders.join(", "))
+        }
     }
+}
+
+// Returns the files that are open elsewhere without delete sharing, which makes renaming
+// them fail on Windows. Unix renames aren't blocked by open handles, so nothing is reported.
+pub fn locked_files<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Vec<LockedFile> {
+    paths
+        .into_iter()
+        .filter(|path| is_locked(path))
+        .map(|path| LockedFile {
+            path: path.to_path_buf(),
+            holders: file_holders(path),
+        })
+        .collect()
+}
 
-    match fs::metadata(path) {
-        Ok(metadata) => {
-            if metadata.permissions().readonly() && !is_running_as_admin() {
-                return false;
+// Remembers per-directory results and admin status across files
+#[derive(Default)]
+struct AccessChecker {
+    writable_dirs: HashMap<PathBuf, bool>,
+    is_admin: Option<bool>,
+    probe_fallback: bool,
+}
+
+impl AccessChecker {
+    fn can_modify(&mut self, path: &Path) -> bool {
+        let path = long_path(path);
+        let path = path.as_ref();
+        if !path.exists() {
+            return path.parent().is_some_and(|dir| {
+                *self
+                    .writable_dirs
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| can_write_to_directory(dir, self.probe_fallback))
+            });
+        }
+
+        match fs::metadata(path) {
+            Ok(metadata) => {
+                if metadata.permissions().readonly()
+                    && !*self.is_admin.get_or_insert_with(is_running_as_admin)
+                {
+                    return false;
+                }
+                can_open_for_write(path)
             }
-            can_open_for_write(path)
+            Err(_) => false,
         }
-        Err(_) => false,
     }
 }
 
-// Tests write access by creating temp file
-fn can_write_to_directory(dir: &Path) -> bool {
-    if !dir.exists() || !dir.is_dir() {
+/// System folders whose contents need explicit confirmation before renaming
+pub struct ProtectedRoots(Vec<String>);
+
+impl ProtectedRoots {
+    // Platform defaults plus user-configured folders
+    pub fn new(extra_roots: &[PathBuf]) -> Self {
+        let roots = default_protected_roots()
+            .iter()
+            .chain(extra_roots)
+            .map(|root| comparable(root))
+            .map(|root| root.trim_end_matches(std::path::MAIN_SEPARATOR).to_string())
+            .collect();
+        Self(roots)
+    }
+
+    // Checks whether the path is one of the roots or lies below one
+    pub fn is_protected_path(&self, path: &Path) -> bool {
+        let path = comparable(path);
+        self.0.iter().any(|root| {
+            path.strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(std::path::MAIN_SEPARATOR))
+        })
+    }
+}
+
+// Parses a user-entered folder list, separated like the PATH variable
+pub fn parse_protected_roots(list: &str) -> Vec<PathBuf> {
+    env::split_paths(list)
+        .filter(|root| !root.as_os_str().is_empty())
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn default_protected_roots() -> Vec<PathBuf> {
+    [
+        ("SystemRoot", r"C:\Windows"),
+        ("ProgramFiles", r"C:\Program Files"),
+        ("ProgramFiles(x86)", r"C:\Program Files (x86)"),
+        ("ProgramData", r"C:\ProgramData"),
+    ]
+    .into_iter()
+    .map(|(var, fallback)| env::var_os(var).map_or_else(|| PathBuf::from(fallback), PathBuf::from))
+    .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn default_protected_roots() -> Vec<PathBuf> {
+    ["/System", "/Library", "/usr", "/bin", "/sbin", "/etc"]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn default_protected_roots() -> Vec<PathBuf> {
+    [
+        "/bin", "/sbin", "/usr", "/lib", "/lib64", "/etc", "/boot", "/dev", "/proc", "/sys",
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+// Resolved path text for containment checks. Symlinks are followed so `/bin` and
+// `/usr/bin` agree; Windows drops the verbatim prefix, and case-insensitive platforms
+// compare lowercased.
+fn comparable(path: &Path) -> String {
+    let resolved = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let text = resolved.to_string_lossy();
+    let text = if cfg!(windows) {
+        match text.strip_prefix(r"\\?\UNC\") {
+            Some(rest) => format!(r"\\{}", rest),
+            None => text
+                .strip_prefix(r"\\?\")
+                .unwrap_or(&text)
+                .replace('/', r"\"),
+        }
+    } else {
+        text.into_owned()
+    };
+    if cfg!(any(windows, target_os = "macos")) {
+        text.to_lowercase()
+    } else {
+        text
+    }
+}
+
+// Checks whether new entries can be created in `dir`, probing with a temp file only when
+// the OS can't answer and the fallback is enabled
+fn can_write_to_directory(dir: &Path, probe_fallback: bool) -> bool {
+    if !dir.is_dir() {
         return false;
     }
+    match directory_access(dir) {
+        Some(writable) => writable,
+        None if probe_fallback => probe_directory(dir),
+        // Undetermined: the rename itself reports any failure
+        None => true,
+    }
+}
+
+// Asks the OS whether new entries may be created, without touching the directory.
+// Uses the effective user and honors read-only mounts.
+#[cfg(target_family = "unix")]
+fn directory_access(dir: &Path) -> Option<bool> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `path` is a valid NUL-terminated string that outlives the call
+    let result =
+        unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), libc::W_OK, libc::AT_EACCESS) };
+    if result == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EACCES | libc::EPERM | libc::EROFS) => Some(false),
+        _ => None,
+    }
+}
+
+// Opens the directory requesting the add-file right, which runs the full ACL check
+#[cfg(target_os = "windows")]
+fn directory_access(dir: &Path) -> Option<bool> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_ADD_FILE: u32 = 0x0002;
+    // Required to open a directory handle
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_WRITE_PROTECT: i32 = 19;
+
+    let result = OpenOptions::new()
+        .access_mode(FILE_ADD_FILE)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(dir);
+    match result {
+        Ok(_) => Some(true),
+        Err(e) => match e.raw_os_error() {
+            Some(ERROR_ACCESS_DENIED | ERROR_WRITE_PROTECT) => Some(false),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_family = "unix")))]
+fn directory_access(_dir: &Path) -> Option<bool> {
+    None
+}
+
+// Last resort: creates and removes a test file. Visible to sync clients and changes the
+// directory's modification time.
+fn probe_directory(dir: &Path) -> bool {
     let test_file = dir.join(format!(".write_test_{}", std::process::id()));
     match fs::File::create(&test_file) {
         Ok(file) => {
@@ -41,9 +274,107 @@ fn can_open_for_write(path: &Path) -> bool {
     OpenOptions::new().write(true).open(path).is_ok()
 }
 
+// Opens the file for deletion while sharing everything, which fails only when another
+// handle denies delete sharing - the same condition that makes a rename fail
+#[cfg(target_os = "windows")]
+fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const DELETE: u32 = 0x0001_0000;
+    const FILE_SHARE_ALL: u32 = 0x0000_0007;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+
+    let result = OpenOptions::new()
+        .access_mode(DELETE)
+        .share_mode(FILE_SHARE_ALL)
+        .open(long_path(path));
+    match result {
+        Ok(_) => false,
+        Err(e) => matches!(
+            e.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+        ),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_locked(_path: &Path) -> bool {
+    false
+}
+
+// Asks the Restart Manager which applications have the file open
+#[cfg(target_os = "windows")]
+fn file_holders(path: &Path) -> Vec<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::{PCWSTR, PWSTR};
+    use windows::Win32::Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS};
+    use windows::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    let wide: Vec<u16> = long_path(path)
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    // SAFETY: the session out-pointer is valid; the key buffer has the documented size
+    if unsafe { RmStartSession(&mut session, None, PWSTR(key.as_mut_ptr())) } != ERROR_SUCCESS {
+        return Vec::new();
+    }
+
+    let files = [PCWSTR(wide.as_ptr())];
+    // SAFETY: `files` points at a NUL-terminated path that outlives the call
+    let registered = unsafe { RmRegisterResources(session, Some(&files), None, None) };
+    let mut infos: Vec<RM_PROCESS_INFO> = Vec::new();
+    let mut result = registered;
+    if registered == ERROR_SUCCESS {
+        // The holder list can grow between the sizing call and the real one
+        for _ in 0..3 {
+            let mut needed = 0u32;
+            let mut count = infos.len() as u32;
+            let mut reasons = 0u32;
+            let buffer = (!infos.is_empty()).then(|| infos.as_mut_ptr());
+            // SAFETY: `buffer` is absent or has room for `count` entries
+            result = unsafe { RmGetList(session, &mut needed, &mut count, buffer, &mut reasons) };
+            if result != ERROR_MORE_DATA {
+                infos.truncate(count as usize);
+                break;
+            }
+            infos = vec![RM_PROCESS_INFO::default(); needed as usize];
+        }
+    }
+    // SAFETY: the session was started above and is ended exactly once
+    let _ = unsafe { RmEndSession(session) };
+
+    if result != ERROR_SUCCESS {
+        return Vec::new();
+    }
+    infos
+        .iter()
+        .map(|info| {
+            let len = info
+                .strAppName
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(info.strAppName.len());
+            String::from_utf16_lossy(&info.strAppName[..len])
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn file_holders(_path: &Path) -> Vec<String> {
+    Vec::new()
+}
+
 // Checks if running as admin (Windows)
 #[cfg(target_os = "windows")]
-fn is_running_as_admin() -> bool {
+pub fn is_running_as_admin() -> bool {
     use windows::Win32::Foundation::BOOL;
     use windows::Win32::Security::{
         AllocateAndInitializeSid, CheckTokenMembership, FreeSid, PSID, SID_IDENTIFIER_AUTHORITY,
@@ -92,13 +423,84 @@ fn is_running_as_admin() -> bool {
 
 // Checks if running as root (Unix)
 #[cfg(target_family = "unix")]
-fn is_running_as_admin() -> bool {
+pub fn is_running_as_admin() -> bool {
     // SAFETY: getuid() is always safe to call
     unsafe { libc::getuid() == 0 }
 }
 
 // Fallback for other platforms
 #[cfg(not(any(target_os = "windows", target_family = "unix")))]
-fn is_running_as_admin() -> bool {
+pub fn is_running_as_admin() -> bool {
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(dir: &Path) -> Vec<PathBuf> {
+        let mut names: Vec<PathBuf> = fs::read_dir(dir)
+            .expect("listing")
+            .map(|e| e.expect("entry").path())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn writable_directories_are_checked_without_a_probe_file() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        fs::write(dir.path().join("a.txt"), "a").expect("test file");
+        let modified = fs::metadata(dir.path()).and_then(|m| m.modified()).ok();
+
+        assert!(directory_access(dir.path()).is_some());
+        assert!(can_write_to_directory(dir.path(), false));
+        assert_eq!(entries(dir.path()), [dir.path().join("a.txt")]);
+        assert_eq!(
+            fs::metadata(dir.path()).and_then(|m| m.modified()).ok(),
+            modified
+        );
+    }
+
+    #[test]
+    fn missing_folders_and_files_are_not_writable_directories() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").expect("test file");
+        assert!(!can_write_to_directory(&file, true));
+        assert!(!can_write_to_directory(&dir.path().join("missing"), true));
+    }
+
+    // Root and administrators may write anywhere, so the check only means something
+    // for a regular user
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn read_only_directories_are_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if is_running_as_admin() {
+            return;
+        }
+        let dir = tempfile::tempdir().expect("temp dir");
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).expect("folder");
+        let file = locked.join("a.txt");
+        fs::write(&file, "a").expect("test file");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).expect("read-only");
+
+        assert_eq!(directory_access(&locked), Some(false));
+        assert!(!can_write_to_directory(&locked, true));
+        assert_eq!(entries(&locked), [file]);
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("restore");
+    }
+
+    #[test]
+    fn files_in_writable_folders_can_be_modified() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").expect("test file");
+        assert!(unmodifiable_files([file.as_path()], false).is_empty());
+        assert!(locked_files([file.as_path()]).is_empty());
+    }
+}
@@ -1,16 +1,104 @@
 // Settings persistence using SQLite
 
-use crate::theme::{MAX_PATTERN_LENGTH, MAX_TEMPLATE_LENGTH};
-use rusqlite::{Connection, Result as SqlResult};
-use std::path::PathBuf;
+use crate::file_ops::open_path;
+use crate::theme::{
+    DEFAULT_IDLE_QUIET_SECS, DEFAULT_PREVIEW_BUDGET_MS, MAX_PATTERN_LENGTH, MAX_RECENT_FOLDERS,
+    MAX_RULES, MAX_TEMPLATE_LENGTH, MIN_PREVIEW_BUDGET_MS,
+};
+use crate::types::{
+    CaseLocale, Counter, FolderOptions, FolderPreset, NameConvention, NumberGrouping, PreviewArrow,
+    RenameRule, SortOrder, TermPreset,
+};
+use anyhow::{Context, Result};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags, Result as SqlResult};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Layout of the settings database, stored as its user_version. Databases written before
+// versioning read as 0 and have the same layout as version 1.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Iteration template used until the user enters another
+pub const DEFAULT_TEMPLATE: &str = "{n}";
 
 pub struct Settings {
     pub dark_mode: bool,
     pub regex_mode: bool,
     pub case_sensitive: bool,
+    pub case_locale: CaseLocale,
+    pub lowercase_result: bool,
+    pub whole_word: bool,
     pub template: String,
     pub start_number: u32,
     pub padding: usize,
+    // {n1}, {n2}... counters in order
+    pub counters: Vec<Counter>,
+    pub skip_existing: bool,
+    // Leave names already following the template alone and number around them
+    pub keep_numbered: bool,
+    // Take the padding from numbered names already in the folder when there are any
+    pub match_existing_width: bool,
+    // Pad {n} to the widest number in the batch when the padding is too small
+    pub fit_padding: bool,
+    pub compound_extensions: bool,
+    pub reverse_numbering: bool,
+    // Start each template's numbering where its last successful rename left off
+    pub continue_numbering: bool,
+    // Next start number per template, recorded after each successful rename
+    pub numbering_continuations: HashMap<String, u32>,
+    pub number_grouping: NumberGrouping,
+    pub name_convention: NameConvention,
+    pub preview_budget_ms: u64,
+    pub ordered_commit: bool,
+    pub preview_arrow: PreviewArrow,
+    pub truncate_names: bool,
+    // Preview nested under folders rather than one flat list
+    pub preview_tree: bool,
+    // New names shown in monospace with stems padded to line up; display only
+    pub align_preview_names: bool,
+    // Maximum characters per name in the preview; 0 fits the pane width
+    pub max_name_width: usize,
+    // Files kept from each scanned folder in sort order; 0 keeps up to the global limit
+    pub per_folder_limit: usize,
+    // Ask before renaming more files than this at once; 0 never asks
+    pub confirm_threshold: usize,
+    // Seconds the folders must go without changes before an idle run starts
+    pub idle_quiet_secs: u64,
+    pub sort_order: SortOrder,
+    pub import_keep_order: bool,
+    pub number_duplicates: bool,
+    // Fall back to creating a test file when a folder's access can't be queried
+    pub probe_directories: bool,
+    // Check every renamed file on disk once the batch is done
+    pub verify_renames: bool,
+    // Flag names that differ from existing files only in letter case even where the OS is
+    // case-sensitive, for folders that will move to Windows or macOS
+    pub strict_case_check: bool,
+    // Keep each renamed file's original name in an extended attribute or sidecar file
+    pub record_original_names: bool,
+    pub notify_on_completion: bool,
+    // Extra protected folders, separated like the PATH variable
+    pub protected_roots: String,
+    // Command run after each batch with the path of a JSON results file; empty for none
+    pub post_rename_hook: String,
+    // Extensions previewed and renamed, e.g. "jpg, png"; empty for every file
+    pub extension_filter: String,
+    // Custom one-click filter groups ("Images: jpg png; Video: mp4"); empty for the built-in ones
+    pub extension_groups: String,
+    // Put between the folder name and the file name by "Add Folder Prefix"
+    pub folder_separator: String,
+    // Templates per extension ("jpg png: image_{n}; pdf: doc_{n}")
+    pub extension_templates: String,
+    // Extension given by the change-extension mode; empty removes extensions
+    pub new_extension: String,
+    // Regex rules of the rules mode, in the order they're tried
+    pub rename_rules: Vec<RenameRule>,
+    pub term_presets: Vec<TermPreset>,
+    // Term preset names keyed by the folder (and its subfolders) they load for
+    pub folder_presets: Vec<FolderPreset>,
+    // Most recently scanned folders, newest first
+    pub recent_folders: Vec<PathBuf>,
 }
 
 impl Default for Settings {
@@ -19,9 +107,52 @@ impl Default for Settings {
             dark_mode: true,
             regex_mode: false,
             case_sensitive: true,
-            template: String::from("{n}"),
+            case_locale: CaseLocale::default(),
+            lowercase_result: false,
+            whole_word: false,
+            template: DEFAULT_TEMPLATE.to_string(),
             start_number: 1,
             padding: 3,
+            counters: Vec::new(),
+            skip_existing: false,
+            keep_numbered: false,
+            match_existing_width: false,
+            fit_padding: false,
+            compound_extensions: false,
+            reverse_numbering: false,
+            continue_numbering: false,
+            numbering_continuations: HashMap::new(),
+            number_grouping: NumberGrouping::default(),
+            name_convention: NameConvention::default(),
+            preview_budget_ms: DEFAULT_PREVIEW_BUDGET_MS,
+            ordered_commit: false,
+            preview_arrow: PreviewArrow::default(),
+            truncate_names: true,
+            preview_tree: true,
+            align_preview_names: false,
+            max_name_width: 0,
+            per_folder_limit: 0,
+            confirm_threshold: 0,
+            idle_quiet_secs: DEFAULT_IDLE_QUIET_SECS,
+            sort_order: SortOrder::default(),
+            import_keep_order: false,
+            number_duplicates: false,
+            probe_directories: false,
+            verify_renames: true,
+            strict_case_check: false,
+            record_original_names: false,
+            notify_on_completion: false,
+            protected_roots: String::new(),
+            post_rename_hook: String::new(),
+            extension_filter: String::new(),
+            extension_groups: String::new(),
+            extension_templates: String::new(),
+            new_extension: String::new(),
+            rename_rules: Vec::new(),
+            folder_separator: String::from("_"),
+            term_presets: Vec::new(),
+            folder_presets: Vec::new(),
+            recent_folders: Vec::new(),
         }
     }
 }
@@ -41,7 +172,12 @@ impl Settings {
 
 // Returns path to settings database
 fn get_db_path() -> Option<PathBuf> {
-    dirs::data_local_dir().map(|p| p.join("file-rename-plus").join("settings.db"))
+    data_dir().map(|p| p.join("settings.db"))
+}
+
+// Folder holding the settings database
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|p| p.join("file-rename-plus"))
 }
 
 // Initializes database and creates tables if needed
@@ -53,6 +189,100 @@ fn init_db(conn: &Connection) -> SqlResult<()> {
         )",
         [],
     )?;
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version < SCHEMA_VERSION {
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    }
+    Ok(())
+}
+
+// Opens the data folder in the system file manager, creating it if needed
+pub fn open_data_folder() -> Result<PathBuf> {
+    let dir = data_dir().context("No data folder on this system")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Could not create {}", dir.display()))?;
+    open_path(&dir)?;
+    Ok(dir)
+}
+
+// Copies the settings database to `dest` with SQLite's online backup, so a save running
+// at the same time can't leave a half-written copy
+pub fn backup_settings(dest: &Path) -> Result<()> {
+    let db_path = get_db_path().context("No data folder on this system")?;
+    backup_database(&db_path, dest)
+}
+
+// The copy is written next to `dest` and renamed over it once complete, so a failed backup
+// leaves whatever `dest` held (replacing it was confirmed in the save dialog) untouched
+fn backup_database(db_path: &Path, dest: &Path) -> Result<()> {
+    if let (Ok(db), Ok(target)) = (db_path.canonicalize(), dest.canonicalize()) {
+        if db == target {
+            anyhow::bail!("A backup can't replace the settings database it is made from");
+        }
+    }
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Could not open settings at {}", db_path.display()))?;
+
+    let name = dest
+        .file_name()
+        .with_context(|| format!("{} is not a file name", dest.display()))?;
+    let temp = dest.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    // A leftover from an earlier attempt would be backed up into rather than replaced
+    let _ = std::fs::remove_file(&temp);
+    let written = conn
+        .backup(DatabaseName::Main, &temp, None)
+        .with_context(|| format!("Could not write backup to {}", dest.display()))
+        .and_then(|()| {
+            std::fs::rename(&temp, dest)
+                .with_context(|| format!("Could not replace {}", dest.display()))
+        });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    written
+}
+
+// Replaces the settings database with the backup at `src` after checking it is a settings
+// database this version can read
+pub fn restore_settings(src: &Path) -> Result<()> {
+    check_backup(src)?;
+    let db_path = get_db_path().context("No data folder on this system")?;
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create {}", parent.display()))?;
+    }
+    let mut conn = Connection::open(&db_path)
+        .with_context(|| format!("Could not open settings at {}", db_path.display()))?;
+    conn.restore(DatabaseName::Main, src, None::<fn(Progress)>)
+        .with_context(|| format!("Could not restore from {}", src.display()))?;
+    Ok(())
+}
+
+// Fails unless `src` is a settings database with a schema no newer than this version's
+fn check_backup(src: &Path) -> Result<()> {
+    let not_settings = || format!("{} is not a settings backup", src.display());
+    let conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(not_settings)?;
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .with_context(not_settings)?;
+    if version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "{} was made by a newer version (schema {}, this version reads up to {})",
+            src.display(),
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    conn.query_row(
+        "SELECT COUNT(*) FROM settings WHERE key IS NOT NULL AND value IS NOT NULL",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .with_context(not_settings)?;
     Ok(())
 }
 
@@ -84,6 +314,15 @@ pub fn load_settings() -> Settings {
     if let Ok(val) = get_setting(&conn, "case_sensitive") {
         settings.case_sensitive = val == "true";
     }
+    if let Ok(val) = get_setting(&conn, "case_locale") {
+        settings.case_locale = CaseLocale::from_key(&val).unwrap_or_default();
+    }
+    if let Ok(val) = get_setting(&conn, "lowercase_result") {
+        settings.lowercase_result = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "whole_word") {
+        settings.whole_word = val == "true";
+    }
     if let Ok(val) = get_setting(&conn, "template") {
         settings.template = val.chars().take(MAX_TEMPLATE_LENGTH).collect();
     }
@@ -93,6 +332,121 @@ pub fn load_settings() -> Settings {
     if let Ok(val) = get_setting(&conn, "padding") {
         settings.padding = val.parse().unwrap_or(3).min(10);
     }
+    if let Ok(val) = get_setting(&conn, "counters") {
+        settings.counters = val.lines().filter_map(parse_counter).collect();
+    }
+    if let Ok(val) = get_setting(&conn, "skip_existing") {
+        settings.skip_existing = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "keep_numbered") {
+        settings.keep_numbered = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "match_existing_width") {
+        settings.match_existing_width = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "fit_padding") {
+        settings.fit_padding = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "compound_extensions") {
+        settings.compound_extensions = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "reverse_numbering") {
+        settings.reverse_numbering = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "continue_numbering") {
+        settings.continue_numbering = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "number_grouping") {
+        settings.number_grouping = NumberGrouping::from_key(&val).unwrap_or_default();
+    }
+    if let Ok(val) = get_setting(&conn, "name_convention") {
+        settings.name_convention = NameConvention::from_key(&val).unwrap_or_default();
+    }
+    if let Ok(val) = get_setting(&conn, "preview_budget_ms") {
+        settings.preview_budget_ms = val
+            .parse()
+            .unwrap_or(DEFAULT_PREVIEW_BUDGET_MS)
+            .max(MIN_PREVIEW_BUDGET_MS);
+    }
+    if let Ok(val) = get_setting(&conn, "ordered_commit") {
+        settings.ordered_commit = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "preview_arrow") {
+        settings.preview_arrow = PreviewArrow::from_key(&val).unwrap_or_default();
+    }
+    if let Ok(val) = get_setting(&conn, "sort_order") {
+        settings.sort_order = SortOrder::from_key(&val).unwrap_or_default();
+    }
+    if let Ok(val) = get_setting(&conn, "truncate_names") {
+        settings.truncate_names = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "preview_tree") {
+        settings.preview_tree = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "align_preview_names") {
+        settings.align_preview_names = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "max_name_width") {
+        settings.max_name_width = val.parse().unwrap_or(0);
+    }
+    if let Ok(val) = get_setting(&conn, "per_folder_limit") {
+        settings.per_folder_limit = val.parse().unwrap_or(0);
+    }
+    if let Ok(val) = get_setting(&conn, "confirm_threshold") {
+        settings.confirm_threshold = val.parse().unwrap_or(0);
+    }
+    if let Ok(val) = get_setting(&conn, "idle_quiet_secs") {
+        settings.idle_quiet_secs = val.parse().unwrap_or(DEFAULT_IDLE_QUIET_SECS);
+    }
+    if let Ok(val) = get_setting(&conn, "import_keep_order") {
+        settings.import_keep_order = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "number_duplicates") {
+        settings.number_duplicates = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "probe_directories") {
+        settings.probe_directories = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "verify_renames") {
+        settings.verify_renames = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "strict_case_check") {
+        settings.strict_case_check = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "record_original_names") {
+        settings.record_original_names = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "notify_on_completion") {
+        settings.notify_on_completion = val == "true";
+    }
+    if let Ok(val) = get_setting(&conn, "post_rename_hook") {
+        settings.post_rename_hook = val;
+    }
+    if let Ok(val) = get_setting(&conn, "protected_roots") {
+        settings.protected_roots = val;
+    }
+    if let Ok(val) = get_setting(&conn, "extension_filter") {
+        settings.extension_filter = val;
+    }
+    if let Ok(val) = get_setting(&conn, "extension_groups") {
+        settings.extension_groups = val;
+    }
+    if let Ok(val) = get_setting(&conn, "extension_templates") {
+        settings.extension_templates = val;
+    }
+    if let Ok(val) = get_setting(&conn, "new_extension") {
+        settings.new_extension = val;
+    }
+    if let Ok(val) = get_setting(&conn, "rename_rules") {
+        settings.rename_rules = val.lines().filter_map(parse_rule).take(MAX_RULES).collect();
+    }
+    if let Ok(val) = get_setting(&conn, "folder_separator") {
+        settings.folder_separator = val;
+    }
+    settings.term_presets = load_term_presets(&conn).unwrap_or_default();
+    settings.folder_presets = load_folder_presets(&conn).unwrap_or_default();
+    settings.numbering_continuations = load_continuations(&conn).unwrap_or_default();
+    settings.recent_folders = load_recent_folders(&conn).unwrap_or_default();
 
     settings
 }
@@ -126,9 +480,330 @@ pub fn save_settings(settings: &Settings) {
         "case_sensitive",
         &settings.case_sensitive.to_string(),
     );
+    let _ = set_setting(&conn, "case_locale", settings.case_locale.key());
+    let _ = set_setting(
+        &conn,
+        "lowercase_result",
+        &settings.lowercase_result.to_string(),
+    );
+    let _ = set_setting(&conn, "whole_word", &settings.whole_word.to_string());
     let _ = set_setting(&conn, "template", &template);
     let _ = set_setting(&conn, "start_number", &settings.start_number.to_string());
     let _ = set_setting(&conn, "padding", &settings.padding.min(10).to_string());
+    let counters: Vec<String> = settings
+        .counters
+        .iter()
+        .map(|c| format!("{},{},{}", c.start, c.step, c.padding.min(10)))
+        .collect();
+    let _ = set_setting(&conn, "counters", &counters.join("\n"));
+    let _ = set_setting(&conn, "skip_existing", &settings.skip_existing.to_string());
+    let _ = set_setting(&conn, "keep_numbered", &settings.keep_numbered.to_string());
+    let _ = set_setting(
+        &conn,
+        "match_existing_width",
+        &settings.match_existing_width.to_string(),
+    );
+    let _ = set_setting(&conn, "fit_padding", &settings.fit_padding.to_string());
+    let _ = set_setting(
+        &conn,
+        "compound_extensions",
+        &settings.compound_extensions.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "reverse_numbering",
+        &settings.reverse_numbering.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "continue_numbering",
+        &settings.continue_numbering.to_string(),
+    );
+    let _ = set_setting(&conn, "number_grouping", settings.number_grouping.key());
+    let _ = set_setting(&conn, "name_convention", settings.name_convention.key());
+    let _ = set_setting(
+        &conn,
+        "preview_budget_ms",
+        &settings.preview_budget_ms.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "ordered_commit",
+        &settings.ordered_commit.to_string(),
+    );
+    let _ = set_setting(&conn, "preview_arrow", settings.preview_arrow.key());
+    let _ = set_setting(&conn, "sort_order", settings.sort_order.key());
+    let _ = set_setting(
+        &conn,
+        "truncate_names",
+        &settings.truncate_names.to_string(),
+    );
+    let _ = set_setting(&conn, "preview_tree", &settings.preview_tree.to_string());
+    let _ = set_setting(
+        &conn,
+        "align_preview_names",
+        &settings.align_preview_names.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "max_name_width",
+        &settings.max_name_width.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "per_folder_limit",
+        &settings.per_folder_limit.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "confirm_threshold",
+        &settings.confirm_threshold.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "idle_quiet_secs",
+        &settings.idle_quiet_secs.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "import_keep_order",
+        &settings.import_keep_order.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "number_duplicates",
+        &settings.number_duplicates.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "probe_directories",
+        &settings.probe_directories.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "verify_renames",
+        &settings.verify_renames.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "strict_case_check",
+        &settings.strict_case_check.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "record_original_names",
+        &settings.record_original_names.to_string(),
+    );
+    let _ = set_setting(
+        &conn,
+        "notify_on_completion",
+        &settings.notify_on_completion.to_string(),
+    );
+    let _ = set_setting(&conn, "protected_roots", &settings.protected_roots);
+    let _ = set_setting(&conn, "post_rename_hook", &settings.post_rename_hook);
+    let _ = set_setting(&conn, "extension_filter", &settings.extension_filter);
+    let _ = set_setting(&conn, "extension_groups", &settings.extension_groups);
+    let _ = set_setting(&conn, "extension_templates", &settings.extension_templates);
+    let _ = set_setting(&conn, "new_extension", &settings.new_extension);
+    // One rule per line, pattern and template separated by a tab, which neither can hold
+    let rules: Vec<String> = settings
+        .rename_rules
+        .iter()
+        .map(|r| format!("{}\t{}", r.pattern, r.template))
+        .collect();
+    let _ = set_setting(&conn, "rename_rules", &rules.join("\n"));
+    let _ = set_setting(&conn, "folder_separator", &settings.folder_separator);
+    let _ = save_term_presets(&conn, &settings.term_presets);
+    let _ = save_folder_presets(&conn, &settings.folder_presets);
+    let _ = save_continuations(&conn, &settings.numbering_continuations);
+    let _ = save_recent_folders(&conn, &settings.recent_folders);
+}
+
+// Reads a counter stored as "start,step,padding"
+fn parse_counter(line: &str) -> Option<Counter> {
+    let mut fields = line.split(',').map(str::trim);
+    Some(Counter {
+        start: fields.next()?.parse().ok()?,
+        step: fields.next()?.parse().ok()?,
+        padding: fields.next()?.parse::<usize>().ok()?.min(10),
+    })
+}
+
+// Reads a rule stored as "pattern<TAB>template"
+fn parse_rule(line: &str) -> Option<RenameRule> {
+    let (pattern, template) = line.split_once('\t')?;
+    Some(RenameRule {
+        pattern: pattern.chars().take(MAX_PATTERN_LENGTH).collect(),
+        template: template.chars().take(MAX_TEMPLATE_LENGTH).collect(),
+    })
+}
+
+// Each preset is stored under its own key with one term per line
+const TERM_PRESET_PREFIX: &str = "term_preset:";
+
+fn load_term_presets(conn: &Connection) -> SqlResult<Vec<TermPreset>> {
+    let mut stmt =
+        conn.prepare("SELECT key, value FROM settings WHERE key LIKE ?1 ORDER BY key")?;
+    let rows = stmt.query_map([format!("{}%", TERM_PRESET_PREFIX)], |row| {
+        let key: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        Ok(TermPreset {
+            name: key[TERM_PRESET_PREFIX.len()..].to_string(),
+            terms: value.lines().map(str::to_string).collect(),
+        })
+    })?;
+    rows.collect()
+}
+
+// Replaces all stored presets so deleted ones don't linger
+fn save_term_presets(conn: &Connection, presets: &[TermPreset]) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM settings WHERE key LIKE ?1",
+        [format!("{}%", TERM_PRESET_PREFIX)],
+    )?;
+    for preset in presets {
+        let key = format!("{}{}", TERM_PRESET_PREFIX, preset.name);
+        set_setting(conn, &key, &preset.terms.join("\n"))?;
+    }
+    Ok(())
+}
+
+// Each association is stored under its folder path: a term preset link with the preset
+// name as the value, a numbering link as "padding<TAB>template"
+const FOLDER_PRESET_PREFIX: &str = "folder_preset:";
+const FOLDER_NUMBERING_PREFIX: &str = "folder_numbering:";
+
+fn load_folder_presets(conn: &Connection) -> SqlResult<Vec<FolderPreset>> {
+    let mut stmt =
+        conn.prepare("SELECT key, value FROM settings WHERE key LIKE ?1 OR key LIKE ?2")?;
+    let rows = stmt.query_map(
+        [
+            format!("{}%", FOLDER_PRESET_PREFIX),
+            format!("{}%", FOLDER_NUMBERING_PREFIX),
+        ],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+    )?;
+    let mut presets = Vec::new();
+    for row in rows {
+        let (key, value) = row?;
+        let preset = if let Some(folder) = key.strip_prefix(FOLDER_PRESET_PREFIX) {
+            Some(FolderPreset {
+                folder: PathBuf::from(folder),
+                options: FolderOptions::Terms(value),
+            })
+        } else {
+            key.strip_prefix(FOLDER_NUMBERING_PREFIX)
+                .and_then(|folder| {
+                    Some(FolderPreset {
+                        folder: PathBuf::from(folder),
+                        options: parse_folder_numbering(&value)?,
+                    })
+                })
+        };
+        presets.extend(preset);
+    }
+    presets.sort_by(|a, b| a.folder.cmp(&b.folder));
+    Ok(presets)
+}
+
+// "4\tIMG_{n}" back into the template and padding, within the same limits as typed ones
+fn parse_folder_numbering(value: &str) -> Option<FolderOptions> {
+    let (padding, template) = value.split_once('\t')?;
+    Some(FolderOptions::Numbering {
+        template: template.chars().take(MAX_TEMPLATE_LENGTH).collect(),
+        padding: padding.parse().ok()?,
+    })
+}
+
+fn save_folder_presets(conn: &Connection, associations: &[FolderPreset]) -> SqlResult<()> {
+    for prefix in [FOLDER_PRESET_PREFIX, FOLDER_NUMBERING_PREFIX] {
+        conn.execute(
+            "DELETE FROM settings WHERE key LIKE ?1",
+            [format!("{}%", prefix)],
+        )?;
+    }
+    for association in associations {
+        // Paths that aren't valid UTF-8 couldn't be read back as the same folder
+        let Some(folder) = association.folder.to_str() else {
+            continue;
+        };
+        match &association.options {
+            FolderOptions::Terms(preset) => {
+                set_setting(conn, &format!("{}{}", FOLDER_PRESET_PREFIX, folder), preset)?
+            }
+            FolderOptions::Numbering { template, padding } => set_setting(
+                conn,
+                &format!("{}{}", FOLDER_NUMBERING_PREFIX, folder),
+                &format!("{}\t{}", padding, template),
+            )?,
+        }
+    }
+    Ok(())
+}
+
+// Numbering continuations are stored one per template, keyed by the template itself
+const CONTINUATION_PREFIX: &str = "next_number:";
+
+fn load_continuations(conn: &Connection) -> SqlResult<HashMap<String, u32>> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings WHERE key LIKE ?1")?;
+    let rows = stmt.query_map([format!("{}%", CONTINUATION_PREFIX)], |row| {
+        let key: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        Ok((
+            key[CONTINUATION_PREFIX.len()..].to_string(),
+            value.parse().ok(),
+        ))
+    })?;
+    let mut continuations = HashMap::new();
+    for row in rows {
+        if let (template, Some(next)) = row? {
+            continuations.insert(template, next);
+        }
+    }
+    Ok(continuations)
+}
+
+// Replaces all stored continuations so reset ones don't linger
+fn save_continuations(conn: &Connection, continuations: &HashMap<String, u32>) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM settings WHERE key LIKE ?1",
+        [format!("{}%", CONTINUATION_PREFIX)],
+    )?;
+    for (template, next) in continuations {
+        let key = format!("{}{}", CONTINUATION_PREFIX, template);
+        set_setting(conn, &key, &next.to_string())?;
+    }
+    Ok(())
+}
+
+// Recent folders are stored one per key; the zero-padded index keeps them in order
+const RECENT_FOLDER_PREFIX: &str = "recent_folder:";
+
+fn load_recent_folders(conn: &Connection) -> SqlResult<Vec<PathBuf>> {
+    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key LIKE ?1 ORDER BY key")?;
+    let rows = stmt.query_map([format!("{}%", RECENT_FOLDER_PREFIX)], |row| {
+        row.get::<_, String>(0).map(PathBuf::from)
+    })?;
+    let mut folders = rows.collect::<SqlResult<Vec<_>>>()?;
+    folders.truncate(MAX_RECENT_FOLDERS);
+    Ok(folders)
+}
+
+fn save_recent_folders(conn: &Connection, folders: &[PathBuf]) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM settings WHERE key LIKE ?1",
+        [format!("{}%", RECENT_FOLDER_PREFIX)],
+    )?;
+    let storable = folders
+        .iter()
+        .filter_map(|folder| folder.to_str())
+        // Longer values would be truncated into a different path
+        .filter(|folder| folder.chars().count() <= MAX_PATTERN_LENGTH)
+        .take(MAX_RECENT_FOLDERS);
+    for (i, folder) in storable.enumerate() {
+        set_setting(conn, &format!("{}{:02}", RECENT_FOLDER_PREFIX, i), folder)?;
+    }
+    Ok(())
 }
 
 fn get_setting(conn: &Connection, key: &str) -> SqlResult<String> {
@@ -146,3 +821,64 @@ fn set_setting(conn: &Connection, key: &str, value: &str) -> SqlResult<()> {
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings_db(path: &Path) {
+        let conn = Connection::open(path).expect("database");
+        init_db(&conn).expect("schema");
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .expect("listing")
+            .map(|e| e.expect("entry").file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn backups_replace_the_destination_in_one_step() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let db = dir.path().join("settings.db");
+        settings_db(&db);
+        let dest = dir.path().join("backup.db");
+        std::fs::write(&dest, "old backup").expect("earlier file");
+
+        backup_database(&db, &dest).expect("backup");
+        check_backup(&dest).expect("readable backup");
+        assert_eq!(names(dir.path()), ["backup.db", "settings.db"]);
+    }
+
+    #[test]
+    fn backups_never_overwrite_the_live_database() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let db = dir.path().join("settings.db");
+        settings_db(&db);
+        let before = std::fs::read(&db).expect("contents");
+
+        // Also when reached through another spelling of the path
+        let same = dir.path().join(".").join("settings.db");
+        assert!(backup_database(&db, &same).is_err());
+        assert_eq!(std::fs::read(&db).expect("contents"), before);
+        assert_eq!(names(dir.path()), ["settings.db"]);
+    }
+
+    #[test]
+    fn failed_backups_leave_the_destination_alone() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let dest = dir.path().join("backup.db");
+        std::fs::write(&dest, "old backup").expect("earlier file");
+
+        let missing = dir.path().join("missing.db");
+        assert!(backup_database(&missing, &dest).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&dest).expect("contents"),
+            "old backup"
+        );
+        assert_eq!(names(dir.path()), ["backup.db"]);
+    }
+}
@@ -23,11 +23,42 @@ pub const COLOR_SUCCESS: Color = Color::from_rgb(0.3, 1.0, 0.5);
 pub const COLOR_INFO: Color = Color::from_rgb(0.3, 0.8, 1.0);
 pub const COLOR_MUTED_DARK: Color = Color::from_rgb(0.5, 0.5, 0.5);
 pub const COLOR_CONFLICT: Color = Color::from_rgb(1.0, 0.3, 0.3);
+pub const COLOR_WARNING: Color = Color::from_rgb(1.0, 0.75, 0.3);
+
+// Darker variants that stay readable on the light theme
+pub const COLOR_ERROR_LIGHT: Color = Color::from_rgb(0.75, 0.1, 0.1);
+pub const COLOR_SUCCESS_LIGHT: Color = Color::from_rgb(0.05, 0.5, 0.2);
+pub const COLOR_WARNING_LIGHT: Color = Color::from_rgb(0.65, 0.4, 0.0);
+pub const COLOR_MUTED_LIGHT: Color = Color::from_rgb(0.3, 0.3, 0.3);
+
+// Backgrounds marking regex groups in the capture view: the match outside any group, then
+// $1, $2... cycling through the rest. Translucent so text stays readable on both themes.
+pub const GROUP_COLORS: [Color; 6] = [
+    Color::from_rgba(0.5, 0.5, 0.5, 0.35),
+    Color::from_rgba(0.2, 0.6, 1.0, 0.45),
+    Color::from_rgba(1.0, 0.6, 0.1, 0.45),
+    Color::from_rgba(0.3, 0.85, 0.4, 0.45),
+    Color::from_rgba(0.85, 0.35, 0.9, 0.45),
+    Color::from_rgba(1.0, 0.3, 0.35, 0.45),
+];
 
 // Input limits
 pub const MAX_PATTERN_LENGTH: usize = 1024;
 pub const MAX_TEMPLATE_LENGTH: usize = 256;
 pub const MAX_FILES: usize = 10000;
+pub const MAX_RECENT_FOLDERS: usize = 10;
+pub const MAX_COUNTERS: usize = 9;
+// Rules in the rename rules mode
+pub const MAX_RULES: usize = 20;
+// Matching names shown in the regex capture view
+pub const CAPTURE_VIEW_FILES: usize = 5;
+
+// Preview time budget (milliseconds)
+pub const DEFAULT_PREVIEW_BUDGET_MS: u64 = 2000;
+pub const MIN_PREVIEW_BUDGET_MS: u64 = 100;
+
+// Seconds without changes in the watched folders before an idle run starts
+pub const DEFAULT_IDLE_QUIET_SECS: u64 = 30;
 
 // Window
 pub const WINDOW_WIDTH: f32 = 900.0;
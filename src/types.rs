@@ -1,6 +1,6 @@
 // Shared types used across modules
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Represents a file entry in the list with shared name
@@ -8,6 +8,18 @@ use std::sync::Arc;
 pub struct FileEntry {
     pub path: PathBuf,
     pub name: Arc<String>,
+    pub identity: Option<FileIdentity>,
+    // Keeps its position when the list is sorted or reordered, and survives Clear
+    pub pinned: bool,
+    // Position in the order files were added to the list; 0 until listed
+    pub added: u64,
+}
+
+/// Filesystem identity (device + inode / volume + file index), recorded only for hard-linked files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileIdentity {
+    pub device: u64,
+    pub index: u64,
 }
 
 /// Represents a preview of a rename operation
@@ -16,15 +28,201 @@ pub struct RenamePreview {
     pub original_path: PathBuf,
     pub original_name: Arc<String>,
     pub new_name: String,
-    pub has_conflict: bool,
+    // Why the new name clashes with another; empty when it doesn't
+    pub conflicts: Vec<ConflictReason>,
+    pub invalid_reason: Option<String>,
+    pub match_count: usize,
+    // Name was supplied by hand (pasted) rather than computed
+    pub edited: bool,
+    // Things that may not turn out as shown; unlike conflicts they don't stop the rename
+    pub warnings: Vec<NameWarning>,
 }
 
-/// Application operating modes
+/// Why a usable new name may still not turn out as the preview shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameWarning {
+    // More bytes than most filesystems allow in one name
+    TooLong,
+    // Windows drops a trailing dot or space, so the name on disk would differ
+    TrailingDotOrSpace,
+    // {created} was filled from the modified time, as the creation time isn't recorded
+    ModifiedDateUsed,
+}
+
+impl std::fmt::Display for NameWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameWarning::TooLong => write!(f, "Longer than the 255 bytes most filesystems allow"),
+            NameWarning::TrailingDotOrSpace => {
+                write!(f, "Ends with a dot or space, which Windows drops")
+            }
+            NameWarning::ModifiedDateUsed => write!(
+                f,
+                "No creation time recorded; {{created}} used the modified time"
+            ),
+        }
+    }
+}
+
+/// Why a preview's new name can't be used as it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    // Another file in the batch gets the same name, ignoring letter case
+    DuplicateInBatch,
+    // A file outside the batch already has the name
+    ExistsOnDisk,
+    // A file outside the batch has the name in other letter case, which a case-insensitive
+    // filesystem treats as the same name
+    CaseCollision,
+}
+
+impl std::fmt::Display for ConflictReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictReason::DuplicateInBatch => {
+                write!(f, "Another file in this batch gets the same name")
+            }
+            ConflictReason::ExistsOnDisk => {
+                write!(f, "A file with this name already exists in the folder")
+            }
+            ConflictReason::CaseCollision => write!(
+                f,
+                "Case collision with an existing file whose name differs only in letter case"
+            ),
+        }
+    }
+}
+
+/// Kind of failure behind an error, which picks how the error panel presents it and what
+/// it suggests doing next
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorKind {
+    PermissionDenied,
+    NotFound,
+    // A target name is already taken, on disk or in the batch
+    Conflict,
+    InvalidName,
+    #[default]
+    Other,
+}
+
+impl ErrorKind {
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            ErrorKind::PermissionDenied => Some(
+                "Close programs using the files and clear read-only flags, or run the app \
+                 with rights to change this folder",
+            ),
+            ErrorKind::NotFound => Some(
+                "The file or folder was moved or deleted after it was listed; add the folder \
+                 again to refresh the list",
+            ),
+            ErrorKind::Conflict => {
+                Some("Change the new names or pick a conflict policy, then execute again")
+            }
+            ErrorKind::InvalidName => {
+                Some("Edit the pattern or template so every new name is valid on this system")
+            }
+            ErrorKind::Other => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::PermissionDenied => write!(f, "Permission denied"),
+            ErrorKind::NotFound => write!(f, "Not found"),
+            ErrorKind::Conflict => write!(f, "Name conflict"),
+            ErrorKind::InvalidName => write!(f, "Invalid name"),
+            ErrorKind::Other => write!(f, "Error"),
+        }
+    }
+}
+
+/// Extra iteration counter, written `{n1}`, `{n2}`... in templates. Every counter advances
+/// by its own step once per file, in lockstep with `{n}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Counter {
+    pub start: i64,
+    pub step: i64,
+    pub padding: usize,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self {
+            start: 1,
+            step: 1,
+            padding: 0,
+        }
+    }
+}
+
+/// Groups whose files are numbered separately in iteration mode; `{g}` is the group number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NumberGrouping {
+    #[default]
+    None,
+    Folder,
+    Extension,
+}
+
+impl NumberGrouping {
+    pub const ALL: [NumberGrouping; 3] = [
+        NumberGrouping::None,
+        NumberGrouping::Folder,
+        NumberGrouping::Extension,
+    ];
+
+    // Stable key used for settings persistence
+    pub fn key(self) -> &'static str {
+        match self {
+            NumberGrouping::None => "none",
+            NumberGrouping::Folder => "folder",
+            NumberGrouping::Extension => "extension",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|grouping| grouping.key() == key)
+    }
+}
+
+impl std::fmt::Display for NumberGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberGrouping::None => write!(f, "Whole list"),
+            NumberGrouping::Folder => write!(f, "Each folder"),
+            NumberGrouping::Extension => write!(f, "Each extension"),
+        }
+    }
+}
+
+/// Application operating modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AppMode {
     #[default]
     FindReplace,
     Iteration,
+    Convention,
+    // Whole names replaced by a numbered template chosen by extension
+    ByExtension,
+    // Extensions replaced by one given extension, or removed
+    ChangeExtension,
+    // Whole names replaced by the template of the first regex rule that matches
+    Rules,
+}
+
+impl AppMode {
+    pub const ALL: [AppMode; 6] = [
+        AppMode::FindReplace,
+        AppMode::Iteration,
+        AppMode::Convention,
+        AppMode::ByExtension,
+        AppMode::ChangeExtension,
+        AppMode::Rules,
+    ];
 }
 
 impl std::fmt::Display for AppMode {
@@ -32,6 +230,537 @@ impl std::fmt::Display for AppMode {
         match self {
             AppMode::FindReplace => write!(f, "Find & Replace"),
             AppMode::Iteration => write!(f, "Iteration Numbering"),
+            AppMode::Convention => write!(f, "Naming Convention"),
+            AppMode::ByExtension => write!(f, "Template per Extension"),
+            AppMode::ChangeExtension => write!(f, "Change Extension"),
+            AppMode::Rules => write!(f, "Rename Rules"),
+        }
+    }
+}
+
+/// Target word-joining style for the naming convention mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NameConvention {
+    #[default]
+    Snake,
+    Kebab,
+    Camel,
+    Pascal,
+    // Kebab-case stem with a lowercase extension, ready to upload
+    WebSafe,
+}
+
+impl NameConvention {
+    pub const ALL: [NameConvention; 5] = [
+        NameConvention::Snake,
+        NameConvention::Kebab,
+        NameConvention::Camel,
+        NameConvention::Pascal,
+        NameConvention::WebSafe,
+    ];
+
+    // Stable key used for settings persistence
+    pub fn key(self) -> &'static str {
+        match self {
+            NameConvention::Snake => "snake",
+            NameConvention::Kebab => "kebab",
+            NameConvention::Camel => "camel",
+            NameConvention::Pascal => "pascal",
+            NameConvention::WebSafe => "web_safe",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|convention| convention.key() == key)
+    }
+}
+
+impl std::fmt::Display for NameConvention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameConvention::Snake => write!(f, "snake_case"),
+            NameConvention::Kebab => write!(f, "kebab-case"),
+            NameConvention::Camel => write!(f, "camelCase"),
+            NameConvention::Pascal => write!(f, "PascalCase"),
+            NameConvention::WebSafe => write!(f, "web-safe.jpg"),
+        }
+    }
+}
+
+/// What to do when a new name is already taken by a file outside the batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConflictPolicy {
+    #[default]
+    Abort,
+    Skip,
+    Trash,
+    Overwrite,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [ConflictPolicy; 4] = [
+        ConflictPolicy::Abort,
+        ConflictPolicy::Skip,
+        ConflictPolicy::Trash,
+        ConflictPolicy::Overwrite,
+    ];
+}
+
+impl std::fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictPolicy::Abort => write!(f, "Stop"),
+            ConflictPolicy::Skip => write!(f, "Skip file"),
+            ConflictPolicy::Trash => write!(f, "Trash existing"),
+            ConflictPolicy::Overwrite => write!(f, "Overwrite existing"),
+        }
+    }
+}
+
+/// Case rules used by case-insensitive matching
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CaseLocale {
+    #[default]
+    Unicode,
+    Turkic,
+}
+
+impl CaseLocale {
+    pub const ALL: [CaseLocale; 2] = [CaseLocale::Unicode, CaseLocale::Turkic];
+
+    // Stable key used for settings persistence
+    pub fn key(self) -> &'static str {
+        match self {
+            CaseLocale::Unicode => "unicode",
+            CaseLocale::Turkic => "turkic",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|locale| locale.key() == key)
+    }
+}
+
+impl std::fmt::Display for CaseLocale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaseLocale::Unicode => write!(f, "Unicode case"),
+            CaseLocale::Turkic => write!(f, "Turkish/Azeri case"),
         }
     }
 }
+
+/// Order applied to scanned folders and by the Sort button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    // Numbers compared by value, letters ignoring ASCII case, like File Explorer
+    #[default]
+    Natural,
+    // Plain byte order of the names, as most command-line tools sort
+    Lexicographic,
+    // Directory listing order for scans, addition order for the list. The listing order is
+    // whatever the OS returns, which is neither specified nor stable across changes.
+    AsAdded,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 3] = [
+        SortOrder::Natural,
+        SortOrder::Lexicographic,
+        SortOrder::AsAdded,
+    ];
+
+    // Stable key used for settings persistence
+    pub fn key(self) -> &'static str {
+        match self {
+            SortOrder::Natural => "natural",
+            SortOrder::Lexicographic => "lexicographic",
+            SortOrder::AsAdded => "as_added",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.key() == key)
+    }
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOrder::Natural => write!(f, "Natural"),
+            SortOrder::Lexicographic => write!(f, "Lexicographic"),
+            SortOrder::AsAdded => write!(f, "As added"),
+        }
+    }
+}
+
+/// Which files the file list shows, by what the access check found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessFilter {
+    #[default]
+    All,
+    Writable,
+    Locked,
+}
+
+impl AccessFilter {
+    pub const ALL: [AccessFilter; 3] = [
+        AccessFilter::All,
+        AccessFilter::Writable,
+        AccessFilter::Locked,
+    ];
+
+    pub fn shows(self, locked: bool) -> bool {
+        match self {
+            AccessFilter::All => true,
+            AccessFilter::Writable => !locked,
+            AccessFilter::Locked => locked,
+        }
+    }
+}
+
+impl std::fmt::Display for AccessFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessFilter::All => write!(f, "All files"),
+            AccessFilter::Writable => write!(f, "Writable only"),
+            AccessFilter::Locked => write!(f, "Locked only"),
+        }
+    }
+}
+
+/// Row state the preview can be narrowed to; several combine, showing rows in any of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewFilter {
+    Changed,
+    // Conflicts and invalid names, either of which stops the rename
+    Conflicts,
+    Warnings,
+    Edited,
+}
+
+impl PreviewFilter {
+    pub const ALL: [PreviewFilter; 4] = [
+        PreviewFilter::Changed,
+        PreviewFilter::Conflicts,
+        PreviewFilter::Warnings,
+        PreviewFilter::Edited,
+    ];
+
+    pub fn matches(self, preview: &RenamePreview) -> bool {
+        match self {
+            PreviewFilter::Changed => preview.new_name != *preview.original_name,
+            PreviewFilter::Conflicts => {
+                !preview.conflicts.is_empty() || preview.invalid_reason.is_some()
+            }
+            PreviewFilter::Warnings => !preview.warnings.is_empty(),
+            PreviewFilter::Edited => preview.edited,
+        }
+    }
+}
+
+impl std::fmt::Display for PreviewFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreviewFilter::Changed => write!(f, "Changed"),
+            PreviewFilter::Conflicts => write!(f, "Conflicts"),
+            PreviewFilter::Warnings => write!(f, "Warnings"),
+            PreviewFilter::Edited => write!(f, "Edited"),
+        }
+    }
+}
+
+/// Separator drawn between the original and new name in the preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewArrow {
+    #[default]
+    Ascii,
+    Arrow,
+    DoubleArrow,
+}
+
+impl PreviewArrow {
+    pub const ALL: [PreviewArrow; 3] = [
+        PreviewArrow::Ascii,
+        PreviewArrow::Arrow,
+        PreviewArrow::DoubleArrow,
+    ];
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            PreviewArrow::Ascii => "->",
+            PreviewArrow::Arrow => "→",
+            PreviewArrow::DoubleArrow => "⇒",
+        }
+    }
+
+    // Stable key used for settings persistence
+    pub fn key(self) -> &'static str {
+        match self {
+            PreviewArrow::Ascii => "ascii",
+            PreviewArrow::Arrow => "arrow",
+            PreviewArrow::DoubleArrow => "double_arrow",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|arrow| arrow.key() == key)
+    }
+}
+
+impl std::fmt::Display for PreviewArrow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.glyph())
+    }
+}
+
+/// Named list of literal strings for "match any of" find patterns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermPreset {
+    pub name: String,
+    pub terms: Vec<String>,
+}
+
+/// Options loaded automatically when a folder inside `folder` is scanned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderPreset {
+    pub folder: PathBuf,
+    pub options: FolderOptions,
+}
+
+/// What a linked folder loads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FolderOptions {
+    // Name of a saved term preset, loaded into "match any of"
+    Terms(String),
+    // Numbering template and padding, loaded into iteration mode
+    Numbering { template: String, padding: usize },
+}
+
+impl std::fmt::Display for FolderOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FolderOptions::Terms(preset) => write!(f, "{}", preset),
+            FolderOptions::Numbering { template, padding } => {
+                write!(f, "{}, padding {}", template, padding)
+            }
+        }
+    }
+}
+
+impl FolderPreset {
+    /// Association with the longest folder prefix of `folder`, so a subfolder's own preset
+    /// beats its parent's
+    pub fn for_folder<'a>(presets: &'a [FolderPreset], folder: &Path) -> Option<&'a FolderPreset> {
+        presets
+            .iter()
+            .filter(|p| folder.starts_with(&p.folder))
+            .max_by_key(|p| p.folder.components().count())
+    }
+}
+
+/// Numbering template used for files with any of the given extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionTemplate {
+    pub extensions: Vec<String>,
+    pub template: String,
+}
+
+/// Rule for the rules mode: names matching `pattern` become `template`, with `$1`,
+/// `${name}`... standing for what the pattern's groups captured
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RenameRule {
+    pub pattern: String,
+    pub template: String,
+}
+
+/// Named set of extensions offered as a one-click file filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionGroup {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+// Built-in extension groups, used until custom groups are entered in settings
+pub const DEFAULT_EXTENSION_GROUPS: [(&str, &[&str]); 4] = [
+    (
+        "Images",
+        &[
+            "jpg", "jpeg", "png", "gif", "webp", "bmp", "tif", "tiff", "heic", "svg",
+        ],
+    ),
+    (
+        "Documents",
+        &[
+            "pdf", "doc", "docx", "odt", "rtf", "txt", "md", "xls", "xlsx", "ods", "csv", "ppt",
+            "pptx", "odp",
+        ],
+    ),
+    (
+        "Video",
+        &[
+            "mp4", "mkv", "mov", "avi", "wmv", "webm", "m4v", "flv", "mpg", "mpeg",
+        ],
+    ),
+    (
+        "Audio",
+        &[
+            "mp3", "flac", "wav", "aac", "ogg", "opus", "m4a", "wma", "aiff",
+        ],
+    ),
+];
+
+/// Built-in regex fragment offered from the Find field's snippet menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegexSnippet {
+    pub label: &'static str,
+    pub pattern: &'static str,
+}
+
+impl std::fmt::Display for RegexSnippet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}  {}", self.label, self.pattern)
+    }
+}
+
+pub const REGEX_SNIPPETS: [RegexSnippet; 8] = [
+    RegexSnippet {
+        label: "Digits",
+        pattern: r"\d+",
+    },
+    RegexSnippet {
+        label: "Whitespace",
+        pattern: r"\s+",
+    },
+    RegexSnippet {
+        label: "Leading number",
+        pattern: r"^\d+",
+    },
+    RegexSnippet {
+        label: "Trailing number",
+        pattern: r"\d+$",
+    },
+    RegexSnippet {
+        label: "Date",
+        pattern: r"\d{4}-\d{2}-\d{2}",
+    },
+    RegexSnippet {
+        label: "Word",
+        pattern: r"\w+",
+    },
+    RegexSnippet {
+        label: "Brackets",
+        pattern: r"\s*[\[(][^\])]*[\])]",
+    },
+    RegexSnippet {
+        label: "Any text",
+        pattern: ".*",
+    },
+];
+
+/// Script flavors for exporting a rename plan instead of executing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    Shell,
+    Batch,
+}
+
+impl ScriptFormat {
+    pub const ALL: [ScriptFormat; 2] = [ScriptFormat::Shell, ScriptFormat::Batch];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScriptFormat::Shell => "sh",
+            ScriptFormat::Batch => "bat",
+        }
+    }
+}
+
+impl Default for ScriptFormat {
+    fn default() -> Self {
+        if cfg!(windows) {
+            ScriptFormat::Batch
+        } else {
+            ScriptFormat::Shell
+        }
+    }
+}
+
+impl std::fmt::Display for ScriptFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptFormat::Shell => write!(f, "Shell (.sh)"),
+            ScriptFormat::Batch => write!(f, "Batch (.bat)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rename::{apply_iteration_numbering, NumberingOptions};
+
+    #[test]
+    fn the_deepest_linked_folder_supplies_its_options() {
+        let link = |folder: &str, options: FolderOptions| FolderPreset {
+            folder: PathBuf::from(folder),
+            options,
+        };
+        let photos = FolderOptions::Numbering {
+            template: "IMG_{n}".to_string(),
+            padding: 4,
+        };
+        let presets = [
+            link("/home/me", FolderOptions::Terms("documents".to_string())),
+            link("/home/me/photos", photos.clone()),
+            link(
+                "/home/me/photos/raw",
+                FolderOptions::Terms("raw".to_string()),
+            ),
+        ];
+        let found = |folder: &str| {
+            FolderPreset::for_folder(&presets, Path::new(folder)).map(|p| p.options.clone())
+        };
+
+        assert_eq!(found("/home/me/photos"), Some(photos.clone()));
+        assert_eq!(found("/home/me/photos/2024/june"), Some(photos.clone()));
+        assert_eq!(
+            found("/home/me/photos/raw"),
+            Some(FolderOptions::Terms("raw".to_string()))
+        );
+        assert_eq!(
+            found("/home/me/notes"),
+            Some(FolderOptions::Terms("documents".to_string()))
+        );
+        // Prefixes match whole folder names, not text
+        assert_eq!(
+            found("/home/me/photos-old"),
+            Some(FolderOptions::Terms("documents".to_string()))
+        );
+        assert_eq!(found("/srv"), None);
+
+        // The linked template and padding number the folder's files
+        let FolderOptions::Numbering { template, padding } = photos else {
+            unreachable!()
+        };
+        let folder = Path::new("/home/me/photos");
+        let files = ["DSC01.jpg", "DSC02.jpg"].map(|name| FileEntry {
+            path: folder.join(name),
+            name: Arc::new(name.to_string()),
+            identity: None,
+            pinned: false,
+            added: 0,
+        });
+        let options = NumberingOptions {
+            start_number: 1,
+            padding,
+            ..NumberingOptions::default()
+        };
+        let previews =
+            apply_iteration_numbering(&files, &template, &options, None).expect("numbering");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        assert_eq!(names, ["IMG_0001.jpg", "IMG_0002.jpg"]);
+    }
+}
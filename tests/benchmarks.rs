@@ -0,0 +1,60 @@
+// Checks that the benchmark corpus and workloads measure what they claim to
+
+#[path = "../benches/corpus.rs"]
+mod corpus;
+
+use file_rename_plus::rename::{apply_find_replace, detect_conflicts, FindOptions};
+use file_rename_plus::types::FileEntry;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+fn entry(folder: &Path, name: &str) -> FileEntry {
+    FileEntry {
+        path: folder.join(name),
+        name: Arc::new(name.to_string()),
+        identity: None,
+        pinned: false,
+        added: 0,
+    }
+}
+
+#[test]
+fn benchmark_workloads_do_what_they_measure() {
+    // The generator is seeded, so runs compare like with like
+    let files = corpus::corpus(2_000);
+    let again = corpus::corpus(2_000);
+    assert!(files.iter().zip(&again).all(|(a, b)| a.path == b.path));
+    let folders: BTreeSet<&Path> = files.iter().filter_map(|f| f.path.parent()).collect();
+    assert!(folders.len() > 1);
+
+    // The copy-marker pattern strips the markers and keeps the last extension
+    let regex = FindOptions {
+        use_regex: true,
+        ..FindOptions::default()
+    };
+    let sample = [
+        entry(Path::new("/bench"), "invoice (12).pdf"),
+        entry(Path::new("/bench"), "scan - Copy 3.txt"),
+        entry(Path::new("/bench"), "IMG_0001.jpg"),
+    ];
+    let previews = apply_find_replace(
+        &sample,
+        r"^(.+?)(?:\s+-\s+copy\s+\d+|\s*\(\d+\))(\.[^.]*)$",
+        "$1$2",
+        "",
+        regex,
+        &HashMap::new(),
+        None,
+    )
+    .expect("preview");
+    let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+    assert_eq!(names, ["invoice.pdf", "scan.txt"]);
+
+    // Dropping every digit leaves plenty of clashes for conflict detection to find
+    let mut previews =
+        apply_find_replace(&files, r"\d", "", "", regex, &HashMap::new(), None).expect("preview");
+    detect_conflicts(&mut previews);
+    let clashing = previews.iter().filter(|p| !p.conflicts.is_empty()).count();
+    assert!(clashing > files.len() / 2, "{} clashes", clashing);
+}
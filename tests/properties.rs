@@ -0,0 +1,340 @@
+// Invariants of the rename engine checked against generated names, Unicode included.
+// Names that aren't valid UTF-8 can't be listed in the app at all, so they're out of scope.
+
+use file_rename_plus::file_ops::{is_reserved_name, validate_and_rename};
+use file_rename_plus::rename::{
+    align_stems, apply_extension_change, apply_find_replace, apply_iteration_numbering,
+    check_against_list, detect_conflicts, lint_template, FindOptions, NumberingOptions,
+};
+use file_rename_plus::types::{ConflictReason, FileEntry, NumberGrouping, RenamePreview};
+use proptest::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn entry(folder: &Path, name: &str) -> FileEntry {
+    FileEntry {
+        path: folder.join(name),
+        name: Arc::new(name.to_string()),
+        identity: None,
+        pinned: false,
+        added: 0,
+    }
+}
+
+fn preview(folder: &Path, from: &str, to: &str) -> RenamePreview {
+    RenamePreview {
+        original_path: folder.join(from),
+        original_name: Arc::new(from.to_string()),
+        new_name: to.to_string(),
+        conflicts: Vec::new(),
+        invalid_reason: None,
+        match_count: 1,
+        edited: false,
+        warnings: Vec::new(),
+    }
+}
+
+// Any printable text, separators included, for names that only go through the engine
+fn any_name() -> impl Strategy<Value = String> {
+    "\\PC{1,16}"
+}
+
+// Names a filesystem accepts as they are: no separators, NUL or dot-only names, nothing in
+// the reserved namespace, and short enough in bytes for every common filesystem
+fn disk_name() -> impl Strategy<Value = String> {
+    "[^/\\\\\\x00-\\x1f:*?\"<>|]{1,16}".prop_filter("usable file name", |name| {
+        !name.chars().all(|c| c == '.')
+            && name.trim() == name
+            && !name.ends_with('.')
+            && !is_reserved_name(name)
+    })
+}
+
+proptest! {
+    #[test]
+    fn new_names_with_separators_are_flagged(
+        stems in prop::collection::vec((any_name(), any_name()), 1..8),
+        pattern in any_name(),
+        replacement in "[a-z./\\\\]{0,4}",
+    ) {
+        // Every name contains the pattern, so every one is replaced
+        let folder = Path::new("/props");
+        let files: Vec<FileEntry> = stems
+            .iter()
+            .map(|(head, tail)| entry(folder, &format!("{}{}{}", head, pattern, tail)))
+            .collect();
+        let previews = apply_find_replace(
+            &files,
+            &pattern,
+            &replacement,
+            "",
+            FindOptions::default(),
+            &HashMap::new(),
+            None,
+        );
+        for preview in previews.into_iter().flatten() {
+            prop_assert!(
+                !preview.new_name.contains(std::path::is_separator)
+                    || preview.invalid_reason.is_some(),
+                "{:?} isn't flagged",
+                preview.new_name
+            );
+        }
+    }
+
+    #[test]
+    fn replacing_text_with_itself_changes_nothing(
+        names in prop::collection::vec(any_name(), 1..8),
+        // Braces would make the replacement a {folder}, {created} or {hash} token
+        pattern in "[^{}]{1,6}",
+    ) {
+        let folder = Path::new("/props");
+        let files: Vec<FileEntry> = names.iter().map(|n| entry(folder, n)).collect();
+        let options = FindOptions {
+            case_sensitive: true,
+            ..FindOptions::default()
+        };
+        let previews = apply_find_replace(&files, &pattern, &pattern, "", options, &HashMap::new(), None);
+        prop_assert!(previews.is_ok_and(|p| p.is_empty()));
+    }
+
+    #[test]
+    fn conflicts_are_exactly_the_repeated_names(
+        targets in prop::collection::vec("[aAbBéÉ]{1,2}", 1..12),
+    ) {
+        let folder = Path::new("/props");
+        let mut previews: Vec<RenamePreview> = targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| preview(folder, &format!("file{}", i), target))
+            .collect();
+        detect_conflicts(&mut previews);
+        for preview in &previews {
+            let key = preview.new_name.to_lowercase();
+            let repeated = targets.iter().filter(|t| t.to_lowercase() == key).count() > 1;
+            prop_assert_eq!(
+                preview.conflicts.contains(&ConflictReason::DuplicateInBatch),
+                repeated,
+                "{:?}",
+                preview.new_name
+            );
+        }
+    }
+
+    #[test]
+    fn fitted_padding_sorts_in_numbering_order(
+        count in 1usize..150,
+        start_number in 0u32..2000,
+        padding in 0usize..3,
+    ) {
+        let folder = Path::new("/props");
+        let files: Vec<FileEntry> = (0..count)
+            .map(|i| entry(folder, &format!("{}.jpg", i)))
+            .collect();
+        let options = NumberingOptions {
+            start_number,
+            padding,
+            fit_padding: true,
+            ..NumberingOptions::default()
+        };
+        let previews = apply_iteration_numbering(&files, "img_{n}", &options, None)
+            .expect("numbering");
+        let names: Vec<&str> = previews.iter().map(|p| p.new_name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        prop_assert_eq!(&sorted, &names);
+        let widths: BTreeSet<usize> = names.iter().map(|n| n.len()).collect();
+        prop_assert_eq!(widths.len(), 1);
+        // Never narrower than asked for
+        prop_assert!(names[0].len() >= "img_.jpg".len() + padding);
+    }
+
+    #[test]
+    fn aligned_stems_line_up_extensions(
+        stems in prop::collection::vec("[a-zé_ ]{1,12}", 1..8),
+        extensions in prop::collection::vec("\\.[a-z]{1,4}|", 8),
+    ) {
+        let names: Vec<String> = stems
+            .iter()
+            .zip(&extensions)
+            .map(|(stem, extension)| format!("{}{}", stem, extension))
+            .collect();
+        let refs: Vec<&str> = names.iter().map(String::as_str).collect();
+        let aligned = align_stems(&refs, false);
+        let longest = stems.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+        for ((line, name), extension) in aligned.iter().zip(&names).zip(&extensions) {
+            // Only spaces are added, between the stem and the extension
+            prop_assert_eq!(line.chars().count() - extension.len(), longest);
+            prop_assert!(line.starts_with(&name[..name.len() - extension.len()]));
+            prop_assert!(line.ends_with(extension.as_str()));
+            prop_assert_eq!(line.replace(' ', ""), name.replace(' ', ""));
+        }
+    }
+
+    #[test]
+    fn removing_extensions_keeps_stems_and_flags_collisions(
+        stems in prop::collection::vec("[a-zA-Z0-9 _-]{1,8}", 1..6),
+        extensions in prop::collection::vec("[a-z]{1,4}", 1..4),
+        compound in any::<bool>(),
+    ) {
+        let folder = Path::new("/props");
+        let mut files = Vec::new();
+        for stem in &stems {
+            for extension in &extensions {
+                files.push(entry(folder, &format!("{}.{}", stem, extension)));
+            }
+            // No extension, and a dotfile: removing leaves both alone
+            files.push(entry(folder, stem));
+            files.push(entry(folder, &format!(".{}", stem)));
+        }
+        files.push(entry(folder, "archive.tar.gz"));
+        let previews = apply_extension_change(&files, "", compound);
+
+        let expected_archive = if compound { "archive" } else { "archive.tar" };
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for preview in &previews {
+            let name = preview.original_name.as_str();
+            prop_assert!(!name.starts_with('.') && name.contains('.'), "{:?} changed", name);
+            if name == "archive.tar.gz" {
+                prop_assert_eq!(preview.new_name.as_str(), expected_archive);
+            } else {
+                prop_assert_eq!(preview.new_name.as_str(), name.rsplit_once('.').unwrap().0);
+            }
+            *counts.entry(preview.new_name.to_lowercase()).or_insert(0) += 1;
+        }
+        prop_assert_eq!(previews.len(), stems.len() * extensions.len() + 1);
+        // "a.txt" and "a.dat" both becoming "a" is a conflict
+        for preview in &previews {
+            prop_assert_eq!(
+                preview.conflicts.contains(&ConflictReason::DuplicateInBatch),
+                counts[&preview.new_name.to_lowercase()] > 1,
+                "{:?}",
+                preview.new_name
+            );
+        }
+    }
+
+    #[test]
+    fn name_list_check_ignores_order_and_finds_each_difference(
+        names in prop::collection::btree_set(any_name(), 1..8),
+        rotation in 0usize..8,
+        dropped in any::<prop::sample::Index>(),
+        extra in "[a-z]{17,20}",
+    ) {
+        let folder = Path::new("/props");
+        let names: Vec<String> = names.into_iter().collect();
+        let previews: Vec<RenamePreview> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| preview(folder, &format!("file{}", i), name))
+            .collect();
+        let mut expected: Vec<&str> = names.iter().map(String::as_str).collect();
+        let shift = rotation % expected.len();
+        expected.rotate_left(shift);
+        prop_assert!(check_against_list(&previews, &expected).is_match());
+
+        // One name swapped for another no file gets: missing, and its row not listed
+        let i = dropped.index(expected.len());
+        let removed = expected[i];
+        expected[i] = &extra;
+        expected.push(&extra);
+        let check = check_against_list(&previews, &expected);
+        prop_assert_eq!(check.missing, vec![extra.clone()]);
+        prop_assert_eq!(check.repeated, vec![extra.clone()]);
+        let unexpected: Vec<&str> = check.unexpected.iter().map(|(_, to)| to.as_str()).collect();
+        prop_assert_eq!(unexpected, vec![removed]);
+    }
+
+    #[test]
+    fn templates_of_known_placeholders_lint_clean(
+        parts in prop::collection::vec(
+            prop_oneof![
+                Just("{n}".to_string()),
+                Just("{n1}".to_string()),
+                Just("{g}".to_string()),
+                Just("{g:2}".to_string()),
+                "[a-z_ -]{1,4}",
+            ],
+            0..6,
+        ),
+    ) {
+        let template = format!("img{{n}}{}", parts.concat());
+        let lint = lint_template(&template, 1, NumberGrouping::Folder);
+        prop_assert!(lint.problems.is_empty(), "{:?}", lint.problems);
+        prop_assert_eq!(lint.placeholders[0].as_str(), "{n}");
+    }
+
+    #[test]
+    fn renaming_back_restores_the_folder(
+        names in prop::collection::btree_set(disk_name(), 1..8),
+        fresh in prop::collection::btree_set(disk_name(), 0..4),
+        seed in any::<u64>(),
+        ordered in any::<bool>(),
+    ) {
+        // One file per name even where the filesystem ignores case
+        let mut seen = BTreeSet::new();
+        let names: Vec<String> = names
+            .into_iter()
+            .filter(|n| seen.insert(n.to_lowercase()))
+            .collect();
+        let mut targets: Vec<String> = names
+            .iter()
+            .cloned()
+            .chain(fresh.into_iter().filter(|n| seen.insert(n.to_lowercase())))
+            .collect();
+        // A seeded shuffle covers swaps and cycles as well as moves to fresh names
+        let mut state = seed | 1;
+        for i in (1..targets.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            targets.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let folder = dir.path();
+        for (i, name) in names.iter().enumerate() {
+            fs::write(folder.join(name), i.to_string()).expect("test file");
+        }
+        let before = listing(folder);
+
+        let forward: Vec<RenamePreview> = names
+            .iter()
+            .zip(&targets)
+            .map(|(from, to)| preview(folder, from, to))
+            .collect();
+        let report = validate_and_rename(&forward, ordered, &HashMap::new(), |_, _| {})
+            .expect("forward rename");
+        prop_assert!(report.failed.is_empty());
+
+        // The recorded locations, reversed
+        let back: Vec<RenamePreview> = report
+            .locations
+            .iter()
+            .map(|(source, now)| {
+                let name = |p: &Path| p.file_name().unwrap().to_string_lossy().into_owned();
+                preview(folder, &name(now), &name(source))
+            })
+            .collect();
+        let report = validate_and_rename(&back, ordered, &HashMap::new(), |_, _| {})
+            .expect("inverse rename");
+        prop_assert!(report.failed.is_empty());
+        prop_assert_eq!(listing(folder), before);
+    }
+}
+
+// Names and contents, so a mixed-up pair shows as a difference
+fn listing(folder: &Path) -> Vec<(PathBuf, String)> {
+    let mut files: Vec<(PathBuf, String)> = fs::read_dir(folder)
+        .expect("listing")
+        .map(|entry| {
+            let path = entry.expect("entry").path();
+            let contents = fs::read_to_string(&path).expect("contents");
+            (path, contents)
+        })
+        .collect();
+    files.sort();
+    files
+}